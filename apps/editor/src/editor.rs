@@ -1,8 +1,4 @@
-use serenity::{
-    egui, nalgebra_glm, petgraph,
-    winit::{self, dpi::PhysicalSize},
-    world::NodeMetadata,
-};
+use serenity::{egui, nalgebra_glm, petgraph, winit, world::NodeMetadata};
 
 pub struct Editor {
     broker: Broker,
@@ -16,10 +12,12 @@ pub struct Editor {
     command_history: std::collections::VecDeque<Command>,
     redo_stack: Vec<Command>,
     uniform_scaling: bool,
+    camera_preview: bool,
     physics_world_backup: Option<(
         serenity::physics::PhysicsWorld,
         Vec<serenity::world::Transform>,
     )>,
+    import_task: Option<serenity::import::ImportTask>,
 }
 
 impl Editor {
@@ -42,7 +40,9 @@ impl Editor {
             command_history: std::collections::VecDeque::new(),
             redo_stack: Vec::new(),
             uniform_scaling: true,
+            camera_preview: false,
             physics_world_backup: None,
+            import_task: None,
         }
     }
 
@@ -56,10 +56,17 @@ impl Editor {
             .publish(&Topic::Command.to_string(), Message::Command(Command::Exit));
     }
 
-    fn publish_import_gltf_command(&mut self, path: &str) {
+    fn publish_import_command(&mut self, path: &str) {
         self.broker.publish(
             &Topic::Command.to_string(),
-            Message::Command(Command::ImportGltfFile(path.to_string())),
+            Message::Command(Command::ImportFile(path.to_string())),
+        );
+    }
+
+    fn publish_toast(&mut self, message: &str) {
+        self.broker.publish(
+            &Topic::Toast.to_string(),
+            Message::Toast(message.to_string()),
         );
     }
 
@@ -103,11 +110,12 @@ impl Editor {
                         Command::Exit => {
                             context.should_exit = true;
                         }
-                        Command::ImportGltfFile(path) => {
-                            self.selected = None;
-                            self.redo_stack = Vec::new();
-                            self.command_history = std::collections::VecDeque::new();
-                            context.import_file(&path);
+                        Command::ImportFile(path) => {
+                            // Replacing a running import drops it, which cancels it
+                            self.import_task = Some(serenity::import::ImportTask::spawn(
+                                path,
+                                serenity::gltf::ImportOptions::default(),
+                            ));
                         }
                         Command::Translate(node_index, x, y, z) => {
                             translate_node(context, node_index, x, y, z);
@@ -153,6 +161,65 @@ impl Editor {
         }
     }
 
+    fn poll_import(&mut self, context: &mut serenity::app::Context) {
+        let Some(import_task) = self.import_task.as_mut() else {
+            return;
+        };
+        let Some(result) = import_task.poll() else {
+            return;
+        };
+        let path = import_task.path().display().to_string();
+        self.import_task = None;
+        match result {
+            Ok(world) => {
+                self.selected = None;
+                self.redo_stack = Vec::new();
+                self.command_history = std::collections::VecDeque::new();
+                context.load_world(world);
+                self.publish_toast(&format!("Imported {path}"));
+            }
+            Err(serenity::gltf::ImportError::Cancelled) => {
+                self.publish_toast(&format!("Cancelled importing {path}"));
+            }
+            Err(error) => {
+                self.toasts
+                    .add(error_toast(format!("Failed to import {path}: {error}")));
+            }
+        }
+    }
+
+    /// Shows the running import above the other toasts, with a button to cancel it
+    fn import_progress_ui(&mut self, ui_context: &egui::Context) {
+        let Some(import_task) = self.import_task.as_ref() else {
+            return;
+        };
+        let progress = import_task.progress();
+        egui::Area::new("import_progress")
+            .anchor(egui::Align2::CENTER_BOTTOM, (0.0, -10.0))
+            .order(egui::Order::Foreground)
+            .show(ui_context, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(300.0);
+                    ui.label(format!(
+                        "Importing {}",
+                        import_task
+                            .path()
+                            .file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                    ));
+                    ui.add(
+                        egui::ProgressBar::new(progress.fraction())
+                            .text(progress.to_string())
+                            .animate(true),
+                    );
+                    if ui.button("Cancel").clicked() {
+                        import_task.cancel();
+                    }
+                });
+            });
+    }
+
     fn inspector_transform_grid_ui(
         &mut self,
         ui: &mut egui::Ui,
@@ -234,6 +301,104 @@ impl Editor {
         }
     }
 
+    fn viewport_ui(&mut self, context: &mut serenity::app::Context, ui: &mut egui::Ui) {
+        // The world is rendered offscreen at the size of this panel
+        let rect = ui.max_rect();
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        context.scene_viewport = Some(serenity::render::SceneViewport {
+            x: rect.min.x * pixels_per_point,
+            y: rect.min.y * pixels_per_point,
+            width: (rect.width() * pixels_per_point).round() as u32,
+            height: (rect.height() * pixels_per_point).round() as u32,
+            layer_id: ui.layer_id(),
+        });
+
+        if let Some(texture_id) = context.scene_texture_id {
+            ui.put(
+                rect,
+                egui::Image::new(egui::load::SizedTexture::new(texture_id, rect.size())),
+            );
+        }
+
+        // Responses are only clicked when no other layer is on top of the panel,
+        // and dragging the gizmo never counts as a click
+        let response = ui.interact(rect, ui.id().with("Scene Viewport"), egui::Sense::click());
+        let gizmo_dragged = self.gizmo_ui(context, ui, rect);
+        if response.clicked() && !gizmo_dragged {
+            if let Some(position) = response.interact_pointer_pos() {
+                context.pick_request =
+                    Some(nalgebra_glm::vec2(position.x, position.y) * pixels_per_point);
+            }
+        }
+    }
+
+    /// Draws the gizmo for the selected node, returning whether it is being dragged
+    fn gizmo_ui(
+        &mut self,
+        context: &mut serenity::app::Context,
+        ui: &mut egui::Ui,
+        rect: egui::Rect,
+    ) -> bool {
+        let (Some(selected_graph_node_index), Some(scene_index)) =
+            (self.selected, context.active_scene_index)
+        else {
+            return false;
+        };
+        let scene = &context.world.scenes[scene_index];
+        if scene.graph.node_weight(selected_graph_node_index).is_none() {
+            return false;
+        }
+
+        let model_matrix = context
+            .world
+            .global_transform(&scene.graph, selected_graph_node_index);
+        let aspect_ratio = rect.width() / rect.height().max(1.0);
+        let (_camera_position, projection, view) =
+            serenity::world::create_camera_matrices(&context.world, scene, aspect_ratio);
+
+        let gizmo = egui_gizmo::Gizmo::new(ui.next_auto_id())
+            .view_matrix(view)
+            .projection_matrix(projection)
+            .model_matrix(model_matrix)
+            .viewport(rect)
+            .orientation(self.gizmo_orientation)
+            .mode(self.gizmo_mode);
+
+        // TODO: add gizmo controls back
+        gizmo.interact(ui).is_some()
+    }
+
+    fn update_viewports(&mut self, context: &mut serenity::app::Context) {
+        context.viewports.clear();
+        let (Some(scene_index), Some(selected_graph_node_index), true) = (
+            context.active_scene_index,
+            self.selected,
+            self.camera_preview,
+        ) else {
+            return;
+        };
+        let scene = &context.world.scenes[scene_index];
+        let Some(node_index) = scene.graph.node_weight(selected_graph_node_index) else {
+            return;
+        };
+        if context.world.nodes[*node_index].camera_index.is_none()
+            || selected_graph_node_index == scene.default_camera_graph_node_index
+        {
+            return;
+        }
+        // Picture-in-picture preview of the selected camera
+        context.viewports = vec![
+            serenity::render::Viewport::fullscreen(scene.default_camera_graph_node_index),
+            serenity::render::Viewport {
+                camera_graph_node_index: selected_graph_node_index,
+                x: 0.7,
+                y: 0.7,
+                width: 0.25,
+                height: 0.25,
+            },
+        ];
+    }
+
     fn backup_physics_world(&mut self, context: &mut serenity::app::Context) {
         self.physics_world_backup = Some((
             context.world.physics.clone(),
@@ -249,6 +414,25 @@ impl Editor {
     }
 }
 
+fn add_shape_node(context: &mut serenity::app::Context, shape: serenity::world::Shape) {
+    let Some(scene_index) = context.active_scene_index else {
+        return;
+    };
+    let node_index = context.world.add_node();
+    let metadata_index = context.world.nodes[node_index].metadata_index;
+    context.world.metadata[metadata_index].name = shape.name().to_string();
+    context.world.add_primitive_mesh_to_node(
+        node_index,
+        serenity::world::PrimitiveMesh {
+            shape,
+            color: serenity::nalgebra_glm::vec4(0.7, 0.7, 0.7, 1.0),
+            wireframe: false,
+        },
+    );
+    context.world.scenes[scene_index].graph.add_node(node_index);
+    context.should_sync_context = true;
+}
+
 fn translate_node(context: &mut serenity::app::Context, node_index: usize, x: f32, y: f32, z: f32) {
     let transform_index = context.world.nodes[node_index].transform_index;
     let transform = &mut context.world.transforms[transform_index];
@@ -282,7 +466,11 @@ fn scale_node(context: &mut serenity::app::Context, node_index: usize, x: f32, y
 
 impl serenity::app::State for Editor {
     fn initialize(&mut self, context: &mut serenity::app::Context) {
-        context.import_file("resources/models/Lantern.glb");
+        let path = "resources/models/Lantern.glb";
+        if let Err(error) = context.import_file(path) {
+            self.toasts
+                .add(error_toast(format!("Failed to import {path}: {error}")));
+        }
     }
 
     fn receive_event(
@@ -290,6 +478,23 @@ impl serenity::app::State for Editor {
         context: &mut serenity::app::Context,
         event: &winit::event::Event<()>,
     ) {
+        if let winit::event::Event::WindowEvent {
+            event:
+                winit::event::WindowEvent::MouseInput {
+                    button: winit::event::MouseButton::Left,
+                    state: winit::event::ElementState::Pressed,
+                    ..
+                },
+            ..
+        } = *event
+        {
+            // With the gui visible, clicks are picked by the viewport panel so that
+            // windows, menus and the gizmo drawn over it keep their clicks
+            if !context.gui_visible {
+                context.pick_request = Some(context.io.mouse.position);
+            }
+        }
+
         if let winit::event::Event::WindowEvent {
             event:
                 winit::event::WindowEvent::KeyboardInput {
@@ -347,6 +552,13 @@ impl serenity::app::State for Editor {
 
     fn update(&mut self, context: &mut serenity::app::Context) {
         self.receive_messages(context);
+        self.poll_import(context);
+
+        if let Some(pick_result) = context.pick_result.take() {
+            self.selected = pick_result.graph_node_index;
+        }
+
+        self.update_viewports(context);
 
         if let Some(active_scene_index) = context.active_scene_index {
             let scene = &context.world.scenes[active_scene_index];
@@ -432,60 +644,99 @@ impl serenity::app::State for Editor {
     }
 
     fn ui(&mut self, context: &mut serenity::app::Context, ui_context: &mut egui::Context) {
-        egui::Area::new("viewport").show(ui_context, |ui| {
-            ui.with_layer_id(egui::LayerId::background(), |ui| {
-                if let Some(selected_graph_node_index) = self.selected {
-                    if let Some(scene_index) = context.active_scene_index {
-                        let scene = &context.world.scenes[scene_index];
-
-                        scene.graph.node_indices().for_each(|graph_node_index| {
-                            if graph_node_index != selected_graph_node_index {
-                                return;
-                            }
-
-                            let model_matrix = context
-                                .world
-                                .global_transform(&scene.graph, graph_node_index);
-
-                            ui.group(|ui| {
-                                let PhysicalSize { width, height } = context.window.inner_size();
-                                let aspect_ratio = width as f32 / height.max(1) as f32;
-                                let (_camera_position, projection, view) =
-                                    serenity::world::create_camera_matrices(
-                                        &context.world,
-                                        scene,
-                                        aspect_ratio,
-                                    );
-
-                                let gizmo = egui_gizmo::Gizmo::new(ui.next_auto_id())
-                                    .view_matrix(view)
-                                    .projection_matrix(projection)
-                                    .model_matrix(model_matrix)
-                                    .orientation(self.gizmo_orientation)
-                                    .mode(self.gizmo_mode);
-
-                                if let Some(_gizmo_result) = gizmo.interact(ui) {
-                                    // TODO: add gizmo controls back
-                                }
-                            });
-                        });
-                    }
-                }
-            });
-        });
-
         egui::TopBottomPanel::top("top_panel")
             .resizable(true)
             .show(ui_context, |ui| {
                 egui::menu::bar(ui, |ui| {
                     egui::global_dark_light_mode_switch(ui);
                     ui.menu_button("File", |ui| {
-                        if ui.button("Import asset (gltf/glb)...").clicked() {
+                        if ui
+                            .button("Import asset (gltf/glb/obj/stl/ply)...")
+                            .clicked()
+                        {
                             if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("All supported", &["gltf", "glb", "obj", "stl", "ply"])
                                 .add_filter("GLTF / GLB", &["gltf", "glb"])
+                                .add_filter("OBJ", &["obj"])
+                                .add_filter("STL", &["stl"])
+                                .add_filter("PLY", &["ply"])
                                 .pick_file()
                             {
-                                self.publish_import_gltf_command(&path.display().to_string());
+                                self.publish_import_command(&path.display().to_string());
+                                ui.close_menu();
+                            }
+                        }
+                        if ui.button("Export scene (glb)...").clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("GLB", &["glb"])
+                                .save_file()
+                            {
+                                let scene_index = context.active_scene_index.unwrap_or_default();
+                                match serenity::gltf::export_glb(&context.world, scene_index, &path)
+                                {
+                                    Ok(()) => {
+                                        self.publish_toast(&format!("Exported {}", path.display()))
+                                    }
+                                    Err(error) => {
+                                        self.toasts.add(error_toast(format!(
+                                            "Failed to export {}: {error}",
+                                            path.display()
+                                        )));
+                                    }
+                                }
+                                ui.close_menu();
+                            }
+                        }
+                        if ui.button("Optimize meshes").clicked() {
+                            let report = serenity::optimize::optimize_world(
+                                &mut context.world,
+                                &serenity::optimize::OptimizeOptions::default(),
+                            );
+                            context.should_reload_view = true;
+                            self.publish_toast(&format!("Optimized meshes: {report}"));
+                            ui.close_menu();
+                        }
+                        if ui.button("Generate LODs").clicked() {
+                            serenity::lod::generate_lods(
+                                &mut context.world,
+                                &serenity::lod::LodOptions::default(),
+                            );
+                            context.should_reload_view = true;
+                            let levels = context
+                                .world
+                                .meshes
+                                .iter()
+                                .map(|mesh| mesh.lods.len())
+                                .sum::<usize>();
+                            self.publish_toast(&format!("Generated {levels} levels of detail"));
+                            ui.close_menu();
+                        }
+                        if ui.button("Save viewport screenshot...").clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("PNG", &["png"])
+                                .set_file_name("screenshot.png")
+                                .save_file()
+                            {
+                                context.scene_screenshot_path = Some(path);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+
+                    ui.menu_button("Add", |ui| {
+                        let shapes = [
+                            serenity::world::Shape::Cube,
+                            serenity::world::Shape::sphere(),
+                            serenity::world::Shape::capsule(),
+                            serenity::world::Shape::cylinder(),
+                            serenity::world::Shape::cone(),
+                            serenity::world::Shape::plane(),
+                            serenity::world::Shape::torus(),
+                            serenity::world::Shape::arrow(),
+                        ];
+                        for shape in shapes {
+                            if ui.button(shape.name()).clicked() {
+                                add_shape_node(context, shape);
                                 ui.close_menu();
                             }
                         }
@@ -532,6 +783,17 @@ impl serenity::app::State for Editor {
 
                     ui.separator();
 
+                    ui.checkbox(&mut self.camera_preview, "Camera Preview");
+                    ui.checkbox(&mut context.reverse_z, "Reverse Z");
+                    ui.checkbox(&mut context.lod_debug_colors, "LOD Colors");
+                    ui.add(
+                        egui::DragValue::new(&mut context.point_size)
+                            .clamp_range(1.0..=64.0)
+                            .prefix("Point Size: "),
+                    );
+
+                    ui.separator();
+
                     ui.horizontal(|ui| {
                         if ui
                             .checkbox(&mut context.physics_enabled, "Enable Physics")
@@ -587,7 +849,8 @@ impl serenity::app::State for Editor {
                         egui::ScrollArea::vertical()
                             .id_source(ui.next_auto_id())
                             .show(ui, |ui| {
-                                let transform_index = node.transform_index;
+                                let transform_index =
+                                    context.world.nodes[node_index].transform_index;
                                 let transform = &context.world.transforms[transform_index];
                                 ui.heading("Transform");
                                 egui::Grid::new("node_transform_grid").striped(true).show(
@@ -596,12 +859,34 @@ impl serenity::app::State for Editor {
                                         self.inspector_transform_grid_ui(ui, transform, node_index);
                                     },
                                 );
+                                let bounds = context.world.subtree_aabb(
+                                    &context.world.scenes[scene_index].graph,
+                                    selected_graph_node_index,
+                                );
+                                if !bounds.is_empty() {
+                                    ui.heading("Bounds");
+                                    egui::Grid::new("node_bounds_grid").striped(true).show(
+                                        ui,
+                                        |ui| {
+                                            inspector_vector_row(ui, "Center", &bounds.center());
+                                            inspector_vector_row(ui, "Size", &bounds.extents());
+                                        },
+                                    );
+                                }
+                                inspector_morph_weights_ui(ui, &mut context.world, node_index);
+                                inspector_extras_ui(ui, &context.world, node_index);
                             });
                         ui.allocate_space(ui.available_size());
                     }
                 }
             });
 
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none())
+            .show(ui_context, |ui| {
+                self.viewport_ui(context, ui);
+            });
+
         egui::Window::new("Console")
             .collapsible(true)
             .default_open(false)
@@ -635,6 +920,7 @@ impl serenity::app::State for Editor {
                 });
             });
 
+        self.import_progress_ui(ui_context);
         self.toasts.show(ui_context);
     }
 }
@@ -718,7 +1004,7 @@ impl std::fmt::Display for Topic {
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum Command {
-    ImportGltfFile(String),
+    ImportFile(String),
     Translate(usize, f32, f32, f32),
     Rotate(usize, f32, f32, f32),
     Scale(usize, f32, f32, f32),
@@ -732,6 +1018,103 @@ pub enum Message {
     Toast(String),
 }
 
+fn error_toast(text: String) -> egui_toast::Toast {
+    egui_toast::Toast {
+        text: text.into(),
+        kind: egui_toast::ToastKind::Error,
+        options: egui_toast::ToastOptions::default()
+            .duration_in_seconds(10.0)
+            .show_progress(true),
+    }
+}
+
+fn inspector_morph_weights_ui(
+    ui: &mut egui::Ui,
+    world: &mut serenity::world::World,
+    node_index: usize,
+) {
+    let Some(mesh_index) = world.nodes[node_index].mesh_index else {
+        return;
+    };
+    let number_of_morph_targets = world.meshes[mesh_index]
+        .primitives
+        .iter()
+        .map(|primitive| primitive.number_of_morph_targets)
+        .max()
+        .unwrap_or_default();
+    if number_of_morph_targets == 0 {
+        return;
+    }
+
+    ui.heading("Morph Targets");
+    let mut weights = world.morph_weights(node_index).to_vec();
+    weights.resize(number_of_morph_targets, 0.0);
+    let mut changed = false;
+    egui::Grid::new("node_morph_weights_grid")
+        .striped(true)
+        .show(ui, |ui| {
+            weights.iter_mut().enumerate().for_each(|(index, weight)| {
+                ui.label(format!("Target {index}"));
+                changed |= ui.add(egui::Slider::new(weight, 0.0..=1.0)).changed();
+                ui.end_row();
+            });
+        });
+    // Editing gives the node its own weights rather than changing the mesh's defaults
+    if changed {
+        world.nodes[node_index].morph_weights = weights;
+    }
+}
+
+/// Lists the custom properties of the node along with those of its mesh and materials
+fn inspector_extras_ui(ui: &mut egui::Ui, world: &serenity::world::World, node_index: usize) {
+    let node = &world.nodes[node_index];
+    let mut sources = vec![(
+        "Node".to_string(),
+        &world.metadata[node.metadata_index].extras,
+    )];
+    if let Some(mesh_index) = node.mesh_index {
+        let mesh = &world.meshes[mesh_index];
+        sources.push((format!("Mesh {mesh_index}"), &mesh.extras));
+        let mut material_indices = mesh
+            .primitives
+            .iter()
+            .filter_map(|primitive| primitive.material_index)
+            .collect::<Vec<_>>();
+        material_indices.sort_unstable();
+        material_indices.dedup();
+        material_indices.into_iter().for_each(|material_index| {
+            sources.push((
+                format!("Material {material_index}"),
+                &world.materials[material_index].extras,
+            ));
+        });
+    }
+    sources.retain(|(_, extras)| !extras.is_empty());
+    if sources.is_empty() {
+        return;
+    }
+
+    ui.heading("Extras");
+    egui::Grid::new("node_extras_grid")
+        .striped(true)
+        .show(ui, |ui| {
+            sources.iter().for_each(|(source, extras)| {
+                extras.0.iter().for_each(|(key, value)| {
+                    ui.label(source);
+                    ui.label(key);
+                    ui.label(value.to_string());
+                    ui.end_row();
+                });
+            });
+        });
+}
+
+fn inspector_vector_row(ui: &mut egui::Ui, label: &str, vector: &nalgebra_glm::Vec3) {
+    ui.label(label);
+    ui.label(format!("{:.3}, {:.3}, {:.3}", vector.x, vector.y, vector.z));
+    ui.end_row();
+}
+
 fn node_ui(
     world: &serenity::world::World,
     ui: &mut egui::Ui,
@@ -743,7 +1126,7 @@ fn node_ui(
     egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
         .show_header(ui, |ui| {
             let node_index = graph[graph_node_index];
-            let NodeMetadata { name } = &world.metadata[node_index];
+            let NodeMetadata { name, .. } = &world.metadata[node_index];
             let selected = selected_graph_node_index
                 .as_ref()
                 .map(|index| *index == graph_node_index)
@@ -8,19 +8,46 @@ pub struct Context {
     pub should_reload_view: bool,
     pub should_sync_context: bool,
     pub physics_enabled: bool,
+    /// Tints meshes by the level of detail they are drawn at
+    pub lod_debug_colors: bool,
+    /// The width of point primitives in pixels
+    pub point_size: f32,
     pub gui_visible: bool,
     pub debug_visible: bool,
+    /// Renders with a reversed depth range for better precision in large scenes.
+    /// Disable to fall back to a standard depth range.
+    pub reverse_z: bool,
     pub active_scene_index: Option<usize>,
+    /// Cameras to render and the regions they cover.
+    /// When empty, the active scene's default camera covers the whole window.
+    pub viewports: Vec<crate::render::Viewport>,
+    /// Set to a window position in physical pixels to read back the node under it
+    pub pick_request: Option<nalgebra_glm::Vec2>,
+    pub pick_result: Option<crate::view::PickResult>,
+    /// When set, the world is rendered into an offscreen texture of this size
+    /// that the gui displays using `scene_texture_id`, instead of behind the gui
+    pub scene_viewport: Option<crate::render::SceneViewport>,
+    pub scene_texture_id: Option<egui::TextureId>,
+    /// Set to save the offscreen scene texture to an image file
+    pub scene_screenshot_path: Option<std::path::PathBuf>,
 }
 
 impl Context {
-    pub fn import_file(&mut self, path: &str) {
-        self.world = crate::gltf::import_gltf(path);
+    /// Replaces the world with an imported file, keeping the current world if the import fails
+    pub fn import_file(&mut self, path: &str) -> Result<(), crate::gltf::ImportError> {
+        self.load_world(crate::import::import_file(path)?);
+        Ok(())
+    }
+
+    /// Replaces the world, such as with the result of an `ImportTask`, and shows its first scene
+    pub fn load_world(&mut self, world: crate::world::World) {
+        self.world = world;
 
         if self.world.scenes.is_empty() {
             self.world.scenes.push(crate::world::Scene::default());
         }
         self.active_scene_index = Some(0);
+        self.viewports.clear();
 
         if let Some(scene_index) = self.active_scene_index {
             self.add_bounding_boxes(scene_index);
@@ -38,6 +65,7 @@ impl Context {
                 let primitive_mesh = crate::world::PrimitiveMesh {
                     shape: crate::world::Shape::Cube,
                     color: nalgebra_glm::vec4(0.983, 0.486, 0.0, 1.0),
+                    wireframe: true,
                 };
                 self.world
                     .add_primitive_mesh_to_node(node_index, primitive_mesh);
@@ -50,6 +78,33 @@ pub fn window_aspect_ratio(window: &winit::window::Window) -> f32 {
     width as f32 / height.max(1) as f32
 }
 
+/// Mouse input over the offscreen scene viewport is forwarded to the app
+/// even though the gui panel displaying it would otherwise consume it
+fn scene_viewport_receives_event(
+    context: &Context,
+    gui_context: &egui::Context,
+    event: &winit::event::WindowEvent,
+) -> bool {
+    let Some(scene_viewport) = context.scene_viewport else {
+        return false;
+    };
+    match event {
+        // The cursor position is always tracked so presses can be routed,
+        // and releases always delivered so buttons don't get stuck
+        winit::event::WindowEvent::CursorMoved { .. }
+        | winit::event::WindowEvent::MouseInput {
+            state: winit::event::ElementState::Released,
+            ..
+        } => true,
+        winit::event::WindowEvent::MouseInput { .. }
+        | winit::event::WindowEvent::MouseWheel { .. } => {
+            scene_viewport.is_exposed_at(gui_context, context.io.mouse.position)
+                && !gui_context.is_using_pointer()
+        }
+        _ => false,
+    }
+}
+
 pub trait State {
     /// Called once before the main loop
     fn initialize(&mut self, _context: &mut Context) {}
@@ -91,9 +146,18 @@ impl App {
             should_reload_view: false,
             should_sync_context: false,
             physics_enabled: false,
+            lod_debug_colors: false,
+            point_size: 4.0,
             gui_visible: true,
             debug_visible: false,
+            reverse_z: renderer.gpu.reverse_z,
             active_scene_index: None,
+            viewports: Vec::new(),
+            pick_request: None,
+            pick_result: None,
+            scene_viewport: None,
+            scene_texture_id: None,
+            scene_screenshot_path: None,
         };
 
         Self {
@@ -148,11 +212,17 @@ impl App {
                 match &event {
                     winit::event::Event::WindowEvent { event, window_id } => {
                         if *window_id == context.window.id() {
-                            renderer
+                            let consumed = renderer
                                 .gui
                                 .state
                                 .on_event(&renderer.gui.context, event)
-                                .consumed
+                                .consumed;
+                            consumed
+                                && !scene_viewport_receives_event(
+                                    &context,
+                                    &renderer.gui.context,
+                                    event,
+                                )
                         } else {
                             false
                         }
@@ -173,6 +243,11 @@ impl App {
             }
 
             if let winit::event::Event::MainEventsCleared = event {
+                if context.reverse_z != renderer.gpu.reverse_z {
+                    renderer.set_reverse_z(context.reverse_z);
+                    context.should_reload_view = true;
+                }
+
                 if context.should_reload_view {
                     context.should_sync_context = true;
                     renderer.sync_world(&context.world);
@@ -209,6 +284,9 @@ impl App {
                 renderer.render_frame(&mut context, |context, ui| {
                     if context.gui_visible {
                         state.ui(context, ui);
+                    } else {
+                        // Nothing would display an offscreen scene texture
+                        context.scene_viewport = None;
                     }
                 });
             }
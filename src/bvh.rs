@@ -0,0 +1,846 @@
+use nalgebra_glm as glm;
+
+const MAX_TRIANGLES_PER_LEAF: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ray {
+    pub origin: glm::Vec3,
+    /// Always normalized, so distances along the ray are in world units
+    pub direction: glm::Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: glm::Vec3, direction: glm::Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> glm::Vec3 {
+        self.origin + self.direction * distance
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+pub struct BvhNode {
+    pub aabb: crate::world::AxisAlignedBoundingBox,
+    /// For leaves, the first triangle. For interior nodes, the index of the second child,
+    /// as the first child always directly follows its parent.
+    pub offset: usize,
+    /// The number of triangles in a leaf, or zero for interior nodes
+    pub count: usize,
+}
+
+impl BvhNode {
+    pub fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BvhTriangle {
+    pub positions: [glm::Vec3; 3],
+    pub primitive_index: usize,
+    /// The index of the triangle within the primitive's triangle list
+    pub triangle_index: usize,
+}
+
+impl BvhTriangle {
+    fn centroid(&self) -> glm::Vec3 {
+        (self.positions[0] + self.positions[1] + self.positions[2]) / 3.0
+    }
+
+    fn transform(&self, transform: &glm::Mat4) -> [glm::Vec3; 3] {
+        self.positions
+            .map(|position| (transform * position.push(1.0)).xyz())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+    pub distance: f32,
+    pub position: glm::Vec3,
+    /// The geometric normal of the triangle, facing against the ray
+    pub normal: glm::Vec3,
+    /// Weights of the triangle's three corners at the hit position
+    pub barycentric: glm::Vec3,
+    pub primitive_index: usize,
+    pub triangle_index: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClosestPoint {
+    pub position: glm::Vec3,
+    pub distance: f32,
+    pub primitive_index: usize,
+    pub triangle_index: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TriangleOverlap {
+    pub primitive_index: usize,
+    pub triangle_index: usize,
+}
+
+/// A bounding volume hierarchy over the triangles of a mesh's primitives in mesh space.
+/// Queries take the mesh instance's transform and are answered in world space.
+#[derive(Default, Debug, Clone)]
+pub struct MeshBvh {
+    pub nodes: Vec<BvhNode>,
+    pub triangles: Vec<BvhTriangle>,
+}
+
+impl MeshBvh {
+    /// Point and line primitives have no triangles and are skipped
+    pub fn new(world: &crate::world::World, mesh: &crate::world::Mesh) -> Self {
+        let mut triangles = Vec::new();
+        mesh.primitives
+            .iter()
+            .enumerate()
+            .for_each(|(primitive_index, primitive)| {
+                let vertices = &world.vertices[primitive.vertex_offset
+                    ..primitive.vertex_offset + primitive.number_of_vertices];
+                let indices = &world.indices
+                    [primitive.index_offset..primitive.index_offset + primitive.number_of_indices];
+                let Some(triangle_indices) = crate::geometry::triangle_list_indices(
+                    primitive.topology,
+                    vertices.len(),
+                    indices,
+                ) else {
+                    return;
+                };
+                triangles.extend(triangle_indices.chunks_exact(3).enumerate().map(
+                    |(triangle_index, triangle)| BvhTriangle {
+                        positions:
+                            [0, 1, 2].map(|corner| vertices[triangle[corner] as usize].position),
+                        primitive_index,
+                        triangle_index,
+                    },
+                ));
+            });
+        Self::from_triangles(triangles)
+    }
+
+    pub fn from_triangles(mut triangles: Vec<BvhTriangle>) -> Self {
+        let mut nodes = Vec::new();
+        if !triangles.is_empty() {
+            let count = triangles.len();
+            build_node(&mut nodes, &mut triangles, 0, count);
+        }
+        Self { nodes, triangles }
+    }
+
+    /// Returns the nearest triangle hit within `max_distance` of the ray's origin
+    pub fn raycast(&self, transform: &glm::Mat4, ray: &Ray, max_distance: f32) -> Option<RayHit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        // The ray is moved into mesh space, where an unnormalized direction
+        // keeps distances along it equal to world space distances
+        let inverse = glm::inverse(transform);
+        let origin = (inverse * ray.origin.push(1.0)).xyz();
+        let direction = (inverse * ray.direction.push(0.0)).xyz();
+        let inverse_direction = direction.map(|component| 1.0 / component);
+
+        let mut nearest: Option<(f32, usize, glm::Vec2)> = None;
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            let limit = nearest.map_or(max_distance, |(distance, ..)| distance);
+            match ray_aabb_distance(&origin, &inverse_direction, &node.aabb) {
+                Some(distance) if distance <= limit => {}
+                _ => continue,
+            }
+            if node.is_leaf() {
+                (node.offset..node.offset + node.count).for_each(|triangle_index| {
+                    let limit = nearest.map_or(max_distance, |(distance, ..)| distance);
+                    if let Some((distance, uv)) = ray_triangle_intersection(
+                        &origin,
+                        &direction,
+                        &self.triangles[triangle_index].positions,
+                    ) {
+                        if distance <= limit {
+                            nearest = Some((distance, triangle_index, uv));
+                        }
+                    }
+                });
+            } else {
+                stack.push(node.offset);
+                stack.push(node_index + 1);
+            }
+        }
+
+        nearest.map(|(distance, triangle_index, uv)| {
+            let triangle = &self.triangles[triangle_index];
+            let [a, b, c] = triangle.transform(transform);
+            let normal = (b - a)
+                .cross(&(c - a))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(|| -ray.direction);
+            RayHit {
+                distance,
+                position: ray.at(distance),
+                normal: if normal.dot(&ray.direction) > 0.0 {
+                    -normal
+                } else {
+                    normal
+                },
+                barycentric: glm::vec3(1.0 - uv.x - uv.y, uv.x, uv.y),
+                primitive_index: triangle.primitive_index,
+                triangle_index: triangle.triangle_index,
+            }
+        })
+    }
+
+    /// Returns every triangle touching the sphere
+    pub fn overlap_sphere(
+        &self,
+        transform: &glm::Mat4,
+        center: &glm::Vec3,
+        radius: f32,
+    ) -> Vec<TriangleOverlap> {
+        let mut overlaps = Vec::new();
+        let radius_squared = radius * radius;
+        self.traverse(
+            |aabb| distance_squared_to_aabb(center, &aabb.transform(transform)) <= radius_squared,
+            |triangle| {
+                let [a, b, c] = triangle.transform(transform);
+                let closest = closest_point_on_triangle(center, &a, &b, &c);
+                if glm::distance2(&closest, center) <= radius_squared {
+                    overlaps.push(TriangleOverlap {
+                        primitive_index: triangle.primitive_index,
+                        triangle_index: triangle.triangle_index,
+                    });
+                }
+            },
+        );
+        overlaps
+    }
+
+    /// Returns the point on the mesh surface nearest to `point`, if one lies within `max_distance`
+    pub fn closest_point(
+        &self,
+        transform: &glm::Mat4,
+        point: &glm::Vec3,
+        max_distance: f32,
+    ) -> Option<ClosestPoint> {
+        let mut closest: Option<ClosestPoint> = None;
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let Some(node) = self.nodes.get(node_index) else {
+                continue;
+            };
+            let limit = closest.map_or(max_distance, |closest| closest.distance);
+            if distance_squared_to_aabb(point, &node.aabb.transform(transform)) > limit * limit {
+                continue;
+            }
+            if node.is_leaf() {
+                self.triangles[node.offset..node.offset + node.count]
+                    .iter()
+                    .for_each(|triangle| {
+                        let [a, b, c] = triangle.transform(transform);
+                        let position = closest_point_on_triangle(point, &a, &b, &c);
+                        let distance = glm::distance(&position, point);
+                        let limit = closest.map_or(max_distance, |closest| closest.distance);
+                        if distance <= limit {
+                            closest = Some(ClosestPoint {
+                                position,
+                                distance,
+                                primitive_index: triangle.primitive_index,
+                                triangle_index: triangle.triangle_index,
+                            });
+                        }
+                    });
+            } else {
+                // The nearer child is visited first so the search radius shrinks sooner
+                let first = node_index + 1;
+                let second = node.offset;
+                let distance_to = |index: usize| {
+                    distance_squared_to_aabb(point, &self.nodes[index].aabb.transform(transform))
+                };
+                if distance_to(first) <= distance_to(second) {
+                    stack.push(second);
+                    stack.push(first);
+                } else {
+                    stack.push(first);
+                    stack.push(second);
+                }
+            }
+        }
+        closest
+    }
+
+    fn traverse(
+        &self,
+        mut visit_node: impl FnMut(&crate::world::AxisAlignedBoundingBox) -> bool,
+        mut visit_triangle: impl FnMut(&BvhTriangle),
+    ) {
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let Some(node) = self.nodes.get(node_index) else {
+                continue;
+            };
+            if !visit_node(&node.aabb) {
+                continue;
+            }
+            if node.is_leaf() {
+                self.triangles[node.offset..node.offset + node.count]
+                    .iter()
+                    .for_each(&mut visit_triangle);
+            } else {
+                stack.push(node.offset);
+                stack.push(node_index + 1);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NodeRayHit {
+    pub graph_node_index: petgraph::graph::NodeIndex,
+    pub hit: RayHit,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NodeClosestPoint {
+    pub graph_node_index: petgraph::graph::NodeIndex,
+    pub closest_point: ClosestPoint,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NodeTriangleOverlap {
+    pub graph_node_index: petgraph::graph::NodeIndex,
+    pub overlap: TriangleOverlap,
+}
+
+/// A BVH for every mesh in a world, queried against the mesh nodes of a scene.
+/// It must be rebuilt when vertex or index data changes, but not when nodes move.
+#[derive(Default, Debug, Clone)]
+pub struct WorldBvh {
+    pub meshes: Vec<MeshBvh>,
+}
+
+impl WorldBvh {
+    pub fn new(world: &crate::world::World) -> Self {
+        Self {
+            meshes: world
+                .meshes
+                .iter()
+                .map(|mesh| MeshBvh::new(world, mesh))
+                .collect(),
+        }
+    }
+
+    pub fn raycast(
+        &self,
+        world: &crate::world::World,
+        scene_index: usize,
+        ray: &Ray,
+        max_distance: f32,
+    ) -> Option<NodeRayHit> {
+        let mut nearest: Option<NodeRayHit> = None;
+        self.mesh_nodes(world, scene_index, |graph_node_index, bvh, transform| {
+            let limit = nearest.map_or(max_distance, |nearest| nearest.hit.distance);
+            if let Some(hit) = bvh.raycast(transform, ray, limit) {
+                nearest = Some(NodeRayHit {
+                    graph_node_index,
+                    hit,
+                });
+            }
+        });
+        nearest
+    }
+
+    pub fn overlap_sphere(
+        &self,
+        world: &crate::world::World,
+        scene_index: usize,
+        center: &glm::Vec3,
+        radius: f32,
+    ) -> Vec<NodeTriangleOverlap> {
+        let mut overlaps = Vec::new();
+        self.mesh_nodes(world, scene_index, |graph_node_index, bvh, transform| {
+            overlaps.extend(
+                bvh.overlap_sphere(transform, center, radius)
+                    .into_iter()
+                    .map(|overlap| NodeTriangleOverlap {
+                        graph_node_index,
+                        overlap,
+                    }),
+            );
+        });
+        overlaps
+    }
+
+    pub fn closest_point(
+        &self,
+        world: &crate::world::World,
+        scene_index: usize,
+        point: &glm::Vec3,
+        max_distance: f32,
+    ) -> Option<NodeClosestPoint> {
+        let mut closest: Option<NodeClosestPoint> = None;
+        self.mesh_nodes(world, scene_index, |graph_node_index, bvh, transform| {
+            let limit = closest.map_or(max_distance, |closest| closest.closest_point.distance);
+            if let Some(closest_point) = bvh.closest_point(transform, point, limit) {
+                closest = Some(NodeClosestPoint {
+                    graph_node_index,
+                    closest_point,
+                });
+            }
+        });
+        closest
+    }
+
+    fn mesh_nodes(
+        &self,
+        world: &crate::world::World,
+        scene_index: usize,
+        mut visit: impl FnMut(petgraph::graph::NodeIndex, &MeshBvh, &glm::Mat4),
+    ) {
+        let graph = &world.scenes[scene_index].graph;
+        graph.node_indices().for_each(|graph_node_index| {
+            let node = &world.nodes[graph[graph_node_index]];
+            let Some(bvh) = node
+                .mesh_index
+                .and_then(|mesh_index| self.meshes.get(mesh_index))
+            else {
+                return;
+            };
+            let transform = world.global_transform(graph, graph_node_index);
+            visit(graph_node_index, bvh, &transform);
+        });
+    }
+}
+
+/// Builds the node covering `triangles[start..end]`, splitting at the median centroid
+/// along the longest axis of the centroid bounds, and returns its index
+fn build_node(
+    nodes: &mut Vec<BvhNode>,
+    triangles: &mut [BvhTriangle],
+    start: usize,
+    end: usize,
+) -> usize {
+    let aabb = crate::world::AxisAlignedBoundingBox::from_points(
+        triangles[start..end]
+            .iter()
+            .flat_map(|triangle| triangle.positions.iter()),
+    );
+    let node_index = nodes.len();
+    nodes.push(BvhNode {
+        aabb,
+        offset: start,
+        count: end - start,
+    });
+    if end - start <= MAX_TRIANGLES_PER_LEAF {
+        return node_index;
+    }
+
+    let centroid_bounds = crate::world::AxisAlignedBoundingBox::from_points(
+        triangles[start..end]
+            .iter()
+            .map(|triangle| triangle.centroid())
+            .collect::<Vec<_>>()
+            .iter(),
+    );
+    let extents = centroid_bounds.extents();
+    let axis = extents.imax();
+    if extents[axis] <= 0.0 {
+        return node_index;
+    }
+
+    let middle = (end - start) / 2;
+    triangles[start..end].select_nth_unstable_by(middle, |a, b| {
+        a.centroid()[axis].total_cmp(&b.centroid()[axis])
+    });
+    let middle = start + middle;
+
+    build_node(nodes, triangles, start, middle);
+    let second = build_node(nodes, triangles, middle, end);
+    nodes[node_index].offset = second;
+    nodes[node_index].count = 0;
+    node_index
+}
+
+/// Returns the distance along the ray to where it enters the box, or zero if it starts inside
+fn ray_aabb_distance(
+    origin: &glm::Vec3,
+    inverse_direction: &glm::Vec3,
+    aabb: &crate::world::AxisAlignedBoundingBox,
+) -> Option<f32> {
+    let mut near = 0.0_f32;
+    let mut far = f32::INFINITY;
+    for axis in 0..3 {
+        let first = (aabb.min[axis] - origin[axis]) * inverse_direction[axis];
+        let second = (aabb.max[axis] - origin[axis]) * inverse_direction[axis];
+        near = near.max(first.min(second));
+        far = far.min(first.max(second));
+    }
+    (near <= far).then_some(near)
+}
+
+/// Möller-Trumbore intersection, accepting hits on either side of the triangle.
+/// Returns the distance along the ray and the barycentric weights of the second and third corners.
+fn ray_triangle_intersection(
+    origin: &glm::Vec3,
+    direction: &glm::Vec3,
+    [a, b, c]: &[glm::Vec3; 3],
+) -> Option<(f32, glm::Vec2)> {
+    let edge_1 = b - a;
+    let edge_2 = c - a;
+    let p = direction.cross(&edge_2);
+    let determinant = edge_1.dot(&p);
+    if determinant.abs() <= f32::EPSILON * edge_1.norm() * edge_2.norm() {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+    let offset = origin - a;
+    let u = offset.dot(&p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = offset.cross(&edge_1);
+    let v = direction.dot(&q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge_2.dot(&q) * inverse_determinant;
+    (distance >= 0.0).then_some((distance, glm::vec2(u, v)))
+}
+
+fn distance_squared_to_aabb(point: &glm::Vec3, aabb: &crate::world::AxisAlignedBoundingBox) -> f32 {
+    let clamped = glm::clamp_vec(point, &aabb.min, &aabb.max);
+    glm::distance2(&clamped, point)
+}
+
+/// Finds the closest point by the Voronoi region of the triangle the point falls in
+/// (Real-Time Collision Detection, 5.1.5)
+fn closest_point_on_triangle(
+    point: &glm::Vec3,
+    a: &glm::Vec3,
+    b: &glm::Vec3,
+    c: &glm::Vec3,
+) -> glm::Vec3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = point - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+
+    let bp = point - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = point - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+
+    /// A small linear congruential generator so the meshes and queries are repeatable
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> f32 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 40) as f32 / (1u64 << 24) as f32
+        }
+
+        fn range(&mut self, minimum: f32, maximum: f32) -> f32 {
+            minimum + (maximum - minimum) * self.next()
+        }
+
+        fn point(&mut self, extent: f32) -> glm::Vec3 {
+            glm::vec3(
+                self.range(-extent, extent),
+                self.range(-extent, extent),
+                self.range(-extent, extent),
+            )
+        }
+    }
+
+    fn triangle(positions: [glm::Vec3; 3], triangle_index: usize) -> super::BvhTriangle {
+        super::BvhTriangle {
+            positions,
+            primitive_index: triangle_index % 2,
+            triangle_index,
+        }
+    }
+
+    fn triangle_soup(random: &mut Random) -> Vec<super::BvhTriangle> {
+        (0..200)
+            .map(|triangle_index| {
+                let center = random.point(5.0);
+                let positions = [0, 1, 2].map(|_| center + random.point(0.75));
+                triangle(positions, triangle_index)
+            })
+            .collect()
+    }
+
+    fn height_field(random: &mut Random) -> Vec<super::BvhTriangle> {
+        let size = 12;
+        let heights = (0..(size + 1) * (size + 1))
+            .map(|_| random.range(-0.5, 0.5))
+            .collect::<Vec<_>>();
+        let position = |x: usize, z: usize| {
+            glm::vec3(
+                x as f32 - size as f32 / 2.0,
+                heights[z * (size + 1) + x],
+                z as f32 - size as f32 / 2.0,
+            )
+        };
+        (0..size * size)
+            .flat_map(|quad| {
+                let (x, z) = (quad % size, quad / size);
+                [
+                    [position(x, z), position(x, z + 1), position(x + 1, z)],
+                    [
+                        position(x + 1, z),
+                        position(x, z + 1),
+                        position(x + 1, z + 1),
+                    ],
+                ]
+            })
+            .enumerate()
+            .map(|(triangle_index, positions)| triangle(positions, triangle_index))
+            .collect()
+    }
+
+    fn transforms() -> [glm::Mat4; 2] {
+        [
+            glm::Mat4::identity(),
+            glm::translation(&glm::vec3(3.0, -1.0, 2.0))
+                * glm::rotation(0.9, &glm::vec3(0.3, 1.0, -0.2).normalize())
+                * glm::scaling(&glm::vec3(1.5, 0.5, 2.0)),
+        ]
+    }
+
+    fn world_triangles(
+        triangles: &[super::BvhTriangle],
+        transform: &glm::Mat4,
+    ) -> Vec<(usize, [glm::Vec3; 3])> {
+        triangles
+            .iter()
+            .map(|triangle| (triangle.triangle_index, triangle.transform(transform)))
+            .collect()
+    }
+
+    fn brute_force_raycast(
+        triangles: &[(usize, [glm::Vec3; 3])],
+        ray: &super::Ray,
+    ) -> Option<(f32, usize)> {
+        triangles
+            .iter()
+            .filter_map(|(triangle_index, positions)| {
+                super::ray_triangle_intersection(&ray.origin, &ray.direction, positions)
+                    .map(|(distance, _)| (distance, *triangle_index))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+
+    fn brute_force_closest_point(triangles: &[(usize, [glm::Vec3; 3])], point: &glm::Vec3) -> f32 {
+        triangles
+            .iter()
+            .map(|(_, [a, b, c])| {
+                glm::distance(&super::closest_point_on_triangle(point, a, b, c), point)
+            })
+            .fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let mut random = Random(7);
+        let meshes = [triangle_soup(&mut random), height_field(&mut random)];
+        meshes.iter().for_each(|triangles| {
+            let bvh = super::MeshBvh::from_triangles(triangles.clone());
+            transforms().iter().for_each(|transform| {
+                let world_triangles = world_triangles(triangles, transform);
+
+                let mut hits = 0;
+                (0..60).for_each(|_| {
+                    let origin = random.point(12.0);
+                    let ray = super::Ray::new(origin, random.point(4.0) - origin);
+                    let expected = brute_force_raycast(&world_triangles, &ray);
+                    let hit = bvh.raycast(transform, &ray, f32::MAX);
+                    assert_eq!(hit.is_some(), expected.is_some());
+                    if let (Some(hit), Some((distance, triangle_index))) = (hit, expected) {
+                        hits += 1;
+                        assert!((hit.distance - distance).abs() < 1e-3);
+                        assert_eq!(hit.triangle_index, triangle_index);
+                        assert!(hit.normal.dot(&ray.direction) <= 0.0);
+                        assert!(glm::distance(&hit.position, &ray.at(distance)) < 1e-3);
+                        // Shorter rays stop before the hit
+                        assert!(bvh.raycast(transform, &ray, distance * 0.99).is_none());
+                    }
+                });
+                assert!(hits > 5);
+
+                (0..30).for_each(|_| {
+                    let center = random.point(8.0);
+                    let radius = random.range(0.25, 2.5);
+                    let mut expected = world_triangles
+                        .iter()
+                        .filter(|(_, [a, b, c])| {
+                            glm::distance(
+                                &super::closest_point_on_triangle(&center, a, b, c),
+                                &center,
+                            ) <= radius
+                        })
+                        .map(|(triangle_index, _)| *triangle_index)
+                        .collect::<Vec<_>>();
+                    let mut overlaps = bvh
+                        .overlap_sphere(transform, &center, radius)
+                        .iter()
+                        .map(|overlap| overlap.triangle_index)
+                        .collect::<Vec<_>>();
+                    expected.sort();
+                    overlaps.sort();
+                    assert_eq!(overlaps, expected);
+                });
+
+                (0..30).for_each(|_| {
+                    let point = random.point(10.0);
+                    let expected = brute_force_closest_point(&world_triangles, &point);
+                    let closest = bvh.closest_point(transform, &point, f32::MAX).unwrap();
+                    assert!((closest.distance - expected).abs() < 1e-3);
+                    assert!((glm::distance(&closest.position, &point) - expected).abs() < 1e-3);
+                    assert!(bvh
+                        .closest_point(transform, &point, expected * 0.99)
+                        .is_none());
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn world_queries_cover_every_instance() {
+        let mut random = Random(11);
+        let triangles = height_field(&mut random);
+
+        let mut world = crate::world::World::default();
+        world.vertices = triangles
+            .iter()
+            .flat_map(|triangle| triangle.positions)
+            .map(|position| crate::world::Vertex {
+                position,
+                ..Default::default()
+            })
+            .collect();
+        world.meshes.push(crate::world::Mesh {
+            primitives: vec![crate::world::Primitive {
+                number_of_vertices: world.vertices.len(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        world.scenes.push(crate::world::Scene::default());
+
+        // The second instance is a rotated and scaled child of a translated parent
+        let parent = world.add_node();
+        let child = world.add_node();
+        world.nodes[parent].mesh_index = Some(0);
+        world.nodes[child].mesh_index = Some(0);
+        world.transforms[world.nodes[parent].transform_index].translation =
+            glm::vec3(0.0, -3.0, 1.0);
+        let child_transform = &mut world.transforms[world.nodes[child].transform_index];
+        child_transform.translation = glm::vec3(2.0, 6.0, 0.0);
+        child_transform.rotation = glm::quat_angle_axis(0.6, &glm::Vec3::x());
+        child_transform.scale = glm::vec3(0.5, 2.0, 1.0);
+        let parent_graph_node_index = world.scenes[0].graph.add_node(parent);
+        world.add_child_node(0, parent_graph_node_index, child);
+
+        let graph = &world.scenes[0].graph;
+        let instances = graph
+            .node_indices()
+            .map(|graph_node_index| {
+                let transform = world.global_transform(graph, graph_node_index);
+                (graph_node_index, world_triangles(&triangles, &transform))
+            })
+            .collect::<Vec<_>>();
+
+        let bvh = super::WorldBvh::new(&world);
+        let mut hit_instances = std::collections::HashSet::new();
+        (0..60).for_each(|_| {
+            let origin = random.point(12.0);
+            let ray = super::Ray::new(origin, random.point(4.0) - origin);
+            let expected = instances
+                .iter()
+                .filter_map(|(graph_node_index, triangles)| {
+                    brute_force_raycast(triangles, &ray)
+                        .map(|(distance, _)| (distance, *graph_node_index))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            let hit = bvh.raycast(&world, 0, &ray, f32::MAX);
+            assert_eq!(hit.is_some(), expected.is_some());
+            if let (Some(hit), Some((distance, graph_node_index))) = (hit, expected) {
+                assert!((hit.hit.distance - distance).abs() < 1e-3);
+                assert_eq!(hit.graph_node_index, graph_node_index);
+                hit_instances.insert(graph_node_index);
+            }
+
+            let point = random.point(8.0);
+            let expected = instances
+                .iter()
+                .map(|(_, triangles)| brute_force_closest_point(triangles, &point))
+                .fold(f32::MAX, f32::min);
+            let closest = bvh.closest_point(&world, 0, &point, f32::MAX).unwrap();
+            assert!((closest.closest_point.distance - expected).abs() < 1e-3);
+
+            let radius = random.range(0.5, 2.0);
+            let expected = instances
+                .iter()
+                .map(|(_, triangles)| {
+                    triangles
+                        .iter()
+                        .filter(|(_, [a, b, c])| {
+                            glm::distance(
+                                &super::closest_point_on_triangle(&point, a, b, c),
+                                &point,
+                            ) <= radius
+                        })
+                        .count()
+                })
+                .sum::<usize>();
+            assert_eq!(
+                bvh.overlap_sphere(&world, 0, &point, radius).len(),
+                expected
+            );
+        });
+        assert_eq!(hit_instances.len(), 2);
+    }
+}
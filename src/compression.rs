@@ -0,0 +1,635 @@
+/// Why a block compressed image could not be decoded on the cpu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecompressError {
+    /// The image holds plain pixels rather than compressed blocks
+    NotCompressed,
+    /// The encoding has no cpu decoder
+    Unsupported(crate::world::BlockCompression),
+    /// A mip level holds fewer blocks than its size requires
+    MissingData { level: usize },
+}
+
+impl std::fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotCompressed => write!(f, "The image is not compressed"),
+            Self::Unsupported(compression) => {
+                write!(f, "{compression:?} images cannot be decoded on the cpu")
+            }
+            Self::MissingData { level } => write!(f, "Mip level {level} is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for DecompressError {}
+
+/// Decodes a block compressed image and its mips to rgba8 on the cpu, for gpus that cannot sample it.
+/// Bc6h, bc7 and astc have no cpu decoder and return an error.
+pub fn decompress(image: &crate::world::Image) -> Result<crate::world::Image, DecompressError> {
+    let compression = image.compression.ok_or(DecompressError::NotCompressed)?;
+    let decode_block: fn(&[u8]) -> [[u8; 4]; 16] = match compression {
+        crate::world::BlockCompression::Bc1 => |block| decode_bc1(block, false),
+        crate::world::BlockCompression::Bc2 => decode_bc2,
+        crate::world::BlockCompression::Bc3 => decode_bc3,
+        crate::world::BlockCompression::Bc4 => decode_bc4,
+        crate::world::BlockCompression::Bc5 => decode_bc5,
+        crate::world::BlockCompression::Etc2Rgb8 => |block| decode_etc2(block, false),
+        crate::world::BlockCompression::Etc2Rgb8A1 => |block| decode_etc2(block, true),
+        crate::world::BlockCompression::Etc2Rgba8 => decode_etc2_eac,
+        crate::world::BlockCompression::EacR11 => decode_eac_r11,
+        crate::world::BlockCompression::EacRg11 => decode_eac_rg11,
+        crate::world::BlockCompression::Bc6h
+        | crate::world::BlockCompression::Bc7
+        | crate::world::BlockCompression::Astc { .. } => {
+            return Err(DecompressError::Unsupported(compression))
+        }
+    };
+    let block_size = compression.block_size();
+    let mut levels = std::iter::once(&image.pixels)
+        .chain(image.mips.iter())
+        .enumerate()
+        .map(|(level, blocks)| {
+            let width = (image.width >> level).max(1);
+            let height = (image.height >> level).max(1);
+            decode_blocks(blocks, width, height, block_size, decode_block)
+                .ok_or(DecompressError::MissingData { level })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let pixels = levels.remove(0);
+    Ok(crate::world::Image {
+        pixels,
+        format: crate::world::ImageFormat::R8G8B8A8,
+        width: image.width,
+        height: image.height,
+        mips: levels,
+        compression: None,
+    })
+}
+
+/// Decodes rows of 4x4 blocks, dropping the texels of edge blocks that fall outside the image
+fn decode_blocks(
+    blocks: &[u8],
+    width: u32,
+    height: u32,
+    block_size: usize,
+    decode_block: fn(&[u8]) -> [[u8; 4]; 16],
+) -> Option<Vec<u8>> {
+    let (width, height) = (width as usize, height as usize);
+    let blocks_wide = (width + 3) / 4;
+    let blocks_high = (height + 3) / 4;
+    if blocks.len() < blocks_wide * blocks_high * block_size {
+        return None;
+    }
+    let mut pixels = vec![0; width * height * 4];
+    blocks
+        .chunks_exact(block_size)
+        .take(blocks_wide * blocks_high)
+        .enumerate()
+        .for_each(|(block_index, block)| {
+            let (block_x, block_y) = (block_index % blocks_wide * 4, block_index / blocks_wide * 4);
+            decode_block(block)
+                .iter()
+                .enumerate()
+                .for_each(|(texel_index, texel)| {
+                    let (x, y) = (block_x + texel_index % 4, block_y + texel_index / 4);
+                    if x < width && y < height {
+                        let offset = (y * width + x) * 4;
+                        pixels[offset..offset + 4].copy_from_slice(texel);
+                    }
+                });
+        });
+    Some(pixels)
+}
+
+fn decode_bc1(block: &[u8], opaque: bool) -> [[u8; 4]; 16] {
+    let endpoints = [
+        u16::from_le_bytes([block[0], block[1]]),
+        u16::from_le_bytes([block[2], block[3]]),
+    ];
+    let [first, second] = endpoints.map(rgb565);
+    let mix = |weight_first: u32, weight_second: u32| -> [u8; 4] {
+        let total = weight_first + weight_second;
+        let channel = |channel: usize| {
+            ((first[channel] as u32 * weight_first + second[channel] as u32 * weight_second)
+                / total) as u8
+        };
+        [channel(0), channel(1), channel(2), u8::MAX]
+    };
+    // Without alpha, the second endpoint being larger selects three colors and transparent black
+    let palette = if opaque || endpoints[0] > endpoints[1] {
+        [first, second, mix(2, 1), mix(1, 2)]
+    } else {
+        [first, second, mix(1, 1), [0, 0, 0, 0]]
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    std::array::from_fn(|texel| palette[(indices >> (texel * 2)) as usize & 0b11])
+}
+
+fn rgb565(color: u16) -> [u8; 4] {
+    let (red, green, blue) = ((color >> 11) & 0x1f, (color >> 5) & 0x3f, color & 0x1f);
+    [
+        ((red << 3) | (red >> 2)) as u8,
+        ((green << 2) | (green >> 4)) as u8,
+        ((blue << 3) | (blue >> 2)) as u8,
+        u8::MAX,
+    ]
+}
+
+fn decode_bc2(block: &[u8]) -> [[u8; 4]; 16] {
+    let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+    let mut texels = decode_bc1(&block[8..], true);
+    texels.iter_mut().enumerate().for_each(|(texel, color)| {
+        color[3] = ((alpha >> (texel * 4)) & 0xf) as u8 * 17;
+    });
+    texels
+}
+
+fn decode_bc3(block: &[u8]) -> [[u8; 4]; 16] {
+    let alpha = decode_bc4_channel(&block[..8]);
+    let mut texels = decode_bc1(&block[8..], true);
+    texels
+        .iter_mut()
+        .zip(alpha)
+        .for_each(|(color, alpha)| color[3] = alpha);
+    texels
+}
+
+fn decode_bc4(block: &[u8]) -> [[u8; 4]; 16] {
+    decode_bc4_channel(block).map(|red| [red, 0, 0, u8::MAX])
+}
+
+fn decode_bc5(block: &[u8]) -> [[u8; 4]; 16] {
+    let (red, green) = (
+        decode_bc4_channel(&block[..8]),
+        decode_bc4_channel(&block[8..]),
+    );
+    std::array::from_fn(|texel| [red[texel], green[texel], 0, u8::MAX])
+}
+
+/// Decodes a single channel block, which is shared by bc3 alpha, bc4 and bc5
+fn decode_bc4_channel(block: &[u8]) -> [u8; 16] {
+    let (first, second) = (block[0] as u32, block[1] as u32);
+    let mut palette = [first, second, 0, 0, 0, 0, 0, u8::MAX as u32];
+    if first > second {
+        (1..7).for_each(|step| {
+            palette[step + 1] = ((7 - step) as u32 * first + step as u32 * second) / 7
+        });
+    } else {
+        (1..5).for_each(|step| {
+            palette[step + 1] = ((5 - step) as u32 * first + step as u32 * second) / 5
+        });
+    }
+    let mut indices = [0; 8];
+    indices[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(indices);
+    std::array::from_fn(|texel| palette[(indices >> (texel * 3)) as usize & 0b111] as u8)
+}
+
+const ETC1_MODIFIERS: [[i32; 2]; 8] = [
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+
+const ETC2_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+/// Decodes an etc2 rgb block, including the etc1 compatible modes.
+/// With punchthrough alpha, the differential bit marks opaque blocks instead.
+fn decode_etc2(block: &[u8], punchthrough: bool) -> [[u8; 4]; 16] {
+    let high = u32::from_be_bytes([block[0], block[1], block[2], block[3]]);
+    let low = u32::from_be_bytes([block[4], block[5], block[6], block[7]]);
+    let differential = punchthrough || high & 0b10 != 0;
+    let opaque = !punchthrough || high & 0b10 != 0;
+    let flip = high & 1 != 0;
+
+    // Texel indices are stored column by column, with the low and high bits in separate halves
+    let index = |x: usize, y: usize| {
+        let bit = x * 4 + y;
+        (((low >> (bit + 16)) & 1) << 1 | ((low >> bit) & 1)) as usize
+    };
+    let clamp = |value: i32| value.clamp(0, u8::MAX as i32) as u8;
+    let four_bits = |value: u32| (value & 0xf) as i32 * 17;
+    let paint = |colors: [[i32; 3]; 4]| -> [[u8; 4]; 16] {
+        std::array::from_fn(|texel| {
+            let (x, y) = (texel % 4, texel / 4);
+            let index = index(x, y);
+            if !opaque && index == 2 {
+                return [0, 0, 0, 0];
+            }
+            let [red, green, blue] = colors[index];
+            [clamp(red), clamp(green), clamp(blue), u8::MAX]
+        })
+    };
+
+    let base = |shift: u32| ((high >> shift) & 0x1f) as i32;
+    let delta = |shift: u32| (((high >> shift) & 0b111) as i32) << 29 >> 29;
+    let overflows = |shift: u32| !(0..32).contains(&(base(shift + 3) + delta(shift)));
+
+    if differential && overflows(24) {
+        // T mode, with one color and a second spread by a distance
+        let red = ((high >> 27) & 0b11) << 2 | ((high >> 24) & 0b11);
+        let first = [four_bits(red), four_bits(high >> 20), four_bits(high >> 16)];
+        let second = [
+            four_bits(high >> 12),
+            four_bits(high >> 8),
+            four_bits(high >> 4),
+        ];
+        let distance = ETC2_DISTANCES[(((high >> 2) & 0b11) << 1 | (high & 1)) as usize];
+        return paint([
+            first,
+            second.map(|channel| channel + distance),
+            second,
+            second.map(|channel| channel - distance),
+        ]);
+    }
+
+    if differential && overflows(16) {
+        // H mode, with two colors each spread by a distance
+        let first = [
+            (high >> 27) & 0xf,
+            ((high >> 24) & 0b111) << 1 | ((high >> 20) & 1),
+            ((high >> 19) & 1) << 3 | ((high >> 16) & 0b11) << 1 | ((high >> 15) & 1),
+        ];
+        let second = [
+            (high >> 11) & 0xf,
+            ((high >> 8) & 0b111) << 1 | ((high >> 7) & 1),
+            (high >> 3) & 0xf,
+        ];
+        let packed = |color: [u32; 3]| color[0] << 8 | color[1] << 4 | color[2];
+        let distance_index =
+            ((high >> 2) & 1) << 2 | (high & 1) << 1 | (packed(first) >= packed(second)) as u32;
+        let distance = ETC2_DISTANCES[distance_index as usize];
+        let (first, second) = (first.map(four_bits), second.map(four_bits));
+        return paint([
+            first.map(|channel| channel + distance),
+            first.map(|channel| channel - distance),
+            second.map(|channel| channel + distance),
+            second.map(|channel| channel - distance),
+        ]);
+    }
+
+    if differential && overflows(8) {
+        // Planar mode, interpolating between an origin, a horizontal and a vertical color
+        let six_bits = |value: u32| ((value & 0x3f) << 2 | (value & 0x3f) >> 4) as i32;
+        let seven_bits = |value: u32| ((value & 0x7f) << 1 | (value & 0x7f) >> 6) as i32;
+        let origin = [
+            six_bits(high >> 25),
+            seven_bits(((high >> 24) & 1) << 6 | ((high >> 17) & 0x3f)),
+            six_bits(((high >> 16) & 1) << 5 | ((high >> 11) & 0b11) << 3 | ((high >> 7) & 0b111)),
+        ];
+        let horizontal = [
+            six_bits(((high >> 2) & 0x1f) << 1 | (high & 1)),
+            seven_bits(low >> 25),
+            six_bits(low >> 19),
+        ];
+        let vertical = [six_bits(low >> 13), seven_bits(low >> 6), six_bits(low)];
+        return std::array::from_fn(|texel| {
+            let (x, y) = ((texel % 4) as i32, (texel / 4) as i32);
+            let channel = |channel: usize| {
+                clamp(
+                    (x * (horizontal[channel] - origin[channel])
+                        + y * (vertical[channel] - origin[channel])
+                        + 4 * origin[channel]
+                        + 2)
+                        >> 2,
+                )
+            };
+            [channel(0), channel(1), channel(2), u8::MAX]
+        });
+    }
+
+    // The etc1 modes, with a base color for each half of the block
+    let colors = if differential {
+        let five_bits = |value: i32| (value << 3 | value >> 2);
+        let first = [base(27), base(19), base(11)];
+        let second = [
+            first[0] + delta(24),
+            first[1] + delta(16),
+            first[2] + delta(8),
+        ];
+        [first.map(five_bits), second.map(five_bits)]
+    } else {
+        [
+            [
+                four_bits(high >> 28),
+                four_bits(high >> 20),
+                four_bits(high >> 12),
+            ],
+            [
+                four_bits(high >> 24),
+                four_bits(high >> 16),
+                four_bits(high >> 8),
+            ],
+        ]
+    };
+    let tables = [(high >> 5) & 0b111, (high >> 2) & 0b111];
+    std::array::from_fn(|texel| {
+        let (x, y) = (texel % 4, texel / 4);
+        let half = if flip { y / 2 } else { x / 2 };
+        let [small, large] = ETC1_MODIFIERS[tables[half] as usize];
+        // Without opacity, the small modifiers are dropped to make room for transparency
+        let small = if opaque { small } else { 0 };
+        let modifier = match index(x, y) {
+            0 => small,
+            1 => large,
+            2 if !opaque => return [0, 0, 0, 0],
+            2 => -small,
+            _ => -large,
+        };
+        let [red, green, blue] = colors[half].map(|channel| clamp(channel + modifier));
+        [red, green, blue, u8::MAX]
+    })
+}
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+/// Decodes an etc2 rgba block, which is an eac alpha block followed by an etc2 rgb block
+fn decode_etc2_eac(block: &[u8]) -> [[u8; 4]; 16] {
+    let alpha = decode_eac_channel(&block[..8], false);
+    let mut texels = decode_etc2(&block[8..], false);
+    texels
+        .iter_mut()
+        .zip(alpha)
+        .for_each(|(color, alpha)| color[3] = alpha);
+    texels
+}
+
+fn decode_eac_r11(block: &[u8]) -> [[u8; 4]; 16] {
+    decode_eac_channel(block, true).map(|red| [red, 0, 0, u8::MAX])
+}
+
+fn decode_eac_rg11(block: &[u8]) -> [[u8; 4]; 16] {
+    let (red, green) = (
+        decode_eac_channel(&block[..8], true),
+        decode_eac_channel(&block[8..], true),
+    );
+    std::array::from_fn(|texel| [red[texel], green[texel], 0, u8::MAX])
+}
+
+/// Decodes a single channel eac block, which is shared by etc2 alpha and the r11 and rg11 formats.
+/// Eleven bit blocks are decoded at full precision and then narrowed to eight bits.
+fn decode_eac_channel(block: &[u8], eleven_bits: bool) -> [u8; 16] {
+    let bits = u64::from_be_bytes(block[..8].try_into().unwrap());
+    let base = block[0] as i32;
+    let multiplier = (block[1] >> 4) as i32;
+    let modifiers = EAC_MODIFIERS[(block[1] & 0xf) as usize];
+    std::array::from_fn(|texel| {
+        // Indices are stored column by column starting from the most significant bits
+        let (x, y) = (texel % 4, texel / 4);
+        let modifier = modifiers[(bits >> (45 - (x * 4 + y) * 3)) as usize & 0b111];
+        if !eleven_bits {
+            return (base + modifier * multiplier).clamp(0, u8::MAX as i32) as u8;
+        }
+        // A zero multiplier steps by single eleven bit units instead of eights
+        let step = if multiplier == 0 { 1 } else { multiplier * 8 };
+        let value = (base * 8 + 4 + modifier * step).clamp(0, 2047);
+        ((value * u8::MAX as i32 + 1023) / 2047) as u8
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    fn decode(compression: crate::world::BlockCompression, block: &[u8]) -> Vec<[u8; 4]> {
+        let image = crate::world::Image {
+            pixels: block.to_vec(),
+            format: crate::world::ImageFormat::R8G8B8A8,
+            width: 4,
+            height: 4,
+            mips: Vec::new(),
+            compression: Some(compression),
+        };
+        let decoded = super::decompress(&image).unwrap();
+        assert_eq!(decoded.format, crate::world::ImageFormat::R8G8B8A8);
+        decoded
+            .pixels
+            .chunks_exact(4)
+            .map(|texel| texel.try_into().unwrap())
+            .collect()
+    }
+
+    // Each row of texels selects the palette entries 0, 1, 2 and 3 in order
+    const BC1_INDICES: [u8; 4] = [0b11_10_01_00; 4];
+
+    #[test]
+    fn bc1_blocks_decode_to_their_reference_texels() {
+        // Red then blue endpoints select four opaque colors
+        let block = [[0x00, 0xf8, 0x1f, 0x00], BC1_INDICES].concat();
+        let row = [
+            [255, 0, 0, 255],
+            [0, 0, 255, 255],
+            [170, 0, 85, 255],
+            [85, 0, 170, 255],
+        ];
+        assert_eq!(
+            decode(crate::world::BlockCompression::Bc1, &block),
+            row.repeat(4)
+        );
+
+        // Blue then red endpoints select three colors and transparent black
+        let block = [[0x1f, 0x00, 0x00, 0xf8], BC1_INDICES].concat();
+        let row = [
+            [0, 0, 255, 255],
+            [255, 0, 0, 255],
+            [127, 0, 127, 255],
+            [0, 0, 0, 0],
+        ];
+        assert_eq!(
+            decode(crate::world::BlockCompression::Bc1, &block),
+            row.repeat(4)
+        );
+
+        // 565 channels are widened by repeating their high bits
+        let block = [0x10, 0x84, 0x10, 0x84, 0, 0, 0, 0];
+        assert_eq!(
+            decode(crate::world::BlockCompression::Bc1, &block),
+            [[132, 130, 132, 255]; 16]
+        );
+    }
+
+    #[test]
+    fn bc3_blocks_decode_to_their_reference_texels() {
+        // Alpha steps through all eight values, the first endpoint being larger
+        let alpha_block = [255, 0, 0x88, 0xc6, 0xfa, 0x88, 0xc6, 0xfa];
+        let color_block = [[0x00, 0xf8, 0x1f, 0x00], BC1_INDICES].concat();
+        let block = [&alpha_block[..], &color_block].concat();
+        let alphas = [255, 0, 218, 182, 145, 109, 72, 36];
+        let colors = [[255, 0, 0], [0, 0, 255], [170, 0, 85], [85, 0, 170]];
+        let expected = (0..16)
+            .map(|texel| {
+                let [red, green, blue] = colors[texel % 4];
+                [red, green, blue, alphas[texel % 8]]
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decode(crate::world::BlockCompression::Bc3, &block),
+            expected
+        );
+    }
+
+    #[test]
+    fn bc5_blocks_decode_to_their_reference_texels() {
+        // Red has the smaller endpoint first, selecting six values plus zero and one,
+        // while green has the larger endpoint first and steps through its palette backwards
+        let block = [
+            [0, 255, 0x88, 0xc6, 0xfa, 0x88, 0xc6, 0xfa],
+            [200, 100, 0x77, 0x39, 0x05, 0x77, 0x39, 0x05],
+        ]
+        .concat();
+        let reds = [0, 255, 51, 102, 153, 204, 0, 255];
+        let greens = [114, 128, 142, 157, 171, 185, 100, 200];
+        let expected = (0..16)
+            .map(|texel| [reds[texel % 8], greens[texel % 8], 0, 255])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decode(crate::world::BlockCompression::Bc5, &block),
+            expected
+        );
+    }
+
+    // Eac indices step through all eight modifiers down each column, continuing into the next column
+    const EAC_INDICES: [u8; 6] = [0x05, 0x39, 0x77, 0x05, 0x39, 0x77];
+
+    fn eac_expected(values: [u8; 8]) -> Vec<u8> {
+        (0..16)
+            .map(|texel| {
+                let (x, y) = (texel % 4, texel / 4);
+                values[(x * 4 + y) % 8]
+            })
+            .collect()
+    }
+
+    // An individual mode block whose rows select +small, +large, -small and -large in order,
+    // with a dark orange left half on table 0 and a light blue right half on table 7
+    const ETC2_INDIVIDUAL_BLOCK: [u8; 8] = [0x82, 0x46, 0x0f, 0x1c, 0xcc, 0xcc, 0xaa, 0xaa];
+
+    fn etc2_individual_expected() -> Vec<[u8; 3]> {
+        let left = [[138, 70, 2], [144, 76, 8], [134, 66, 0], [128, 60, 0]];
+        let right = [[81, 149, 255], [217, 255, 255], [0, 55, 208], [0, 0, 72]];
+        (0..16)
+            .map(|texel| {
+                let (x, y) = (texel % 4, texel / 4);
+                if x < 2 {
+                    left[y]
+                } else {
+                    right[y]
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn etc2_rgb_blocks_decode_to_their_reference_texels() {
+        let expected = etc2_individual_expected()
+            .into_iter()
+            .map(|[red, green, blue]| [red, green, blue, 255])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decode(
+                crate::world::BlockCompression::Etc2Rgb8,
+                &ETC2_INDIVIDUAL_BLOCK
+            ),
+            expected
+        );
+
+        // A planar block whose blue overflows, with red ramping to the right and green ramping down
+        let block = [0x00, 0x00, 0x04, 0x7f, 0x00, 0x00, 0x1f, 0xc0];
+        let ramp = [0, 64, 128, 191];
+        let expected = (0..16)
+            .map(|texel| [ramp[texel % 4], ramp[texel / 4], 0, 255])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decode(crate::world::BlockCompression::Etc2Rgb8, &block),
+            expected
+        );
+    }
+
+    #[test]
+    fn etc2_rgba_blocks_decode_to_their_reference_texels() {
+        // Base 100 with a multiplier of 2 on modifier table 13
+        let alpha_block = [&[100, 0x2d][..], &EAC_INDICES].concat();
+        let block = [&alpha_block[..], &ETC2_INDIVIDUAL_BLOCK].concat();
+        let expected = etc2_individual_expected()
+            .into_iter()
+            .zip(eac_expected([98, 96, 94, 80, 100, 102, 104, 118]))
+            .map(|([red, green, blue], alpha)| [red, green, blue, alpha])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decode(crate::world::BlockCompression::Etc2Rgba8, &block),
+            expected
+        );
+    }
+
+    #[test]
+    fn eac_blocks_decode_to_their_reference_texels() {
+        // Red has base 128 with a multiplier of 2 on table 0, stepping sixteen eleven bit units per modifier.
+        // Green has base 10 with a zero multiplier on table 13, stepping single units.
+        let red_block = [&[128, 0x20][..], &EAC_INDICES].concat();
+        let green_block = [&[10, 0x0d][..], &EAC_INDICES].concat();
+        let reds = eac_expected([122, 116, 110, 98, 132, 138, 144, 156]);
+        let greens = eac_expected([10, 10, 10, 9, 10, 11, 11, 12]);
+
+        assert_eq!(
+            decode(crate::world::BlockCompression::EacR11, &red_block),
+            reds.iter().map(|red| [*red, 0, 0, 255]).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            decode(
+                crate::world::BlockCompression::EacRg11,
+                &[red_block, green_block].concat()
+            ),
+            reds.iter()
+                .zip(greens.iter())
+                .map(|(red, green)| [*red, *green, 0, 255])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn encodings_without_a_cpu_decoder_are_reported() {
+        [
+            crate::world::BlockCompression::Bc6h,
+            crate::world::BlockCompression::Bc7,
+            crate::world::BlockCompression::Astc {
+                block_width: 4,
+                block_height: 4,
+            },
+        ]
+        .into_iter()
+        .for_each(|compression| {
+            let image = crate::world::Image {
+                pixels: vec![0; 16],
+                format: crate::world::ImageFormat::R8G8B8A8,
+                width: 4,
+                height: 4,
+                mips: Vec::new(),
+                compression: Some(compression),
+            };
+            assert_eq!(
+                super::decompress(&image).unwrap_err(),
+                super::DecompressError::Unsupported(compression)
+            );
+        });
+    }
+}
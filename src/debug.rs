@@ -1,32 +1,37 @@
 use crate::world::Shape;
 
+/// Geometry for one distinct shape within the shared debug vertex and index buffers
+#[derive(Debug, Clone)]
+pub struct DebugShapeMesh {
+    pub shape: Shape,
+    pub base_vertex: i32,
+    pub triangles: std::ops::Range<u32>,
+    pub lines: std::ops::Range<u32>,
+}
+
+/// A run of instances sharing a shape and a render mode
+#[derive(Debug, Clone)]
+pub struct DebugDraw {
+    pub shape_mesh_index: usize,
+    pub wireframe: bool,
+    pub instances: std::ops::Range<u32>,
+}
+
 pub struct DebugRender {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
     pub instance_buffer: wgpu::Buffer,
     pub uniform_buffer: wgpu::Buffer,
     pub uniform_bind_group: wgpu::BindGroup,
-    pub cube_pipeline: wgpu::RenderPipeline,
+    pub solid_pipeline: wgpu::RenderPipeline,
+    pub wireframe_pipeline: wgpu::RenderPipeline,
+    pub shape_meshes: Vec<DebugShapeMesh>,
+    pub draws: Vec<DebugDraw>,
 }
 
 impl DebugRender {
     pub fn new(gpu: &crate::gpu::Gpu) -> Self {
-        let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
-            &gpu.device,
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Cube Vertex Buffer"),
-                contents: bytemuck::cast_slice(&VERTICES),
-                usage: wgpu::BufferUsages::VERTEX,
-            },
-        );
-        let index_buffer = wgpu::util::DeviceExt::create_buffer_init(
-            &gpu.device,
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Cube Index Buffer"),
-                contents: bytemuck::cast_slice(&INDICES),
-                usage: wgpu::BufferUsages::INDEX,
-            },
-        );
+        let (vertex_buffer, index_buffer, shape_meshes) = create_shape_buffers(gpu, &[Shape::Cube]);
 
         let instance_buffer = wgpu::util::DeviceExt::create_buffer_init(
             &gpu.device,
@@ -71,7 +76,8 @@ impl DebugRender {
             label: Some("uniform_bind_group"),
         });
 
-        let cube_pipeline = create_cube_pipeline(gpu, &uniform_bind_group_layout);
+        let solid_pipeline = create_shape_pipeline(gpu, &uniform_bind_group_layout, false);
+        let wireframe_pipeline = create_shape_pipeline(gpu, &uniform_bind_group_layout, true);
 
         Self {
             vertex_buffer,
@@ -79,7 +85,10 @@ impl DebugRender {
             instance_buffer,
             uniform_buffer,
             uniform_bind_group,
-            cube_pipeline,
+            solid_pipeline,
+            wireframe_pipeline,
+            shape_meshes,
+            draws: Vec::new(),
         }
     }
 
@@ -88,65 +97,53 @@ impl DebugRender {
         render_pass: &mut wgpu::RenderPass<'rp>,
         gpu: &crate::gpu::Gpu,
         context: &crate::app::Context,
+        viewport: &crate::render::ViewportCamera,
     ) {
-        if let Some(scene_index) = context.active_scene_index {
-            let scene = &context.world.scenes[scene_index];
-
-            let (camera_position, projection, view) =
-                crate::world::create_camera_matrices(&context.world, scene, gpu.aspect_ratio());
-
-            gpu.queue.write_buffer(
-                &self.uniform_buffer,
-                0,
-                bytemuck::cast_slice(&[Uniform {
-                    view,
-                    projection,
-                    camera_position: nalgebra_glm::vec3_to_vec4(&camera_position),
-                }]),
-            );
-
-            render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
-
-            // Render debug shapes
-            render_pass.set_pipeline(&self.cube_pipeline);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-
-            [Shape::Cube].iter().for_each(|shape| {
-                scene.graph.node_indices().for_each(|graph_node_index| {
-                    let node_index = scene.graph[graph_node_index];
-                    let node = &context.world.nodes[node_index];
-
-                    if node.mesh_index.is_none() {
-                        return;
-                    }
-
-                    if let Some(primitive_mesh_index) = node.primitive_mesh_index {
-                        let primitive_mesh = &context.world.primitive_meshes[primitive_mesh_index];
+        if context.active_scene_index.is_none() || self.draws.is_empty() {
+            return;
+        }
 
-                        if &primitive_mesh.shape != shape {
-                            return;
-                        }
+        gpu.queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[Uniform {
+                view: viewport.view,
+                projection: viewport.projection,
+                camera_position: nalgebra_glm::vec3_to_vec4(&viewport.camera_position),
+            }]),
+        );
 
-                        let instance_offset = primitive_mesh_index as u32;
-                        match primitive_mesh.shape {
-                            Shape::Cube => {
-                                render_pass.draw_indexed(
-                                    0..(INDICES.len() as u32),
-                                    0,
-                                    instance_offset..(instance_offset + 1),
-                                );
-                            }
-                        }
-                    }
-                });
-            });
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+
+        // Solid shapes are part of the scene, while wireframes are debug overlays
+        for draw in self.draws.iter() {
+            if draw.wireframe && !context.debug_visible {
+                continue;
+            }
+            let shape_mesh = &self.shape_meshes[draw.shape_mesh_index];
+            if draw.wireframe {
+                render_pass.set_pipeline(&self.wireframe_pipeline);
+                render_pass.draw_indexed(
+                    shape_mesh.lines.clone(),
+                    shape_mesh.base_vertex,
+                    draw.instances.clone(),
+                );
+            } else {
+                render_pass.set_pipeline(&self.solid_pipeline);
+                render_pass.draw_indexed(
+                    shape_mesh.triangles.clone(),
+                    shape_mesh.base_vertex,
+                    draw.instances.clone(),
+                );
+            }
         }
     }
 
     pub fn sync_context(&mut self, context: &crate::app::Context, gpu: &crate::gpu::Gpu) {
-        let mut instance_bindings = Vec::new();
+        let mut instances = Vec::new();
 
         if let Some(scene_index) = context.active_scene_index {
             let scene = &context.world.scenes[scene_index];
@@ -156,35 +153,85 @@ impl DebugRender {
 
                 if let Some(primitive_mesh_index) = node.primitive_mesh_index {
                     let primitive_mesh = &context.world.primitive_meshes[primitive_mesh_index];
-                    match node.aabb_index {
+                    let transform = context
+                        .world
+                        .global_transform(&scene.graph, graph_node_index);
+                    let model = match node.aabb_index {
                         Some(aabb) => {
                             let aabb = &context.world.aabbs[aabb];
-                            let transform = context
-                                .world
-                                .global_transform(&scene.graph, graph_node_index);
-                            let instance_binding = InstanceBinding {
-                                model: (transform
-                                    * nalgebra_glm::translation(&aabb.center())
-                                    * nalgebra_glm::scaling(&(aabb.extents() / 2.0))),
-                                color: primitive_mesh.color,
-                            };
-                            instance_bindings.push(instance_binding);
-                        }
-                        None => {
-                            let model = context
-                                .world
-                                .global_transform(&scene.graph, graph_node_index);
-                            let instance_binding = InstanceBinding {
-                                model,
-                                color: primitive_mesh.color,
-                            };
-                            instance_bindings.push(instance_binding);
+                            transform
+                                * nalgebra_glm::translation(&aabb.center())
+                                * nalgebra_glm::scaling(&(aabb.extents() / 2.0))
                         }
-                    }
+                        None => transform,
+                    };
+                    instances.push((
+                        primitive_mesh.shape,
+                        primitive_mesh.wireframe,
+                        InstanceBinding {
+                            model,
+                            color: primitive_mesh.color,
+                        },
+                    ));
                 }
             });
         }
 
+        let mut shapes = Vec::new();
+        instances.iter().for_each(|(shape, _, _)| {
+            if !shapes.contains(shape) {
+                shapes.push(*shape);
+            }
+        });
+        let shapes_changed = shapes.len() != self.shape_meshes.len()
+            || shapes
+                .iter()
+                .zip(self.shape_meshes.iter())
+                .any(|(shape, shape_mesh)| *shape != shape_mesh.shape);
+        if shapes_changed && !shapes.is_empty() {
+            let (vertex_buffer, index_buffer, shape_meshes) = create_shape_buffers(gpu, &shapes);
+            self.vertex_buffer = vertex_buffer;
+            self.index_buffer = index_buffer;
+            self.shape_meshes = shape_meshes;
+        }
+
+        // Instances are grouped so each shape and render mode is a single instanced draw
+        let shape_mesh_index = |shape: &Shape| {
+            self.shape_meshes
+                .iter()
+                .position(|shape_mesh| shape_mesh.shape == *shape)
+                .unwrap_or_default()
+        };
+        instances.sort_by_key(|(shape, wireframe, _)| (shape_mesh_index(shape), *wireframe));
+
+        let mut draws: Vec<DebugDraw> = Vec::new();
+        instances
+            .iter()
+            .enumerate()
+            .for_each(|(instance_index, (shape, wireframe, _))| {
+                let instance_index = instance_index as u32;
+                let shape_mesh_index = shape_mesh_index(shape);
+                match draws.last_mut() {
+                    Some(draw)
+                        if draw.shape_mesh_index == shape_mesh_index
+                            && draw.wireframe == *wireframe =>
+                    {
+                        draw.instances.end = instance_index + 1;
+                    }
+                    _ => draws.push(DebugDraw {
+                        shape_mesh_index,
+                        wireframe: *wireframe,
+                        instances: instance_index..(instance_index + 1),
+                    }),
+                }
+            });
+        self.draws = draws;
+
+        let instance_bindings = instances
+            .into_iter()
+            .map(|(_, _, instance_binding)| instance_binding)
+            .collect::<Vec<_>>();
+
         if (self.instance_buffer.size() as usize)
             < instance_bindings.len() * std::mem::size_of::<InstanceBinding>()
         {
@@ -206,9 +253,57 @@ impl DebugRender {
     }
 }
 
-fn create_cube_pipeline(
+/// Generates every shape's geometry into one shared vertex buffer and one shared index buffer
+fn create_shape_buffers(
+    gpu: &crate::gpu::Gpu,
+    shapes: &[Shape],
+) -> (wgpu::Buffer, wgpu::Buffer, Vec<DebugShapeMesh>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let shape_meshes = shapes
+        .iter()
+        .map(|shape| {
+            let mesh = crate::shape::create_shape_mesh(shape);
+            let base_vertex = vertices.len() as i32;
+            vertices.extend_from_slice(&mesh.vertices);
+
+            let triangles_start = indices.len() as u32;
+            indices.extend_from_slice(&mesh.triangle_indices);
+            let lines_start = indices.len() as u32;
+            indices.extend_from_slice(&mesh.line_indices);
+
+            DebugShapeMesh {
+                shape: *shape,
+                base_vertex,
+                triangles: triangles_start..lines_start,
+                lines: lines_start..(indices.len() as u32),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
+        &gpu.device,
+        &wgpu::util::BufferInitDescriptor {
+            label: Some("Shape Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        },
+    );
+    let index_buffer = wgpu::util::DeviceExt::create_buffer_init(
+        &gpu.device,
+        &wgpu::util::BufferInitDescriptor {
+            label: Some("Shape Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        },
+    );
+    (vertex_buffer, index_buffer, shape_meshes)
+}
+
+fn create_shape_pipeline(
     gpu: &crate::gpu::Gpu,
     uniform_bind_group_layout: &wgpu::BindGroupLayout,
+    wireframe: bool,
 ) -> wgpu::RenderPipeline {
     let shader_module = gpu
         .device
@@ -231,23 +326,32 @@ fn create_cube_pipeline(
                 module: &shader_module,
                 entry_point: "vertex_main",
                 buffers: &[
-                    Vertex::description(&Vertex::vertex_attributes()),
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<crate::shape::ShapeVertex>()
+                            as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+                    },
                     InstanceBinding::description(&InstanceBinding::vertex_attributes()),
                 ],
             },
             primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::LineList,
+                topology: if wireframe {
+                    wgpu::PrimitiveTopology::LineList
+                } else {
+                    wgpu::PrimitiveTopology::TriangleList
+                },
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Line,
+                cull_mode: (!wireframe).then_some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
                 conservative: false,
                 unclipped_depth: false,
             },
             depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
+                format: crate::gpu::Gpu::DEPTH_FORMAT,
                 depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
+                depth_compare: gpu.depth_compare(),
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
@@ -258,7 +362,11 @@ fn create_cube_pipeline(
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader_module,
-                entry_point: "fragment_main",
+                entry_point: if wireframe {
+                    "fragment_main"
+                } else {
+                    "fragment_solid_main"
+                },
                 targets: &[Some(wgpu::ColorTargetState {
                     format: gpu.surface_format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -277,59 +385,6 @@ pub struct Uniform {
     pub camera_position: nalgebra_glm::Vec4,
 }
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct Vertex {
-    position: nalgebra_glm::Vec3,
-}
-
-impl Vertex {
-    pub fn vertex_attributes() -> Vec<wgpu::VertexAttribute> {
-        wgpu::vertex_attr_array![0 => Float32x3].to_vec()
-    }
-
-    pub fn description(attributes: &[wgpu::VertexAttribute]) -> wgpu::VertexBufferLayout {
-        wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes,
-        }
-    }
-}
-
-const VERTICES: [Vertex; 8] = [
-    Vertex {
-        position: nalgebra_glm::Vec3::new(-1.0, -1.0, -1.0),
-    },
-    Vertex {
-        position: nalgebra_glm::Vec3::new(1.0, -1.0, -1.0),
-    },
-    Vertex {
-        position: nalgebra_glm::Vec3::new(1.0, 1.0, -1.0),
-    },
-    Vertex {
-        position: nalgebra_glm::Vec3::new(-1.0, 1.0, -1.0),
-    },
-    Vertex {
-        position: nalgebra_glm::Vec3::new(-1.0, -1.0, 1.0),
-    },
-    Vertex {
-        position: nalgebra_glm::Vec3::new(1.0, -1.0, 1.0),
-    },
-    Vertex {
-        position: nalgebra_glm::Vec3::new(1.0, 1.0, 1.0),
-    },
-    Vertex {
-        position: nalgebra_glm::Vec3::new(-1.0, 1.0, 1.0),
-    },
-];
-
-const INDICES: [u16; 24] = [
-    0, 1, 1, 2, 2, 3, 3, 0, // Front face
-    4, 5, 5, 6, 6, 7, 7, 4, // Back face
-    0, 4, 1, 5, 2, 6, 3, 7, // Connecting edges
-];
-
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct InstanceBinding {
@@ -364,11 +419,14 @@ var<uniform> ubo: Uniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) world_position: vec3<f32>,
+    @location(2) normal: vec3<f32>,
 };
 
 struct InstanceInput {
@@ -388,9 +446,14 @@ fn vertex_main(vert: VertexInput, instance: InstanceInput) -> VertexOutput {
         instance.model_matrix_3,
     );
 
+    let world_position = model_matrix * vec4(vert.position, 1.0);
+    let normal_matrix = mat3x3<f32>(model_matrix[0].xyz, model_matrix[1].xyz, model_matrix[2].xyz);
+
     var out: VertexOutput;
-    out.position = ubo.projection * ubo.view * model_matrix * vec4(vert.position, 1.0);
+    out.position = ubo.projection * ubo.view * world_position;
     out.color = instance.color;
+    out.world_position = world_position.xyz;
+    out.normal = normal_matrix * vert.normal;
     return out;
 }
 
@@ -398,4 +461,12 @@ fn vertex_main(vert: VertexInput, instance: InstanceInput) -> VertexOutput {
 fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return in.color;
 }
+
+// Solid shapes are lit by a light at the camera so they read clearly from any angle
+@fragment
+fn fragment_solid_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let light_direction = normalize(ubo.camera_position.xyz - in.world_position);
+    let diffuse = max(dot(normalize(in.normal), light_direction), 0.0);
+    return vec4(in.color.rgb * (0.3 + 0.7 * diffuse), in.color.a);
+}
 "#;
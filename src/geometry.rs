@@ -0,0 +1,421 @@
+use nalgebra_glm as glm;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum NormalMode {
+    /// Each triangle gets its own face normal, which unwelds shared vertices.
+    /// This is what the glTF specification requires when normals are missing.
+    #[default]
+    Flat,
+    /// Face normals are area weighted and averaged across every vertex sharing a position
+    Smooth,
+}
+
+/// Returns the primitive's triangles as a triangle list of vertex indices,
+/// or `None` for point and line topologies
+pub fn triangle_list_indices(
+    topology: crate::world::PrimitiveTopology,
+    number_of_vertices: usize,
+    indices: &[u32],
+) -> Option<Vec<u32>> {
+    let indices = if indices.is_empty() {
+        (0..number_of_vertices as u32).collect::<Vec<_>>()
+    } else {
+        indices.to_vec()
+    };
+    match topology {
+        crate::world::PrimitiveTopology::Triangles => Some(
+            indices
+                .chunks_exact(3)
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>(),
+        ),
+        crate::world::PrimitiveTopology::TriangleStrip => Some(
+            (2..indices.len())
+                .flat_map(|index| {
+                    // Every other triangle in a strip has its winding flipped
+                    if index % 2 == 0 {
+                        [indices[index - 2], indices[index - 1], indices[index]]
+                    } else {
+                        [indices[index - 1], indices[index - 2], indices[index]]
+                    }
+                })
+                .collect::<Vec<_>>(),
+        ),
+        crate::world::PrimitiveTopology::TriangleFan => Some(
+            (2..indices.len())
+                .flat_map(|index| [indices[index - 1], indices[index], indices[0]])
+                .collect::<Vec<_>>(),
+        ),
+        _ => None,
+    }
+}
+
+/// Rewrites topologies wgpu cannot draw into ones it can.
+/// Line loops become line strips closed by repeating the first index,
+/// and triangle fans become triangle lists.
+/// Indexed points are unrolled into a plain point list, since points are drawn as instanced quads.
+/// Returns the source vertex of each vertex when the vertices had to be reordered,
+/// so the caller can reorder per-vertex data such as morph targets to match.
+pub fn convert_topology(
+    vertices: &mut Vec<crate::world::Vertex>,
+    indices: &mut Vec<u32>,
+    topology: &mut crate::world::PrimitiveTopology,
+) -> Option<Vec<u32>> {
+    let all_indices = |indices: &[u32]| {
+        if indices.is_empty() {
+            (0..vertices.len() as u32).collect::<Vec<_>>()
+        } else {
+            indices.to_vec()
+        }
+    };
+    match *topology {
+        crate::world::PrimitiveTopology::LineLoop => {
+            let mut loop_indices = all_indices(indices);
+            if let Some(first) = loop_indices.first().copied() {
+                loop_indices.push(first);
+            }
+            *indices = loop_indices;
+            *topology = crate::world::PrimitiveTopology::LineStrip;
+            None
+        }
+        crate::world::PrimitiveTopology::TriangleFan => {
+            *indices =
+                triangle_list_indices(*topology, vertices.len(), indices).unwrap_or_default();
+            *topology = crate::world::PrimitiveTopology::Triangles;
+            None
+        }
+        crate::world::PrimitiveTopology::Points if !indices.is_empty() => {
+            let unrolled = std::mem::take(indices);
+            *vertices = unrolled
+                .iter()
+                .map(|index| vertices[*index as usize])
+                .collect();
+            Some(unrolled)
+        }
+        _ => None,
+    }
+}
+
+/// Replaces the normals of a primitive's vertices.
+/// Flat normals unweld the primitive into an unindexed triangle list, so the vertices,
+/// indices and topology may all change. Point and line primitives are left untouched.
+pub fn generate_normals(
+    vertices: &mut Vec<crate::world::Vertex>,
+    indices: &mut Vec<u32>,
+    topology: &mut crate::world::PrimitiveTopology,
+    normal_mode: NormalMode,
+) {
+    let Some(triangles) = triangle_list_indices(*topology, vertices.len(), indices) else {
+        return;
+    };
+
+    match normal_mode {
+        NormalMode::Flat => {
+            let mut flat_vertices = Vec::with_capacity(triangles.len());
+            triangles.chunks_exact(3).for_each(|triangle| {
+                let corners = [
+                    vertices[triangle[0] as usize],
+                    vertices[triangle[1] as usize],
+                    vertices[triangle[2] as usize],
+                ];
+                let normal = face_normal(
+                    &corners[0].position,
+                    &corners[1].position,
+                    &corners[2].position,
+                )
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(glm::Vec3::y);
+                flat_vertices.extend(corners.into_iter().map(|mut vertex| {
+                    vertex.normal = normal;
+                    vertex
+                }));
+            });
+            *vertices = flat_vertices;
+            indices.clear();
+            *topology = crate::world::PrimitiveTopology::Triangles;
+        }
+        NormalMode::Smooth => {
+            // Normals are shared by position so seams in other attributes stay smooth
+            let position_key = |position: &glm::Vec3| position.map(f32::to_bits);
+            let mut accumulated = std::collections::HashMap::new();
+            triangles.chunks_exact(3).for_each(|triangle| {
+                let [a, b, c] = [triangle[0], triangle[1], triangle[2]]
+                    .map(|index| vertices[index as usize].position);
+                // The unnormalized cross product weights each face by its area
+                let normal = face_normal(&a, &b, &c);
+                [a, b, c].iter().for_each(|position| {
+                    *accumulated
+                        .entry(position_key(position))
+                        .or_insert_with(glm::Vec3::zeros) += normal;
+                });
+            });
+            vertices.iter_mut().for_each(|vertex| {
+                vertex.normal = accumulated
+                    .get(&position_key(&vertex.position))
+                    .and_then(|normal: &glm::Vec3| normal.try_normalize(f32::EPSILON))
+                    .unwrap_or_else(glm::Vec3::y);
+            });
+        }
+    }
+}
+
+/// Generates tangents from the first uv set with MikkTSpace,
+/// storing the bitangent sign in `w`.
+/// Vertices whose corners get different tangents are split, which turns the indices into a triangle list.
+/// Returns the source vertex of each vertex when vertices had to be split,
+/// so the caller can duplicate per-vertex data such as morph targets to match.
+pub fn generate_tangents(
+    vertices: &mut Vec<crate::world::Vertex>,
+    indices: &mut Vec<u32>,
+    topology: &mut crate::world::PrimitiveTopology,
+) -> Option<Vec<u32>> {
+    let triangles = triangle_list_indices(*topology, vertices.len(), indices)?;
+
+    let mut geometry = TangentGeometry {
+        vertices,
+        triangles: &triangles,
+        tangents: vec![None; triangles.len()],
+    };
+    if !mikktspace::generate_tangents(&mut geometry) {
+        log::warn!("Failed to generate tangents");
+        return None;
+    }
+    let corner_tangents = geometry.tangents;
+
+    // The first corner of each vertex keeps the vertex, later corners with a different tangent split it
+    let mut assigned = vec![None; vertices.len()];
+    let mut splits = std::collections::HashMap::new();
+    let mut sources = (0..vertices.len() as u32).collect::<Vec<_>>();
+    let mut split_indices = Vec::with_capacity(triangles.len());
+    for (index, tangent) in triangles.iter().zip(corner_tangents) {
+        let source = *index as usize;
+        let tangent = tangent.unwrap_or_else(|| perpendicular_tangent(&vertices[source].normal));
+        let key = tangent.map(f32::to_bits);
+        let vertex_index = match assigned[source] {
+            None => {
+                assigned[source] = Some(key);
+                vertices[source].tangent = glm::Vec4::from(tangent);
+                *index
+            }
+            Some(assigned_key) if assigned_key == key => *index,
+            Some(_) => *splits.entry((*index, key)).or_insert_with(|| {
+                let mut vertex = vertices[source];
+                vertex.tangent = glm::Vec4::from(tangent);
+                vertices.push(vertex);
+                sources.push(*index);
+                vertices.len() as u32 - 1
+            }),
+        };
+        split_indices.push(vertex_index);
+    }
+
+    // Vertices no triangle references still get a valid tangent frame
+    vertices
+        .iter_mut()
+        .zip(assigned.iter())
+        .filter(|(_, assigned)| assigned.is_none())
+        .for_each(|(vertex, _)| {
+            vertex.tangent = glm::Vec4::from(perpendicular_tangent(&vertex.normal));
+        });
+
+    if splits.is_empty() {
+        return None;
+    }
+    *indices = split_indices;
+    *topology = crate::world::PrimitiveTopology::Triangles;
+    Some(sources)
+}
+
+/// Exposes a triangle list to MikkTSpace and collects the tangent it generates for each corner
+struct TangentGeometry<'a> {
+    vertices: &'a [crate::world::Vertex],
+    triangles: &'a [u32],
+    tangents: Vec<Option<[f32; 4]>>,
+}
+
+impl TangentGeometry<'_> {
+    fn vertex(&self, face: usize, vert: usize) -> &crate::world::Vertex {
+        &self.vertices[self.triangles[face * 3 + vert] as usize]
+    }
+}
+
+impl mikktspace::Geometry for TangentGeometry<'_> {
+    fn num_faces(&self) -> usize {
+        self.triangles.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertex(face, vert).position.into()
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertex(face, vert).normal.into()
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.vertex(face, vert).uv_0.into()
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.tangents[face * 3 + vert] = Some(tangent);
+    }
+}
+
+fn perpendicular_tangent(normal: &glm::Vec3) -> [f32; 4] {
+    let tangent = any_perpendicular(normal);
+    [tangent.x, tangent.y, tangent.z, 1.0]
+}
+
+fn face_normal(a: &glm::Vec3, b: &glm::Vec3, c: &glm::Vec3) -> glm::Vec3 {
+    (b - a).cross(&(c - a))
+}
+
+fn any_perpendicular(normal: &glm::Vec3) -> glm::Vec3 {
+    let axis = if normal.x.abs() < 0.9 {
+        glm::Vec3::x()
+    } else {
+        glm::Vec3::y()
+    };
+    let perpendicular = axis - normal * normal.dot(&axis);
+    if perpendicular.norm_squared() > f32::EPSILON {
+        perpendicular.normalize()
+    } else {
+        glm::Vec3::x()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+
+    fn vertex(position: glm::Vec3, uv: glm::Vec2) -> crate::world::Vertex {
+        crate::world::Vertex {
+            position,
+            normal: glm::Vec3::z(),
+            uv_0: uv,
+            ..Default::default()
+        }
+    }
+
+    /// Two triangles folded along the z axis, facing +x+y and -x+y
+    fn roof() -> (Vec<crate::world::Vertex>, Vec<u32>) {
+        let vertices = [
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(0.0, 1.0, 1.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(-1.0, 0.0, 0.0),
+        ]
+        .into_iter()
+        .map(|position| vertex(position, glm::Vec2::zeros()))
+        .collect();
+        (vertices, vec![0, 1, 2, 1, 0, 3])
+    }
+
+    #[test]
+    fn flat_normals_unweld_each_face() {
+        let (mut vertices, mut indices) = roof();
+        let mut topology = crate::world::PrimitiveTopology::Triangles;
+        super::generate_normals(
+            &mut vertices,
+            &mut indices,
+            &mut topology,
+            super::NormalMode::Flat,
+        );
+
+        assert_eq!(vertices.len(), 6);
+        assert!(indices.is_empty());
+        assert_eq!(topology, crate::world::PrimitiveTopology::Triangles);
+        let right = glm::vec3(1.0, 1.0, 0.0).normalize();
+        let left = glm::vec3(-1.0, 1.0, 0.0).normalize();
+        assert!(vertices[..3]
+            .iter()
+            .all(|vertex| glm::distance(&vertex.normal, &right) < 1e-6));
+        assert!(vertices[3..]
+            .iter()
+            .all(|vertex| glm::distance(&vertex.normal, &left) < 1e-6));
+    }
+
+    #[test]
+    fn smooth_normals_average_shared_positions() {
+        let (mut vertices, mut indices) = roof();
+        let mut topology = crate::world::PrimitiveTopology::Triangles;
+        super::generate_normals(
+            &mut vertices,
+            &mut indices,
+            &mut topology,
+            super::NormalMode::Smooth,
+        );
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices, [0, 1, 2, 1, 0, 3]);
+        // The ridge is shared by both faces of equal area, so it points straight up
+        assert!(vertices[..2]
+            .iter()
+            .all(|vertex| glm::distance(&vertex.normal, &glm::Vec3::y()) < 1e-6));
+        assert!(glm::distance(&vertices[2].normal, &glm::vec3(1.0, 1.0, 0.0).normalize()) < 1e-6);
+        assert!(glm::distance(&vertices[3].normal, &glm::vec3(-1.0, 1.0, 0.0).normalize()) < 1e-6);
+    }
+
+    #[test]
+    fn mirrored_uvs_flip_the_tangent_sign() {
+        let quad = |u_direction: f32| {
+            let mut vertices = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+                .into_iter()
+                .map(|(x, y)| vertex(glm::vec3(x, y, 0.0), glm::vec2(x * u_direction, y)))
+                .collect::<Vec<_>>();
+            let mut indices = vec![0, 1, 2, 0, 2, 3];
+            let mut topology = crate::world::PrimitiveTopology::Triangles;
+            assert_eq!(
+                super::generate_tangents(&mut vertices, &mut indices, &mut topology),
+                None
+            );
+            vertices
+        };
+
+        quad(1.0).iter().for_each(|vertex| {
+            assert!(glm::distance(&vertex.tangent, &glm::vec4(1.0, 0.0, 0.0, 1.0)) < 1e-6);
+        });
+        quad(-1.0).iter().for_each(|vertex| {
+            assert!(glm::distance(&vertex.tangent, &glm::vec4(-1.0, 0.0, 0.0, -1.0)) < 1e-6);
+        });
+    }
+
+    #[test]
+    fn mirrored_uv_seams_split_the_shared_vertices() {
+        // A quad welded along x = 0 whose uvs mirror there, as on a symmetric character texture
+        let mut vertices = [
+            (-1.0, 0.0),
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (-1.0, 1.0),
+            (0.0, 1.0),
+            (1.0, 1.0),
+        ]
+        .into_iter()
+        .map(|(x, y)| vertex(glm::vec3(x, y, 0.0), glm::vec2(f32::abs(x), y)))
+        .collect::<Vec<_>>();
+        let mut indices = vec![0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4];
+        let mut topology = crate::world::PrimitiveTopology::Triangles;
+
+        let sources = super::generate_tangents(&mut vertices, &mut indices, &mut topology);
+
+        // MikkTSpace gives the mirrored half the tangent -x with a negative bitangent sign
+        let left = glm::vec4(-1.0, 0.0, 0.0, -1.0);
+        let right = glm::vec4(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(sources, Some(vec![0, 1, 2, 3, 4, 5, 1, 4]));
+        assert_eq!(indices, [0, 1, 4, 0, 4, 3, 6, 2, 5, 6, 5, 7]);
+        let expected = [left, left, right, left, left, right, right, right];
+        assert_eq!(vertices.len(), expected.len());
+        vertices
+            .iter()
+            .zip(expected.iter())
+            .for_each(|(vertex, expected)| {
+                assert!(glm::distance(&vertex.tangent, expected) < 1e-6);
+            });
+    }
+}
@@ -82,20 +82,16 @@ impl GridRender {
         render_pass: &mut wgpu::RenderPass<'rp>,
         gpu: &crate::gpu::Gpu,
         context: &crate::app::Context,
+        viewport: &crate::render::ViewportCamera,
     ) {
-        if let Some(scene_index) = context.active_scene_index {
-            let scene = &context.world.scenes[scene_index];
-
-            let (camera_position, projection, view) =
-                crate::world::create_camera_matrices(&context.world, scene, gpu.aspect_ratio());
-
+        if context.active_scene_index.is_some() {
             gpu.queue.write_buffer(
                 &self.uniform_buffer,
                 0,
                 bytemuck::cast_slice(&[Uniform {
-                    view,
-                    projection,
-                    camera_position: nalgebra_glm::vec3_to_vec4(&camera_position),
+                    view: viewport.view,
+                    projection: viewport.projection,
+                    camera_position: nalgebra_glm::vec3_to_vec4(&viewport.camera_position),
                 }]),
             );
 
@@ -158,9 +154,9 @@ fn create_line_pipeline(
                 unclipped_depth: false,
             },
             depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
+                format: crate::gpu::Gpu::DEPTH_FORMAT,
                 depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
+                depth_compare: gpu.depth_compare(),
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
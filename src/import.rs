@@ -0,0 +1,186 @@
+/// Imports a file with the importer for its extension, treating unknown extensions as gltf
+pub fn import_file(
+    path: impl AsRef<std::path::Path>,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    import_file_with_progress(path, &crate::gltf::ImportOptions::default(), &mut |_| true)
+}
+
+pub fn import_file_with_progress(
+    path: impl AsRef<std::path::Path>,
+    options: &crate::gltf::ImportOptions,
+    progress: &mut dyn FnMut(crate::gltf::ImportProgress) -> bool,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("obj") => crate::obj::import_obj_with_progress(path, options, progress),
+        Some("ply") => crate::ply::import_ply_with_progress(path, options, progress),
+        Some("stl") => crate::stl::import_stl_with_progress(path, options, progress),
+        _ => crate::gltf::import_gltf_with_progress(path, options, progress),
+    }
+}
+
+/// Adds a scene with a node for each named mesh under a scene root, followed by the main camera,
+/// laid out the way the gltf importer lays out a scene
+pub(crate) fn add_mesh_scene(world: &mut crate::world::World, mesh_names: Vec<String>) {
+    let scene_index = world.scenes.len();
+    world.scenes.push(crate::world::Scene::default());
+
+    let root_node_index = world.add_node();
+    world.metadata[world.nodes[root_node_index].metadata_index].name = "Scene Root".to_string();
+    let root_graph_node_index = world.scenes[scene_index].graph.add_node(root_node_index);
+
+    mesh_names
+        .into_iter()
+        .enumerate()
+        .for_each(|(mesh_index, name)| {
+            let node_index = world.add_node();
+            world.nodes[node_index].mesh_index = Some(mesh_index);
+            world.metadata[world.nodes[node_index].metadata_index].name = name;
+            world.add_child_node(scene_index, root_graph_node_index, node_index);
+        });
+
+    let camera_node_index = world.add_node();
+    world.add_camera_to_node(camera_node_index);
+    world.metadata[world.nodes[camera_node_index].metadata_index].name = "Main Camera".to_string();
+    world.add_child_node(scene_index, root_graph_node_index, camera_node_index);
+    let scene = &mut world.scenes[scene_index];
+    scene.default_camera_graph_node_index =
+        petgraph::graph::NodeIndex::new(scene.graph.node_count() - 1);
+}
+
+/// Generates tangents, optimizes the primitive if the options ask for it
+/// and appends its vertices and indices to the world
+pub(crate) fn add_primitive(
+    world: &mut crate::world::World,
+    mut vertices: Vec<crate::world::Vertex>,
+    mut indices: Vec<u32>,
+    mut topology: crate::world::PrimitiveTopology,
+    material_index: Option<usize>,
+    options: &crate::gltf::ImportOptions,
+) -> crate::world::Primitive {
+    crate::geometry::generate_tangents(&mut vertices, &mut indices, &mut topology);
+    if let Some(optimize_options) = options.optimize.as_ref() {
+        crate::optimize::optimize_primitive(
+            &mut vertices,
+            &mut indices,
+            &mut topology,
+            optimize_options,
+        );
+    }
+
+    let primitive = crate::world::Primitive {
+        topology,
+        material_index,
+        vertex_offset: world.vertices.len(),
+        index_offset: world.indices.len(),
+        number_of_vertices: vertices.len(),
+        number_of_indices: indices.len(),
+        // Points are drawn with a single pipeline that reads full precision vertices
+        vertex_format: if options.full_precision_vertices
+            || topology == crate::world::PrimitiveTopology::Points
+        {
+            crate::world::VertexFormat::Full
+        } else {
+            crate::quantize::select_vertex_format(
+                &vertices,
+                false,
+                crate::quantize::material_texture_size(
+                    material_index.and_then(|index| world.materials.get(index)),
+                    &world.textures,
+                    &world.images,
+                ),
+            )
+        },
+        ..Default::default()
+    };
+    world.vertices.extend(vertices);
+    world.indices.extend(indices);
+    primitive
+}
+
+/// An import running on a worker thread, so large files don't stall the event loop
+pub struct ImportTask {
+    path: std::path::PathBuf,
+    progress: crate::gltf::ImportProgress,
+    cancelled: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    receiver: std::sync::mpsc::Receiver<ImportMessage>,
+}
+
+enum ImportMessage {
+    Progress(crate::gltf::ImportProgress),
+    Finished(Result<crate::world::World, crate::gltf::ImportError>),
+}
+
+impl ImportTask {
+    pub fn spawn(path: impl Into<std::path::PathBuf>, options: crate::gltf::ImportOptions) -> Self {
+        let path = path.into();
+        let cancelled = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let worker_path = path.clone();
+        let worker_cancelled = cancelled.clone();
+        std::thread::spawn(move || {
+            let result =
+                crate::import::import_file_with_progress(&worker_path, &options, &mut |progress| {
+                    // The receiver is gone when the task was dropped, which also cancels it
+                    sender.send(ImportMessage::Progress(progress)).is_ok()
+                        && !worker_cancelled.load(std::sync::atomic::Ordering::Relaxed)
+                });
+            let _ = sender.send(ImportMessage::Finished(result));
+        });
+
+        Self {
+            path,
+            progress: crate::gltf::ImportProgress {
+                stage: crate::gltf::ImportStage::Parsing,
+                completed: 0,
+                total: 1,
+            },
+            cancelled,
+            receiver,
+        }
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// The latest progress received by `poll`
+    pub fn progress(&self) -> crate::gltf::ImportProgress {
+        self.progress
+    }
+
+    /// Stops the import at its next progress report, after which `poll` returns `ImportError::Cancelled`
+    pub fn cancel(&self) {
+        self.cancelled
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Receives the worker's progress without blocking, returning the result once the import has finished
+    pub fn poll(&mut self) -> Option<Result<crate::world::World, crate::gltf::ImportError>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(ImportMessage::Progress(progress)) => self.progress = progress,
+                Ok(ImportMessage::Finished(result)) => return Some(result),
+                Err(std::sync::mpsc::TryRecvError::Empty) => return None,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    return Some(Err(crate::gltf::ImportError::Interrupted))
+                }
+            }
+        }
+    }
+
+    /// Blocks until the import has finished
+    pub fn wait(mut self) -> Result<crate::world::World, crate::gltf::ImportError> {
+        loop {
+            match self.receiver.recv() {
+                Ok(ImportMessage::Progress(progress)) => self.progress = progress,
+                Ok(ImportMessage::Finished(result)) => return result,
+                Err(_) => return Err(crate::gltf::ImportError::Interrupted),
+            }
+        }
+    }
+}
@@ -1,13 +1,25 @@
 pub mod app;
+pub mod bvh;
+pub mod compression;
 pub mod debug;
+pub mod geometry;
 pub mod gltf;
 pub mod gpu;
 pub mod grid;
 pub mod gui;
+pub mod import;
 pub mod io;
+pub mod lod;
+pub mod obj;
+pub mod optimize;
 pub mod physics;
+pub mod ply;
+pub mod quantize;
 pub mod render;
+pub mod shape;
 pub mod sky;
+pub mod stl;
+pub mod texture;
 pub mod view;
 pub mod world;
 
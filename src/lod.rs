@@ -0,0 +1,631 @@
+use nalgebra_glm as glm;
+
+/// Border edges are constrained by planes perpendicular to their faces,
+/// weighted more heavily than the faces themselves so silhouettes hold up
+const BORDER_WEIGHT: f64 = 10.0;
+
+/// Colors used to tint each level of detail in the debug color mode, starting with the full detail mesh
+pub const LOD_DEBUG_COLORS: [[f32; 4]; 5] = [
+    [1.0, 1.0, 1.0, 1.0],
+    [0.2, 0.9, 0.2, 1.0],
+    [0.2, 0.5, 1.0, 1.0],
+    [1.0, 0.8, 0.1, 1.0],
+    [1.0, 0.2, 0.2, 1.0],
+];
+
+#[derive(Debug, Copy, Clone)]
+pub struct LodLevel {
+    /// The fraction of the full detail mesh's indices to simplify down to
+    pub index_ratio: f32,
+    /// The level is drawn once the mesh's projected height falls below this fraction of the viewport height
+    pub screen_size: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LodOptions {
+    /// Levels from most to least detailed
+    pub levels: Vec<LodLevel>,
+    /// The largest error allowed, relative to the mesh's extents
+    pub max_error: f32,
+}
+
+impl Default for LodOptions {
+    fn default() -> Self {
+        Self {
+            levels: vec![
+                LodLevel {
+                    index_ratio: 0.5,
+                    screen_size: 0.25,
+                },
+                LodLevel {
+                    index_ratio: 0.25,
+                    screen_size: 0.1,
+                },
+                LodLevel {
+                    index_ratio: 0.125,
+                    screen_size: 0.04,
+                },
+            ],
+            max_error: 0.05,
+        }
+    }
+}
+
+/// Replaces the levels of detail of every mesh in the world.
+/// Levels share their mesh's vertices and only add indices to the world's index buffer.
+pub fn generate_lods(world: &mut crate::world::World, options: &LodOptions) {
+    let crate::world::World {
+        meshes,
+        vertices,
+        indices,
+        ..
+    } = world;
+    meshes.iter_mut().for_each(|mesh| {
+        mesh.lods = options
+            .levels
+            .iter()
+            .map(|level| {
+                let mut error = 0.0_f32;
+                let primitives = mesh
+                    .primitives
+                    .iter()
+                    .map(|primitive| {
+                        let primitive_vertices = &vertices[primitive.vertex_offset
+                            ..primitive.vertex_offset + primitive.number_of_vertices];
+                        let primitive_indices = &indices[primitive.index_offset
+                            ..primitive.index_offset + primitive.number_of_indices];
+                        let Some(triangles) = crate::geometry::triangle_list_indices(
+                            primitive.topology,
+                            primitive_vertices.len(),
+                            primitive_indices,
+                        ) else {
+                            return primitive.clone();
+                        };
+                        let target_index_count =
+                            (triangles.len() as f32 * level.index_ratio) as usize;
+                        let (simplified, primitive_error) = simplify(
+                            primitive_vertices,
+                            &triangles,
+                            target_index_count,
+                            options.max_error,
+                        );
+                        error = error.max(primitive_error);
+                        let lod_primitive = crate::world::Primitive {
+                            index_offset: indices.len(),
+                            number_of_indices: simplified.len(),
+                            topology: crate::world::PrimitiveTopology::Triangles,
+                            ..primitive.clone()
+                        };
+                        indices.extend(simplified);
+                        lod_primitive
+                    })
+                    .collect::<Vec<_>>();
+                crate::world::MeshLod {
+                    primitives,
+                    screen_size: level.screen_size,
+                    error,
+                }
+            })
+            .collect();
+    });
+}
+
+/// The fraction of the viewport height covered by a bounding sphere
+pub fn projected_screen_size(
+    projection: &glm::Mat4,
+    view: &glm::Mat4,
+    center: &glm::Vec3,
+    radius: f32,
+) -> f32 {
+    let view_center = view * glm::vec4(center.x, center.y, center.z, 1.0);
+    // Perspective projections divide by the distance along the view direction, orthographic ones don't
+    let depth = if projection[(3, 3)] == 0.0 {
+        (-view_center.z).max(f32::EPSILON)
+    } else {
+        1.0
+    };
+    radius * projection[(1, 1)].abs() / depth
+}
+
+/// Simplifies a triangle list with quadric error metrics, collapsing edges onto existing vertices
+/// until `target_index_count` is reached or the next collapse would exceed `max_error`,
+/// which is relative to the extents of the vertices.
+/// Returns the new triangle list and the error it reached.
+pub fn simplify(
+    vertices: &[crate::world::Vertex],
+    indices: &[u32],
+    target_index_count: usize,
+    max_error: f32,
+) -> (Vec<u32>, f32) {
+    if indices.len() <= target_index_count || vertices.is_empty() {
+        return (indices.to_vec(), 0.0);
+    }
+
+    // Error is measured in a normalized space so it is relative to the mesh's size
+    let (minimum, maximum) = vertices.iter().fold(
+        (glm::Vec3::repeat(f32::MAX), glm::Vec3::repeat(f32::MIN)),
+        |(minimum, maximum), vertex| {
+            (
+                glm::min2(&minimum, &vertex.position),
+                glm::max2(&maximum, &vertex.position),
+            )
+        },
+    );
+    let scale = 1.0 / (maximum - minimum).max().max(f32::EPSILON) as f64;
+
+    // Vertices split along attribute seams share a position and are simplified together
+    let mut class_lookup = std::collections::HashMap::new();
+    let mut class_positions: Vec<glm::DVec3> = Vec::new();
+    let mut class_members: Vec<Vec<u32>> = Vec::new();
+    let class_of = vertices
+        .iter()
+        .enumerate()
+        .map(|(vertex_index, vertex)| {
+            let class = *class_lookup
+                .entry(vertex.position.map(f32::to_bits))
+                .or_insert_with(|| {
+                    class_positions.push(
+                        glm::convert::<glm::Vec3, glm::DVec3>(vertex.position - minimum) * scale,
+                    );
+                    class_members.push(Vec::new());
+                    class_positions.len() as u32 - 1
+                });
+            class_members[class as usize].push(vertex_index as u32);
+            class
+        })
+        .collect::<Vec<_>>();
+    let number_of_classes = class_positions.len();
+
+    let mut corners = Vec::new();
+    let mut triangles = Vec::new();
+    indices.chunks_exact(3).for_each(|corner| {
+        let triangle = [corner[0], corner[1], corner[2]].map(|index| class_of[index as usize]);
+        if triangle[0] != triangle[1] && triangle[1] != triangle[2] && triangle[0] != triangle[2] {
+            corners.push([corner[0], corner[1], corner[2]]);
+            triangles.push(triangle);
+        }
+    });
+    let mut alive = vec![true; triangles.len()];
+    let mut alive_count = triangles.len();
+
+    let mut quadrics = vec![Quadric::default(); number_of_classes];
+    let mut class_triangles = vec![Vec::new(); number_of_classes];
+    let mut edge_faces = std::collections::HashMap::new();
+    triangles
+        .iter()
+        .enumerate()
+        .for_each(|(triangle_index, triangle)| {
+            let [a, b, c] = triangle.map(|class| class_positions[class as usize]);
+            let normal = (b - a).cross(&(c - a));
+            let area = normal.norm();
+            if area > 0.0 {
+                let normal = normal / area;
+                let quadric = Quadric::from_plane(&normal, -normal.dot(&a), area);
+                triangle
+                    .iter()
+                    .for_each(|class| quadrics[*class as usize].add(&quadric));
+            }
+            for corner in 0..3 {
+                let (from, to) = (triangle[corner], triangle[(corner + 1) % 3]);
+                class_triangles[from as usize].push(triangle_index);
+                edge_faces
+                    .entry((from.min(to), from.max(to)))
+                    .or_insert_with(Vec::new)
+                    .push((triangle_index, from, to));
+            }
+        });
+    edge_faces.values().for_each(|faces| {
+        let [(triangle_index, from, to)] = faces[..] else {
+            return;
+        };
+        let [a, b, c] = triangles[triangle_index].map(|class| class_positions[class as usize]);
+        let face_normal = (b - a).cross(&(c - a));
+        let (start, end) = (class_positions[from as usize], class_positions[to as usize]);
+        let edge = end - start;
+        if let Some(normal) = edge.cross(&face_normal).try_normalize(f64::EPSILON) {
+            let quadric = Quadric::from_plane(
+                &normal,
+                -normal.dot(&start),
+                edge.norm_squared() * BORDER_WEIGHT,
+            );
+            quadrics[from as usize].add(&quadric);
+            quadrics[to as usize].add(&quadric);
+        }
+    });
+
+    let mut versions = vec![0_u32; number_of_classes];
+    let mut collapsed_into = vec![None; number_of_classes];
+    let mut heap = std::collections::BinaryHeap::new();
+    let push_candidate = |heap: &mut std::collections::BinaryHeap<Collapse>,
+                          quadrics: &[Quadric],
+                          versions: &[u32],
+                          a: u32,
+                          b: u32| {
+        let mut quadric = quadrics[a as usize];
+        quadric.add(&quadrics[b as usize]);
+        let cost_to_b = quadric.error(&class_positions[b as usize]);
+        let cost_to_a = quadric.error(&class_positions[a as usize]);
+        let (from, to, cost) = if cost_to_b <= cost_to_a {
+            (a, b, cost_to_b)
+        } else {
+            (b, a, cost_to_a)
+        };
+        heap.push(Collapse {
+            cost,
+            from,
+            to,
+            from_version: versions[from as usize],
+            to_version: versions[to as usize],
+        });
+    };
+    edge_faces.keys().for_each(|(a, b)| {
+        push_candidate(&mut heap, &quadrics, &versions, *a, *b);
+    });
+
+    let max_cost = (max_error as f64).powi(2);
+    let mut error = 0.0_f64;
+    while alive_count * 3 > target_index_count {
+        let Some(collapse) = heap.pop() else {
+            break;
+        };
+        let (from, to) = (collapse.from as usize, collapse.to as usize);
+        if collapsed_into[from].is_some()
+            || collapsed_into[to].is_some()
+            || versions[from] != collapse.from_version
+            || versions[to] != collapse.to_version
+        {
+            continue;
+        }
+        if collapse.cost > max_cost {
+            break;
+        }
+
+        // Reject collapses that would flip a remaining triangle
+        let flips = class_triangles[from].iter().any(|triangle_index| {
+            let triangle = triangles[*triangle_index];
+            if !alive[*triangle_index] || triangle.contains(&(to as u32)) {
+                return false;
+            }
+            let positions = triangle.map(|class| class_positions[class as usize]);
+            let moved = triangle.map(|class| {
+                class_positions[if class as usize == from {
+                    to
+                } else {
+                    class as usize
+                }]
+            });
+            let before = (positions[1] - positions[0]).cross(&(positions[2] - positions[0]));
+            let after = (moved[1] - moved[0]).cross(&(moved[2] - moved[0]));
+            before.dot(&after) <= 0.0
+        });
+        if flips {
+            continue;
+        }
+
+        error = error.max(collapse.cost);
+        collapsed_into[from] = Some(to as u32);
+        let from_quadric = quadrics[from];
+        quadrics[to].add(&from_quadric);
+        versions[to] += 1;
+
+        let from_triangles = std::mem::take(&mut class_triangles[from]);
+        from_triangles.into_iter().for_each(|triangle_index| {
+            if !alive[triangle_index] {
+                return;
+            }
+            let triangle = &mut triangles[triangle_index];
+            if triangle.contains(&(to as u32)) {
+                alive[triangle_index] = false;
+                alive_count -= 1;
+            } else {
+                triangle.iter_mut().for_each(|class| {
+                    if *class as usize == from {
+                        *class = to as u32;
+                    }
+                });
+                class_triangles[to].push(triangle_index);
+            }
+        });
+        class_triangles[to].retain(|triangle_index| alive[*triangle_index]);
+        class_triangles[to].sort_unstable();
+        class_triangles[to].dedup();
+
+        let mut neighbors = class_triangles[to]
+            .iter()
+            .flat_map(|triangle_index| triangles[*triangle_index])
+            .filter(|class| *class as usize != to)
+            .collect::<Vec<_>>();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors.into_iter().for_each(|neighbor| {
+            push_candidate(&mut heap, &quadrics, &versions, to as u32, neighbor);
+        });
+    }
+
+    // Each corner keeps its vertex if it survived, otherwise it takes the vertex at the
+    // collapsed position whose attributes are closest to the ones it had
+    let mut replacements = std::collections::HashMap::new();
+    let simplified = (0..triangles.len())
+        .filter(|triangle_index| alive[*triangle_index])
+        .flat_map(|triangle_index| {
+            let mut corner_vertices = corners[triangle_index];
+            corner_vertices
+                .iter_mut()
+                .zip(triangles[triangle_index])
+                .for_each(|(vertex, class)| {
+                    if class_of[*vertex as usize] == class {
+                        return;
+                    }
+                    *vertex = *replacements.entry((*vertex, class)).or_insert_with(|| {
+                        let original = &vertices[*vertex as usize];
+                        class_members[class as usize]
+                            .iter()
+                            .copied()
+                            .min_by(|a, b| {
+                                attribute_distance(original, &vertices[*a as usize]).total_cmp(
+                                    &attribute_distance(original, &vertices[*b as usize]),
+                                )
+                            })
+                            .unwrap_or(*vertex)
+                    });
+                });
+            corner_vertices
+        })
+        .collect::<Vec<_>>();
+
+    (simplified, error.sqrt() as f32)
+}
+
+fn attribute_distance(a: &crate::world::Vertex, b: &crate::world::Vertex) -> f32 {
+    glm::distance2(&a.normal, &b.normal)
+        + glm::distance2(&a.uv_0, &b.uv_0)
+        + glm::distance2(&a.color_0, &b.color_0)
+}
+
+/// A symmetric 4x4 matrix accumulating weighted squared distances to planes
+#[derive(Default, Copy, Clone)]
+struct Quadric {
+    coefficients: [f64; 10],
+    weight: f64,
+}
+
+impl Quadric {
+    fn from_plane(normal: &glm::DVec3, distance: f64, weight: f64) -> Self {
+        let (a, b, c, d) = (normal.x, normal.y, normal.z, distance);
+        Self {
+            coefficients: [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|coefficient| coefficient * weight),
+            weight,
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.coefficients
+            .iter_mut()
+            .zip(other.coefficients.iter())
+            .for_each(|(coefficient, other)| *coefficient += other);
+        self.weight += other.weight;
+    }
+
+    /// The weighted mean squared distance from the position to the accumulated planes
+    fn error(&self, position: &glm::DVec3) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.coefficients;
+        let (x, y, z) = (position.x, position.y, position.z);
+        let error = aa * x * x
+            + 2.0 * ab * x * y
+            + 2.0 * ac * x * z
+            + 2.0 * ad * x
+            + bb * y * y
+            + 2.0 * bc * y * z
+            + 2.0 * bd * y
+            + cc * z * z
+            + 2.0 * cd * z
+            + dd;
+        if self.weight > 0.0 {
+            error.max(0.0) / self.weight
+        } else {
+            0.0
+        }
+    }
+}
+
+struct Collapse {
+    cost: f64,
+    from: u32,
+    to: u32,
+    from_version: u32,
+    to_version: u32,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost.total_cmp(&other.cost).is_eq()
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    // Reversed so the binary heap pops the cheapest collapse first
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+
+    /// A flat indexed grid of `size` by `size` quads
+    fn grid(size: u32, height: f32) -> (Vec<crate::world::Vertex>, Vec<u32>) {
+        let vertices = (0..=size)
+            .flat_map(|y| (0..=size).map(move |x| (x, y)))
+            .map(|(x, y)| crate::world::Vertex {
+                position: glm::vec3(x as f32, y as f32, height),
+                normal: glm::Vec3::z(),
+                ..Default::default()
+            })
+            .collect();
+        let indices = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                let corner = y * (size + 1) + x;
+                let above = corner + size + 1;
+                [corner, corner + 1, above + 1, corner, above + 1, above]
+            })
+            .collect();
+        (vertices, indices)
+    }
+
+    #[test]
+    fn simplify_reaches_the_target_ratio() {
+        let (vertices, indices) = grid(16, 0.0);
+        [0.5, 0.25, 0.125].into_iter().for_each(|ratio| {
+            let target_index_count = (indices.len() as f32 * ratio) as usize;
+            let (simplified, error) = super::simplify(&vertices, &indices, target_index_count, 1.0);
+            assert!(simplified.len() <= target_index_count);
+            // Each collapse removes about two triangles, so the target is closely approached
+            assert!(simplified.len() + 12 >= target_index_count);
+            assert_eq!(simplified.len() % 3, 0);
+            assert!(simplified
+                .iter()
+                .all(|index| (*index as usize) < vertices.len()));
+            // The grid is flat, so collapses inside it introduce no error
+            assert!(error < 1e-3);
+        });
+    }
+
+    #[test]
+    fn levels_keep_primitive_and_material_order() {
+        let mut world = crate::world::World::default();
+        let primitives = [(Some(1), 0.0), (None, 1.0), (Some(0), 2.0)]
+            .into_iter()
+            .map(|(material_index, height)| {
+                let (vertices, indices) = grid(8, height);
+                let primitive = crate::world::Primitive {
+                    material_index,
+                    vertex_offset: world.vertices.len(),
+                    index_offset: world.indices.len(),
+                    number_of_vertices: vertices.len(),
+                    number_of_indices: indices.len(),
+                    ..Default::default()
+                };
+                world.vertices.extend(vertices);
+                world.indices.extend(indices);
+                primitive
+            })
+            .collect();
+        world.meshes.push(crate::world::Mesh {
+            primitives,
+            ..Default::default()
+        });
+        let options = super::LodOptions {
+            max_error: 1.0,
+            ..Default::default()
+        };
+        super::generate_lods(&mut world, &options);
+
+        let mesh = &world.meshes[0];
+        assert_eq!(mesh.lods.len(), options.levels.len());
+        mesh.lods
+            .iter()
+            .zip(options.levels.iter())
+            .for_each(|(lod, level)| {
+                assert_eq!(lod.screen_size, level.screen_size);
+                assert_eq!(lod.primitives.len(), mesh.primitives.len());
+                lod.primitives.iter().zip(mesh.primitives.iter()).for_each(
+                    |(lod_primitive, primitive)| {
+                        assert_eq!(lod_primitive.material_index, primitive.material_index);
+                        assert_eq!(lod_primitive.vertex_offset, primitive.vertex_offset);
+                        assert_eq!(
+                            lod_primitive.number_of_vertices,
+                            primitive.number_of_vertices
+                        );
+                        let target_index_count =
+                            (primitive.number_of_indices as f32 * level.index_ratio) as usize;
+                        assert!(lod_primitive.number_of_indices <= target_index_count);
+                        assert!(lod_primitive.number_of_indices > 0);
+                        assert!(world.indices[lod_primitive.index_offset
+                            ..lod_primitive.index_offset + lod_primitive.number_of_indices]
+                            .iter()
+                            .all(|index| (*index as usize) < primitive.number_of_vertices));
+                    },
+                );
+            });
+    }
+
+    #[test]
+    fn levels_are_selected_at_their_thresholds() {
+        let mesh = crate::world::Mesh {
+            lods: super::LodOptions::default()
+                .levels
+                .iter()
+                .map(|level| crate::world::MeshLod {
+                    primitives: Vec::new(),
+                    screen_size: level.screen_size,
+                    error: 0.0,
+                })
+                .collect(),
+            ..Default::default()
+        };
+        [
+            (1.0, 0),
+            (0.25, 0),
+            (0.2, 1),
+            (0.1, 1),
+            (0.05, 2),
+            (0.04, 2),
+            (0.01, 3),
+        ]
+        .into_iter()
+        .for_each(|(screen_size, lod_index)| {
+            assert_eq!(mesh.lod_primitives(screen_size).0, lod_index);
+        });
+    }
+
+    #[test]
+    fn projected_size_shrinks_with_distance() {
+        // A 90 degree field of view covers a height of twice the distance
+        let projection = glm::perspective(1.0, std::f32::consts::FRAC_PI_2, 0.1, 1000.0);
+        let view = glm::look_at(&glm::Vec3::zeros(), &-glm::Vec3::z(), &glm::Vec3::y());
+        [(2.0, 0.5), (10.0, 0.1), (50.0, 0.02)]
+            .into_iter()
+            .for_each(|(distance, screen_size)| {
+                let size = super::projected_screen_size(
+                    &projection,
+                    &view,
+                    &glm::vec3(0.0, 0.0, -distance),
+                    1.0,
+                );
+                assert!((size - screen_size).abs() < 1e-6);
+            });
+
+        let orthographic = glm::ortho(-5.0, 5.0, -5.0, 5.0, 0.1, 1000.0);
+        [2.0, 50.0].into_iter().for_each(|distance| {
+            let size = super::projected_screen_size(
+                &orthographic,
+                &view,
+                &glm::vec3(0.0, 0.0, -distance),
+                1.0,
+            );
+            assert!((size - 0.2).abs() < 1e-6);
+        });
+    }
+}
@@ -0,0 +1,837 @@
+use nalgebra_glm as glm;
+
+/// Imports a Wavefront obj file along with the mtl material libraries it references.
+/// Each object or group becomes a mesh with a primitive per material,
+/// and faces without normals are shaded by their smoothing groups.
+pub fn import_obj(
+    path: impl AsRef<std::path::Path>,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    import_obj_with_progress(path, &crate::gltf::ImportOptions::default(), &mut |_| true)
+}
+
+/// The normal mode of the options is not used, as smoothing groups decide how normals are generated
+pub fn import_obj_with_progress(
+    path: impl AsRef<std::path::Path>,
+    options: &crate::gltf::ImportOptions,
+    progress: &mut dyn FnMut(crate::gltf::ImportProgress) -> bool,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|source| crate::gltf::ImportError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut resolver = crate::gltf::FileResolver {
+        base: path
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("./"))
+            .to_path_buf(),
+    };
+    import_obj_from_slice_with_progress(&bytes, &mut resolver, options, progress)
+}
+
+/// Imports an obj file from memory, using `resolver` for material libraries and texture images
+pub fn import_obj_from_slice_with_progress(
+    bytes: &[u8],
+    resolver: &mut dyn crate::gltf::Resolver,
+    options: &crate::gltf::ImportOptions,
+    progress: &mut dyn FnMut(crate::gltf::ImportProgress) -> bool,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    crate::gltf::report_progress(progress, crate::gltf::ImportStage::Parsing, 0, 1)?;
+    let obj = parse_obj(&String::from_utf8_lossy(bytes))?;
+
+    let mut library = MaterialLibrary::default();
+    obj.material_libraries.iter().for_each(|uri| {
+        match resolver.resolve(uri) {
+            Ok(bytes) => parse_mtl(&String::from_utf8_lossy(&bytes), &mut library),
+            Err(error) => log::warn!("Skipping material library {uri}: {error}"),
+        };
+    });
+
+    let mut world = crate::world::World::default();
+    load_materials(&mut world, &library, resolver, progress)?;
+
+    // Material names the libraries don't define get a default material
+    let mut material_indices = library
+        .materials
+        .iter()
+        .enumerate()
+        .map(|(index, material)| (material.name.clone(), index))
+        .collect::<std::collections::HashMap<_, _>>();
+
+    let number_of_objects = obj.objects.len();
+    let mesh_names = obj
+        .objects
+        .iter()
+        .enumerate()
+        .map(|(object_index, object)| {
+            crate::gltf::report_progress(
+                progress,
+                crate::gltf::ImportStage::BuildingMeshes,
+                object_index,
+                number_of_objects,
+            )?;
+            let smooth_normals = smooth_normals(&obj, object);
+            let primitives = object
+                .groups
+                .iter()
+                .map(|group| {
+                    let material_index = group.material.as_ref().map(|name| {
+                        *material_indices.entry(name.clone()).or_insert_with(|| {
+                            log::warn!("Material {name} is not defined by any material library");
+                            world.materials.push(crate::world::Material {
+                                base_color_factor: glm::vec4(1.0, 1.0, 1.0, 1.0),
+                                ..Default::default()
+                            });
+                            world.materials.len() - 1
+                        })
+                    });
+                    build_primitive(
+                        &mut world,
+                        &obj,
+                        group,
+                        &smooth_normals,
+                        material_index,
+                        options,
+                    )
+                })
+                .collect::<Vec<_>>();
+            world.meshes.push(crate::world::Mesh {
+                primitives,
+                ..Default::default()
+            });
+            Ok(object.name.clone())
+        })
+        .collect::<Result<Vec<_>, crate::gltf::ImportError>>()?;
+
+    crate::gltf::report_progress(progress, crate::gltf::ImportStage::BuildingScenes, 0, 1)?;
+    crate::import::add_mesh_scene(&mut world, mesh_names);
+    world.compute_bounds();
+
+    if let Some(lod_options) = options.lods.as_ref() {
+        crate::lod::generate_lods(&mut world, lod_options);
+    }
+
+    Ok(world)
+}
+
+#[derive(Default)]
+struct Obj {
+    positions: Vec<glm::Vec3>,
+    /// Only present when every position has a color
+    colors: Vec<glm::Vec3>,
+    uvs: Vec<glm::Vec2>,
+    normals: Vec<glm::Vec3>,
+    objects: Vec<Object>,
+    material_libraries: Vec<String>,
+}
+
+struct Object {
+    name: String,
+    /// Faces grouped by material, in the order the materials are first used
+    groups: Vec<FaceGroup>,
+}
+
+struct FaceGroup {
+    material: Option<String>,
+    faces: Vec<Face>,
+}
+
+struct Face {
+    corners: Vec<Corner>,
+    /// Zero when smoothing is off, which gives the face a flat normal
+    smoothing_group: u32,
+}
+
+#[derive(Clone, Copy)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+fn parse_obj(source: &str) -> Result<Obj, crate::gltf::ImportError> {
+    let mut obj = Obj::default();
+    let mut material = None;
+    let mut smoothing_group = 0;
+    let mut in_object = false;
+
+    for (line_index, line) in source.lines().enumerate() {
+        let syntax_error = |reason: String| crate::gltf::ImportError::Syntax {
+            line: line_index + 1,
+            reason,
+        };
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let arguments = tokens.collect::<Vec<_>>();
+        match keyword {
+            "v" => {
+                let values = parse_floats(&arguments).map_err(syntax_error)?;
+                if values.len() < 3 {
+                    return Err(syntax_error("a vertex needs three coordinates".to_string()));
+                }
+                obj.positions
+                    .push(glm::vec3(values[0], values[1], values[2]));
+                // Vertex colors are a common extension that follows the coordinates
+                if values.len() >= 6 {
+                    obj.colors.push(glm::vec3(values[3], values[4], values[5]));
+                }
+            }
+            "vt" => {
+                let values = parse_floats(&arguments).map_err(syntax_error)?;
+                let u = values.first().copied().unwrap_or_default();
+                let v = values.get(1).copied().unwrap_or_default();
+                // Obj textures have their origin at the bottom left
+                obj.uvs.push(glm::vec2(u, 1.0 - v));
+            }
+            "vn" => {
+                let values = parse_floats(&arguments).map_err(syntax_error)?;
+                if values.len() < 3 {
+                    return Err(syntax_error("a normal needs three coordinates".to_string()));
+                }
+                obj.normals.push(glm::vec3(values[0], values[1], values[2]));
+            }
+            "f" => {
+                if arguments.len() < 3 {
+                    return Err(syntax_error(
+                        "a face needs at least three corners".to_string(),
+                    ));
+                }
+                let corners = arguments
+                    .iter()
+                    .map(|corner| parse_corner(corner, &obj))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(syntax_error)?;
+                if obj.objects.is_empty() {
+                    obj.objects.push(Object {
+                        name: "Mesh".to_string(),
+                        groups: Vec::new(),
+                    });
+                }
+                let object = obj.objects.last_mut().unwrap();
+                let group_index = match object
+                    .groups
+                    .iter()
+                    .position(|group| group.material == material)
+                {
+                    Some(group_index) => group_index,
+                    None => {
+                        object.groups.push(FaceGroup {
+                            material: material.clone(),
+                            faces: Vec::new(),
+                        });
+                        object.groups.len() - 1
+                    }
+                };
+                object.groups[group_index].faces.push(Face {
+                    corners,
+                    smoothing_group,
+                });
+            }
+            // Groups inside an object are parts of it rather than meshes of their own
+            "g" if in_object => {}
+            "o" | "g" => {
+                in_object |= keyword == "o";
+                let name = arguments.join(" ");
+                // An object or group without faces yet is renamed rather than left empty
+                match obj.objects.last_mut() {
+                    Some(object) if object.groups.is_empty() => object.name = name,
+                    _ => obj.objects.push(Object {
+                        name,
+                        groups: Vec::new(),
+                    }),
+                }
+            }
+            "usemtl" => material = Some(arguments.join(" ")),
+            "mtllib" => obj
+                .material_libraries
+                .extend(arguments.iter().map(|uri| uri.to_string())),
+            "s" => {
+                smoothing_group = match arguments.first().copied() {
+                    Some("off") | None => 0,
+                    Some(group) => group
+                        .parse()
+                        .map_err(|_| syntax_error(format!("{group} is not a smoothing group")))?,
+                };
+            }
+            // Points, lines, curves and surfaces are not imported
+            _ => {}
+        }
+    }
+
+    if obj.colors.len() != obj.positions.len() {
+        obj.colors.clear();
+    }
+    obj.objects.retain(|object| !object.groups.is_empty());
+    Ok(obj)
+}
+
+fn parse_floats(arguments: &[&str]) -> Result<Vec<f32>, String> {
+    arguments
+        .iter()
+        .map(|argument| {
+            argument
+                .parse::<f32>()
+                .map_err(|_| format!("{argument} is not a number"))
+        })
+        .collect()
+}
+
+/// Parses a `position/uv/normal` corner, where negative indices count back from the latest element
+fn parse_corner(corner: &str, obj: &Obj) -> Result<Corner, String> {
+    let mut parts = corner.split('/');
+    let mut index = |count: usize, optional: bool| -> Result<Option<usize>, String> {
+        let part = parts.next().unwrap_or_default();
+        if part.is_empty() && optional {
+            return Ok(None);
+        }
+        let value = part
+            .parse::<i64>()
+            .map_err(|_| format!("{corner} is not a valid face corner"))?;
+        let index = match value {
+            value if value > 0 => value - 1,
+            value if value < 0 => count as i64 + value,
+            _ => return Err(format!("{corner} has an index of zero")),
+        };
+        if index < 0 || index as usize >= count {
+            return Err(format!("{corner} refers to an element that does not exist"));
+        }
+        Ok(Some(index as usize))
+    };
+    Ok(Corner {
+        position: index(obj.positions.len(), false)?.unwrap_or_default(),
+        uv: index(obj.uvs.len(), true)?,
+        normal: index(obj.normals.len(), true)?,
+    })
+}
+
+/// Which normal a corner uses, so corners only share a vertex when their normals match
+#[derive(PartialEq, Eq, Hash)]
+enum NormalSource {
+    Given(usize),
+    Smooth(u32),
+    Flat(usize),
+}
+
+/// Sums the area weighted normals of each position within a smoothing group,
+/// across every material group of the object so smooth surfaces stay smooth where materials change
+fn smooth_normals(
+    obj: &Obj,
+    object: &Object,
+) -> std::collections::HashMap<(usize, u32), glm::Vec3> {
+    let mut smooth_normals = std::collections::HashMap::new();
+    object
+        .groups
+        .iter()
+        .flat_map(|group| group.faces.iter())
+        .filter(|face| face.smoothing_group != 0)
+        .for_each(|face| {
+            let normal = face_normal(obj, face);
+            face.corners
+                .iter()
+                .filter(|corner| corner.normal.is_none())
+                .for_each(|corner| {
+                    *smooth_normals
+                        .entry((corner.position, face.smoothing_group))
+                        .or_insert_with(glm::Vec3::zeros) += normal;
+                });
+        });
+    smooth_normals
+}
+
+fn build_primitive(
+    world: &mut crate::world::World,
+    obj: &Obj,
+    group: &FaceGroup,
+    smooth_normals: &std::collections::HashMap<(usize, u32), glm::Vec3>,
+    material_index: Option<usize>,
+    options: &crate::gltf::ImportOptions,
+) -> crate::world::Primitive {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut vertex_indices = std::collections::HashMap::new();
+    group
+        .faces
+        .iter()
+        .enumerate()
+        .for_each(|(face_index, face)| {
+            let flat_normal = face_normal(obj, face)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(glm::Vec3::y);
+            let corner_indices = face
+                .corners
+                .iter()
+                .map(|corner| {
+                    let normal_source = match (corner.normal, face.smoothing_group) {
+                        (Some(normal), _) => NormalSource::Given(normal),
+                        (None, 0) => NormalSource::Flat(face_index),
+                        (None, smoothing_group) => NormalSource::Smooth(smoothing_group),
+                    };
+                    let normal = match normal_source {
+                        NormalSource::Given(normal) => obj.normals[normal],
+                        NormalSource::Flat(_) => flat_normal,
+                        NormalSource::Smooth(smoothing_group) => smooth_normals
+                            [&(corner.position, smoothing_group)]
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or(flat_normal),
+                    };
+                    *vertex_indices
+                        .entry((corner.position, corner.uv, normal_source))
+                        .or_insert_with(|| {
+                            vertices.push(crate::world::Vertex {
+                                position: obj.positions[corner.position],
+                                normal,
+                                uv_0: corner.uv.map(|uv| obj.uvs[uv]).unwrap_or_default(),
+                                color_0: obj
+                                    .colors
+                                    .get(corner.position)
+                                    .copied()
+                                    .unwrap_or_else(|| glm::vec3(1.0, 1.0, 1.0)),
+                                ..Default::default()
+                            });
+                            vertices.len() as u32 - 1
+                        })
+                })
+                .collect::<Vec<_>>();
+            // Polygons are triangulated as fans, which assumes they are convex
+            (1..corner_indices.len() - 1).for_each(|corner| {
+                indices.extend([
+                    corner_indices[0],
+                    corner_indices[corner],
+                    corner_indices[corner + 1],
+                ]);
+            });
+        });
+
+    crate::import::add_primitive(
+        world,
+        vertices,
+        indices,
+        crate::world::PrimitiveTopology::Triangles,
+        material_index,
+        options,
+    )
+}
+
+/// The area weighted normal of a polygon, using Newell's method so concave and non planar faces are handled
+fn face_normal(obj: &Obj, face: &Face) -> glm::Vec3 {
+    let positions = face
+        .corners
+        .iter()
+        .map(|corner| obj.positions[corner.position])
+        .collect::<Vec<_>>();
+    positions
+        .iter()
+        .zip(positions.iter().cycle().skip(1))
+        .fold(glm::Vec3::zeros(), |normal, (current, next)| {
+            normal + glm::cross(current, next)
+        })
+        * 0.5
+}
+
+#[derive(Default)]
+struct MaterialLibrary {
+    materials: Vec<MtlMaterial>,
+}
+
+struct MtlMaterial {
+    name: String,
+    diffuse: glm::Vec3,
+    emissive: glm::Vec3,
+    dissolve: f32,
+    diffuse_map: Option<String>,
+    emissive_map: Option<String>,
+}
+
+/// Reads the statements of an mtl file the world's materials can represent, skipping the rest
+fn parse_mtl(source: &str, library: &mut MaterialLibrary) {
+    source.lines().for_each(|line| {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            return;
+        };
+        let arguments = tokens.collect::<Vec<_>>();
+        if keyword == "newmtl" {
+            library.materials.push(MtlMaterial {
+                name: arguments.join(" "),
+                diffuse: glm::vec3(1.0, 1.0, 1.0),
+                emissive: glm::Vec3::zeros(),
+                dissolve: 1.0,
+                diffuse_map: None,
+                emissive_map: None,
+            });
+            return;
+        }
+        let Some(material) = library.materials.last_mut() else {
+            return;
+        };
+        let color = || {
+            parse_floats(&arguments)
+                .ok()
+                .filter(|values| values.len() >= 3)
+                .map(|values| glm::vec3(values[0], values[1], values[2]))
+        };
+        let scalar = || {
+            arguments
+                .first()
+                .and_then(|value| value.parse::<f32>().ok())
+        };
+        // Texture options come before the file name
+        let map = || arguments.last().map(|uri| uri.to_string());
+        match keyword {
+            "Kd" => material.diffuse = color().unwrap_or(material.diffuse),
+            "Ke" => material.emissive = color().unwrap_or(material.emissive),
+            "d" => material.dissolve = scalar().unwrap_or(material.dissolve),
+            "Tr" => material.dissolve = scalar().map_or(material.dissolve, |value| 1.0 - value),
+            "map_Kd" => material.diffuse_map = map(),
+            "map_Ke" => material.emissive_map = map(),
+            _ => {}
+        }
+    });
+}
+
+/// Adds the library's materials to the world, loading each texture image once.
+/// Images that can't be loaded are skipped, leaving the material untextured.
+fn load_materials(
+    world: &mut crate::world::World,
+    library: &MaterialLibrary,
+    resolver: &mut dyn crate::gltf::Resolver,
+    progress: &mut dyn FnMut(crate::gltf::ImportProgress) -> bool,
+) -> Result<(), crate::gltf::ImportError> {
+    let mut uris = Vec::new();
+    library.materials.iter().for_each(|material| {
+        [&material.diffuse_map, &material.emissive_map]
+            .into_iter()
+            .flatten()
+            .for_each(|uri| {
+                if !uris.contains(uri) {
+                    uris.push(uri.clone());
+                }
+            });
+    });
+
+    let mut texture_indices = std::collections::HashMap::new();
+    uris.iter().enumerate().try_for_each(|(uri_index, uri)| {
+        crate::gltf::report_progress(
+            progress,
+            crate::gltf::ImportStage::DecodingImages,
+            uri_index,
+            uris.len(),
+        )?;
+        let image = resolver
+            .resolve(uri)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| image::load_from_memory(&bytes).map_err(|error| error.to_string()));
+        match image {
+            Ok(image) => {
+                world.images.push(crate::world::Image::from(image));
+                world.textures.push(crate::world::Texture {
+                    image_index: world.images.len() - 1,
+                    sampler_index: None,
+                });
+                texture_indices.insert(uri.as_str(), world.textures.len() - 1);
+            }
+            Err(error) => log::warn!("Skipping texture {uri}: {error}"),
+        }
+        Ok(())
+    })?;
+
+    let texture_index = |uri: &Option<String>| {
+        uri.as_ref()
+            .and_then(|uri| texture_indices.get(uri.as_str()).copied())
+    };
+    world.materials = library
+        .materials
+        .iter()
+        .map(|material| crate::world::Material {
+            base_color_factor: glm::vec4(
+                material.diffuse.x,
+                material.diffuse.y,
+                material.diffuse.z,
+                material.dissolve,
+            ),
+            base_color_texture_index: texture_index(&material.diffuse_map),
+            emissive_texture_index: texture_index(&material.emissive_map),
+            // Emissive maps are multiplied by the factor, which is usually left at zero in mtl files
+            emissive_factor: match (
+                &material.emissive_map,
+                material.emissive == glm::Vec3::zeros(),
+            ) {
+                (Some(_), true) => glm::vec3(1.0, 1.0, 1.0),
+                _ => material.emissive,
+            },
+            alpha_mode: if material.dissolve < 1.0 {
+                crate::world::AlphaMode::Blend
+            } else {
+                crate::world::AlphaMode::Opaque
+            },
+            ..Default::default()
+        })
+        .collect();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    fn write_files(name: &str, files: &[(&str, &[u8])]) -> std::path::PathBuf {
+        let directory = std::env::temp_dir().join("serenity_obj").join(name);
+        std::fs::create_dir_all(&directory).unwrap();
+        files.iter().for_each(|(file_name, contents)| {
+            std::fs::write(directory.join(file_name), contents).unwrap();
+        });
+        directory.join(files[0].0)
+    }
+
+    const PYRAMID: &str = "
+v -1 0 -1
+v 1 0 -1
+v 1 0 1
+v -1 0 1
+v 0 1 0
+f 2 1 5
+f 3 2 5
+f 4 3 5
+f 1 4 5
+";
+
+    #[test]
+    fn objects_become_meshes_with_a_primitive_per_material() {
+        let path = write_files(
+            "materials",
+            &[
+                (
+                    "materials.obj",
+                    b"mtllib materials.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+o Quad
+usemtl Red
+f 1/1 2/2 3/3 4/4
+o Triangles
+usemtl Red
+f -4/-4 -3/-3 -2/-2
+usemtl Glass
+f 1 3 4
+usemtl Missing
+f 1 2 3
+",
+                ),
+                (
+                    "materials.mtl",
+                    b"newmtl Red
+Kd 1 0 0
+Ke 0.5 0.5 0.5
+newmtl Glass
+Kd 0.8 0.9 1.0
+d 0.25
+",
+                ),
+            ],
+        );
+        let world = crate::obj::import_obj(path).unwrap();
+
+        assert_eq!(world.meshes.len(), 2);
+        let quad = &world.meshes[0].primitives;
+        assert_eq!(quad.len(), 1);
+        assert_eq!(quad[0].material_index, Some(0));
+        assert_eq!(quad[0].number_of_vertices, 4);
+        assert_eq!(quad[0].number_of_indices, 6);
+        let uvs = world.vertices[..4]
+            .iter()
+            .map(|vertex| vertex.uv_0)
+            .collect::<Vec<_>>();
+        assert_eq!(uvs[0], nalgebra_glm::vec2(0.0, 1.0));
+        assert_eq!(uvs[2], nalgebra_glm::vec2(1.0, 0.0));
+
+        let triangles = &world.meshes[1].primitives;
+        assert_eq!(
+            triangles
+                .iter()
+                .map(|primitive| primitive.material_index)
+                .collect::<Vec<_>>(),
+            [Some(0), Some(1), Some(2)]
+        );
+        assert_eq!(
+            world.materials[0].base_color_factor,
+            nalgebra_glm::vec4(1.0, 0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            world.materials[0].emissive_factor,
+            nalgebra_glm::vec3(0.5, 0.5, 0.5)
+        );
+        assert_eq!(world.materials[1].base_color_factor.w, 0.25);
+        assert_eq!(
+            world.materials[1].alpha_mode,
+            crate::world::AlphaMode::Blend
+        );
+        assert_eq!(
+            world.materials[2].base_color_factor,
+            nalgebra_glm::vec4(1.0, 1.0, 1.0, 1.0)
+        );
+
+        let names = world
+            .nodes
+            .iter()
+            .map(|node| world.metadata[node.metadata_index].name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Scene Root", "Quad", "Triangles", "Main Camera"]);
+        let scene = &world.scenes[0];
+        let camera_node_index = scene.graph[scene.default_camera_graph_node_index];
+        assert_eq!(world.nodes[camera_node_index].camera_index, Some(0));
+    }
+
+    #[test]
+    fn groups_inside_objects_keep_the_object_name() {
+        let object_faces = |source: &str| -> Vec<(String, usize)> {
+            super::parse_obj(source)
+                .unwrap()
+                .objects
+                .into_iter()
+                .map(|object| {
+                    let faces = object.groups.iter().map(|group| group.faces.len()).sum();
+                    (object.name, faces)
+                })
+                .collect::<Vec<_>>()
+        };
+        let source = format!("o Cube\ng default{PYRAMID}g lid\nf 1 2 3\no Cone\nf 1 2 5\n");
+        assert_eq!(
+            object_faces(&source),
+            [("Cube".to_string(), 5), ("Cone".to_string(), 1)]
+        );
+
+        // Without objects, each group is a mesh
+        let source = format!("g empty\ng base{PYRAMID}g lid\nf 1 2 3\n");
+        assert_eq!(
+            object_faces(&source),
+            [("base".to_string(), 4), ("lid".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn smoothing_groups_share_normals() {
+        let smooth = write_files(
+            "smooth",
+            &[("smooth.obj", format!("s 1{PYRAMID}").as_bytes())],
+        );
+        let world = crate::obj::import_obj(smooth).unwrap();
+        let primitive = &world.meshes[0].primitives[0];
+        assert_eq!(primitive.number_of_vertices, 5);
+        let apex = world
+            .vertices
+            .iter()
+            .find(|vertex| vertex.position.y == 1.0)
+            .unwrap();
+        assert!((apex.normal - nalgebra_glm::Vec3::y()).norm() < 1e-5);
+
+        let flat = write_files(
+            "flat",
+            &[("flat.obj", format!("s off{PYRAMID}").as_bytes())],
+        );
+        let world = crate::obj::import_obj(flat).unwrap();
+        assert_eq!(world.meshes[0].primitives[0].number_of_vertices, 12);
+        world.vertices.chunks_exact(3).for_each(|triangle| {
+            assert!(triangle
+                .iter()
+                .all(|vertex| vertex.normal == triangle[0].normal));
+        });
+    }
+
+    #[test]
+    fn smoothing_groups_span_material_changes() {
+        // Two adjacent sides of the pyramid use one material and the other two another,
+        // all within one smoothing group
+        let source = PYRAMID
+            .lines()
+            .map(|line| match line {
+                "f 2 1 5" => format!("usemtl Red\n{line}"),
+                "f 4 3 5" => format!("usemtl Blue\n{line}"),
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let path = write_files(
+            "smooth_materials",
+            &[("smooth_materials.obj", format!("s 1{source}").as_bytes())],
+        );
+        let world = crate::obj::import_obj(path).unwrap();
+        assert_eq!(world.meshes[0].primitives.len(), 2);
+        let apexes = world
+            .vertices
+            .iter()
+            .filter(|vertex| vertex.position.y == 1.0)
+            .collect::<Vec<_>>();
+        assert_eq!(apexes.len(), 2);
+        assert!(apexes
+            .iter()
+            .all(|apex| (apex.normal - nalgebra_glm::Vec3::y()).norm() < 1e-5));
+    }
+
+    #[test]
+    fn diffuse_and_emissive_maps_become_textures() {
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            2,
+            2,
+            image::Rgba([10, 20, 30, 255]),
+        ))
+        .write_to(
+            &mut std::io::Cursor::new(&mut png),
+            image::ImageOutputFormat::Png,
+        )
+        .unwrap();
+        let path = write_files(
+            "textured",
+            &[
+                (
+                    "textured.obj",
+                    b"mtllib textured.mtl
+usemtl Lit
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+",
+                ),
+                (
+                    "textured.mtl",
+                    b"newmtl Lit
+map_Kd -s 1 1 1 texture.png
+map_Ke texture.png
+map_Ks missing.png
+",
+                ),
+                ("texture.png", &png),
+            ],
+        );
+        let world = crate::obj::import_obj(path).unwrap();
+        assert_eq!(world.images.len(), 1);
+        assert_eq!(world.images[0].width, 2);
+        assert_eq!(world.images[0].pixels[..4], [10, 20, 30, 255]);
+        let material = &world.materials[0];
+        assert_eq!(material.base_color_texture_index, Some(0));
+        assert_eq!(material.emissive_texture_index, Some(0));
+        assert_eq!(material.emissive_factor, nalgebra_glm::vec3(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn syntax_errors_report_their_line() {
+        let path = write_files(
+            "invalid",
+            &[("invalid.obj", b"v 0 0 0\nv 1 0 0\nf 1 2 7\n")],
+        );
+        let error = crate::obj::import_obj(path).unwrap_err();
+        assert!(matches!(
+            error,
+            crate::gltf::ImportError::Syntax { line: 3, .. }
+        ));
+    }
+}
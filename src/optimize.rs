@@ -0,0 +1,593 @@
+/// Size of the FIFO cache used when measuring post-transform vertex cache efficiency
+pub const STATISTICS_CACHE_SIZE: usize = 16;
+
+/// Size of the cache the vertex cache optimizer targets
+const OPTIMIZER_CACHE_SIZE: usize = 32;
+
+#[derive(Debug, Copy, Clone)]
+pub struct OptimizeOptions {
+    /// Merges vertices whose attributes are bitwise identical
+    pub weld_vertices: bool,
+    /// Builds an index buffer for primitives that don't have one
+    pub generate_indices: bool,
+    /// Reorders triangles to reuse recently transformed vertices
+    pub optimize_vertex_cache: bool,
+    /// Reorders clusters of triangles so outward facing surfaces are drawn first
+    pub optimize_overdraw: bool,
+    /// Reorders vertices into the order the index buffer first references them
+    pub optimize_vertex_fetch: bool,
+}
+
+impl Default for OptimizeOptions {
+    fn default() -> Self {
+        Self {
+            weld_vertices: true,
+            generate_indices: true,
+            optimize_vertex_cache: true,
+            optimize_overdraw: true,
+            optimize_vertex_fetch: true,
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct MeshStatistics {
+    pub vertices: usize,
+    pub indices: usize,
+    /// Triangles of triangle-list primitives, which are the only ones measured for cache efficiency
+    pub triangles: usize,
+    /// Vertex shader invocations for those triangles with a FIFO cache of `STATISTICS_CACHE_SIZE`
+    pub transformed_vertices: usize,
+    /// Unique vertices referenced by those triangles
+    pub referenced_vertices: usize,
+}
+
+impl MeshStatistics {
+    /// Average cache miss ratio, the number of vertex transforms per triangle.
+    /// The best possible value is around 0.5 and the worst is 3.0.
+    pub fn acmr(&self) -> f32 {
+        self.transformed_vertices as f32 / self.triangles.max(1) as f32
+    }
+
+    /// Average transform to vertex ratio, where 1.0 means every vertex is transformed exactly once
+    pub fn atvr(&self) -> f32 {
+        self.transformed_vertices as f32 / self.referenced_vertices.max(1) as f32
+    }
+}
+
+impl std::ops::AddAssign for MeshStatistics {
+    fn add_assign(&mut self, other: Self) {
+        self.vertices += other.vertices;
+        self.indices += other.indices;
+        self.triangles += other.triangles;
+        self.transformed_vertices += other.transformed_vertices;
+        self.referenced_vertices += other.referenced_vertices;
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct OptimizeReport {
+    pub before: MeshStatistics,
+    pub after: MeshStatistics,
+}
+
+impl std::ops::AddAssign for OptimizeReport {
+    fn add_assign(&mut self, other: Self) {
+        self.before += other.before;
+        self.after += other.after;
+    }
+}
+
+impl std::fmt::Display for OptimizeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "vertices {} -> {}, indices {} -> {}, ACMR {:.3} -> {:.3}, ATVR {:.3} -> {:.3}",
+            self.before.vertices,
+            self.after.vertices,
+            self.before.indices,
+            self.after.indices,
+            self.before.acmr(),
+            self.after.acmr(),
+            self.before.atvr(),
+            self.after.atvr(),
+        )
+    }
+}
+
+pub fn primitive_statistics(
+    vertices: &[crate::world::Vertex],
+    indices: &[u32],
+    topology: crate::world::PrimitiveTopology,
+) -> MeshStatistics {
+    let mut statistics = MeshStatistics {
+        vertices: vertices.len(),
+        indices: indices.len(),
+        ..Default::default()
+    };
+    if topology != crate::world::PrimitiveTopology::Triangles {
+        return statistics;
+    }
+
+    let sequential_indices;
+    let indices = if indices.is_empty() {
+        sequential_indices = (0..vertices.len() as u32).collect::<Vec<_>>();
+        &sequential_indices
+    } else {
+        indices
+    };
+    statistics.triangles = indices.len() / 3;
+
+    let mut cache = std::collections::VecDeque::with_capacity(STATISTICS_CACHE_SIZE);
+    let mut referenced = vec![false; vertices.len()];
+    indices.iter().for_each(|index| {
+        if !cache.contains(index) {
+            statistics.transformed_vertices += 1;
+            if cache.len() == STATISTICS_CACHE_SIZE {
+                cache.pop_front();
+            }
+            cache.push_back(*index);
+        }
+        if let Some(referenced) = referenced.get_mut(*index as usize) {
+            if !*referenced {
+                *referenced = true;
+                statistics.referenced_vertices += 1;
+            }
+        }
+    });
+    statistics
+}
+
+/// Runs the optimization pipeline on a single primitive whose indices are relative to its first vertex.
+/// Strip and fan primitives become triangle lists when the vertex cache or overdraw is optimized.
+pub fn optimize_primitive(
+    vertices: &mut Vec<crate::world::Vertex>,
+    indices: &mut Vec<u32>,
+    topology: &mut crate::world::PrimitiveTopology,
+    options: &OptimizeOptions,
+) -> OptimizeReport {
+    let before = primitive_statistics(vertices, indices, *topology);
+
+    // Points are drawn as unindexed instances, so they are left as they are
+    if *topology == crate::world::PrimitiveTopology::Points
+        || (indices.is_empty() && !options.generate_indices)
+    {
+        return OptimizeReport {
+            before,
+            after: before,
+        };
+    }
+
+    if options.weld_vertices {
+        weld_vertices(vertices, indices);
+    } else if indices.is_empty() {
+        *indices = (0..vertices.len() as u32).collect();
+    }
+
+    if options.optimize_vertex_cache || options.optimize_overdraw {
+        if let Some(triangles) =
+            crate::geometry::triangle_list_indices(*topology, vertices.len(), indices)
+        {
+            *indices = triangles;
+            *topology = crate::world::PrimitiveTopology::Triangles;
+            if options.optimize_vertex_cache {
+                optimize_vertex_cache(indices, vertices.len());
+            }
+            if options.optimize_overdraw {
+                optimize_overdraw(indices, vertices);
+            }
+        }
+    }
+
+    if options.optimize_vertex_fetch {
+        optimize_vertex_fetch(vertices, indices);
+    }
+
+    OptimizeReport {
+        before,
+        after: primitive_statistics(vertices, indices, *topology),
+    }
+}
+
+/// Optimizes every mesh primitive in the world, rebuilding the shared vertex and index buffers.
+/// Levels of detail index the old vertices, so they are discarded and must be generated again.
+pub fn optimize_world(
+    world: &mut crate::world::World,
+    options: &OptimizeOptions,
+) -> OptimizeReport {
+    let mut report = OptimizeReport::default();
+    let (mut vertices, mut indices) = (Vec::new(), Vec::new());
+    world.meshes.iter_mut().for_each(|mesh| {
+        mesh.lods.clear();
+        mesh.primitives.iter_mut().for_each(|primitive| {
+            let mut primitive_vertices = world.vertices
+                [primitive.vertex_offset..primitive.vertex_offset + primitive.number_of_vertices]
+                .to_vec();
+            let mut primitive_indices = world.indices
+                [primitive.index_offset..primitive.index_offset + primitive.number_of_indices]
+                .to_vec();
+            // Reordering vertices would misalign the morph target displacements
+            if primitive.number_of_morph_targets == 0 {
+                report += optimize_primitive(
+                    &mut primitive_vertices,
+                    &mut primitive_indices,
+                    &mut primitive.topology,
+                    options,
+                );
+            }
+            primitive.vertex_offset = vertices.len();
+            primitive.index_offset = indices.len();
+            primitive.number_of_vertices = primitive_vertices.len();
+            primitive.number_of_indices = primitive_indices.len();
+            vertices.extend(primitive_vertices);
+            indices.extend(primitive_indices);
+        });
+    });
+    world.vertices = vertices;
+    world.indices = indices;
+    report
+}
+
+/// Merges bitwise identical vertices, generating indices if the primitive had none
+pub fn weld_vertices(vertices: &mut Vec<crate::world::Vertex>, indices: &mut Vec<u32>) {
+    let mut unique = std::collections::HashMap::new();
+    let mut welded = Vec::new();
+    let remap = vertices
+        .iter()
+        .map(|vertex| {
+            *unique
+                .entry(bytemuck::bytes_of(vertex).to_vec())
+                .or_insert_with(|| {
+                    welded.push(*vertex);
+                    welded.len() as u32 - 1
+                })
+        })
+        .collect::<Vec<_>>();
+
+    if indices.is_empty() {
+        *indices = remap;
+    } else {
+        indices
+            .iter_mut()
+            .for_each(|index| *index = remap[*index as usize]);
+    }
+    *vertices = welded;
+}
+
+/// Reorders a triangle list for the post-transform vertex cache
+/// using Tom Forsyth's linear-speed vertex cache optimisation
+pub fn optimize_vertex_cache(indices: &mut [u32], number_of_vertices: usize) {
+    const CACHE_DECAY_POWER: f32 = 1.5;
+    const LAST_TRIANGLE_SCORE: f32 = 0.75;
+    const VALENCE_BOOST_SCALE: f32 = 2.0;
+    const VALENCE_BOOST_POWER: f32 = 0.5;
+
+    let number_of_triangles = indices.len() / 3;
+    if number_of_triangles == 0 {
+        return;
+    }
+
+    let vertex_score = |cache_position: Option<usize>, remaining_valence: usize| -> f32 {
+        if remaining_valence == 0 {
+            return -1.0;
+        }
+        let cache_score = match cache_position {
+            Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+            Some(position) => {
+                let scaler = 1.0 / (OPTIMIZER_CACHE_SIZE - 3) as f32;
+                (1.0 - (position - 3) as f32 * scaler).powf(CACHE_DECAY_POWER)
+            }
+            None => 0.0,
+        };
+        cache_score + VALENCE_BOOST_SCALE * (remaining_valence as f32).powf(-VALENCE_BOOST_POWER)
+    };
+
+    let source = indices.to_vec();
+    let mut vertex_triangles = vec![Vec::new(); number_of_vertices];
+    source
+        .chunks_exact(3)
+        .enumerate()
+        .for_each(|(triangle, corners)| {
+            corners
+                .iter()
+                .for_each(|vertex| vertex_triangles[*vertex as usize].push(triangle));
+        });
+
+    let mut cache_positions = vec![None; number_of_vertices];
+    let mut vertex_scores = vertex_triangles
+        .iter()
+        .map(|triangles| vertex_score(None, triangles.len()))
+        .collect::<Vec<_>>();
+    let triangle_vertices = |triangle: usize| &source[triangle * 3..triangle * 3 + 3];
+    let mut triangle_scores = (0..number_of_triangles)
+        .map(|triangle| {
+            triangle_vertices(triangle)
+                .iter()
+                .map(|vertex| vertex_scores[*vertex as usize])
+                .sum::<f32>()
+        })
+        .collect::<Vec<_>>();
+    let mut emitted = vec![false; number_of_triangles];
+    let mut order = Vec::with_capacity(number_of_triangles);
+    let mut cache: Vec<u32> = Vec::with_capacity(OPTIMIZER_CACHE_SIZE + 3);
+    let mut next_unemitted = 0;
+
+    let mut best_triangle = None;
+    while order.len() < number_of_triangles {
+        let triangle = match best_triangle.take() {
+            Some(triangle) => triangle,
+            None => {
+                // Nothing in the cache is connected to a remaining triangle,
+                // so restart from the first triangle left to keep this linear
+                while emitted[next_unemitted] {
+                    next_unemitted += 1;
+                }
+                next_unemitted
+            }
+        };
+        emitted[triangle] = true;
+        order.push(triangle);
+
+        let corners = triangle_vertices(triangle).to_vec();
+        corners.iter().for_each(|vertex| {
+            let triangles = &mut vertex_triangles[*vertex as usize];
+            if let Some(position) = triangles.iter().position(|other| *other == triangle) {
+                triangles.swap_remove(position);
+            }
+        });
+
+        let mut new_cache = corners.clone();
+        new_cache.extend(cache.iter().filter(|vertex| !corners.contains(vertex)));
+        let evicted = new_cache.split_off(new_cache.len().min(OPTIMIZER_CACHE_SIZE));
+        evicted
+            .iter()
+            .for_each(|vertex| cache_positions[*vertex as usize] = None);
+        new_cache
+            .iter()
+            .enumerate()
+            .for_each(|(position, vertex)| cache_positions[*vertex as usize] = Some(position));
+        cache = new_cache;
+
+        cache.iter().chain(evicted.iter()).for_each(|vertex| {
+            let vertex = *vertex as usize;
+            vertex_scores[vertex] =
+                vertex_score(cache_positions[vertex], vertex_triangles[vertex].len());
+        });
+
+        let mut best_score = f32::MIN;
+        cache.iter().for_each(|vertex| {
+            vertex_triangles[*vertex as usize]
+                .iter()
+                .for_each(|triangle| {
+                    let score = triangle_vertices(*triangle)
+                        .iter()
+                        .map(|vertex| vertex_scores[*vertex as usize])
+                        .sum::<f32>();
+                    triangle_scores[*triangle] = score;
+                    if score > best_score {
+                        best_score = score;
+                        best_triangle = Some(*triangle);
+                    }
+                });
+        });
+    }
+
+    let reordered = order
+        .iter()
+        .flat_map(|triangle| triangle_vertices(*triangle).to_vec())
+        .collect::<Vec<_>>();
+    indices[..reordered.len()].copy_from_slice(&reordered);
+}
+
+/// Splits a cache optimized triangle list into clusters wherever the cache restarts,
+/// then draws the clusters facing most directly away from the mesh center first
+/// so they tend to occlude the rest of the mesh
+pub fn optimize_overdraw(indices: &mut [u32], vertices: &[crate::world::Vertex]) {
+    let number_of_triangles = indices.len() / 3;
+    if number_of_triangles == 0 {
+        return;
+    }
+
+    let position = |index: u32| vertices[index as usize].position;
+    let mesh_center = indices
+        .iter()
+        .map(|index| position(*index))
+        .sum::<nalgebra_glm::Vec3>()
+        / indices.len() as f32;
+
+    // A cluster starts wherever a triangle misses the cache on all three vertices
+    let mut cluster_starts = vec![0];
+    let mut cache = std::collections::VecDeque::with_capacity(STATISTICS_CACHE_SIZE);
+    indices
+        .chunks_exact(3)
+        .enumerate()
+        .for_each(|(triangle, corners)| {
+            let misses = corners
+                .iter()
+                .filter(|index| !cache.contains(*index))
+                .count();
+            if misses == 3 && triangle > 0 {
+                cluster_starts.push(triangle);
+            }
+            corners.iter().for_each(|index| {
+                if !cache.contains(index) {
+                    if cache.len() == STATISTICS_CACHE_SIZE {
+                        cache.pop_front();
+                    }
+                    cache.push_back(*index);
+                }
+            });
+        });
+    cluster_starts.push(number_of_triangles);
+
+    let mut clusters = cluster_starts
+        .windows(2)
+        .map(|range| {
+            let triangles = &indices[range[0] * 3..range[1] * 3];
+            let (mut center, mut normal, mut area) = (
+                nalgebra_glm::Vec3::zeros(),
+                nalgebra_glm::Vec3::zeros(),
+                0.0,
+            );
+            triangles.chunks_exact(3).for_each(|corners| {
+                let [a, b, c] = [corners[0], corners[1], corners[2]].map(position);
+                let face = (b - a).cross(&(c - a));
+                let face_area = face.norm();
+                center += (a + b + c) / 3.0 * face_area;
+                normal += face;
+                area += face_area;
+            });
+            let sort_key = match normal.try_normalize(f32::EPSILON) {
+                Some(normal) if area > 0.0 => (center / area - mesh_center).dot(&normal),
+                _ => f32::MIN,
+            };
+            (sort_key, triangles.to_vec())
+        })
+        .collect::<Vec<_>>();
+    clusters.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let reordered = clusters
+        .into_iter()
+        .flat_map(|(_, triangles)| triangles)
+        .collect::<Vec<_>>();
+    indices[..reordered.len()].copy_from_slice(&reordered);
+}
+
+/// Reorders vertices into the order they are first referenced and drops unreferenced vertices
+pub fn optimize_vertex_fetch(vertices: &mut Vec<crate::world::Vertex>, indices: &mut [u32]) {
+    let mut remap = vec![None; vertices.len()];
+    let mut reordered = Vec::with_capacity(vertices.len());
+    indices.iter_mut().for_each(|index| {
+        let new_index = *remap[*index as usize].get_or_insert_with(|| {
+            reordered.push(vertices[*index as usize]);
+            reordered.len() as u32 - 1
+        });
+        *index = new_index;
+    });
+    *vertices = reordered;
+}
+
+#[cfg(test)]
+mod tests {
+    /// An unindexed grid of quads with its triangles in a scattered order
+    fn scattered_grid(size: usize) -> Vec<crate::world::Vertex> {
+        let number_of_quads = size * size;
+        // Stepping by a number coprime to the quad count visits every quad once
+        (0..number_of_quads)
+            .map(|quad| (quad * 7) % number_of_quads)
+            .flat_map(|quad| {
+                let (x, y) = ((quad % size) as f32, (quad / size) as f32);
+                [
+                    (x, y),
+                    (x + 1.0, y),
+                    (x + 1.0, y + 1.0),
+                    (x, y),
+                    (x + 1.0, y + 1.0),
+                    (x, y + 1.0),
+                ]
+            })
+            .map(|(x, y)| crate::world::Vertex {
+                position: nalgebra_glm::vec3(x, y, 0.0),
+                uv_0: nalgebra_glm::vec2(x, y) / size as f32,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Triangles by position, rotated to start at their smallest corner so winding is kept
+    fn triangle_set(vertices: &[crate::world::Vertex], indices: &[u32]) -> Vec<[[u32; 3]; 3]> {
+        let mut triangles = indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let corners = [triangle[0], triangle[1], triangle[2]]
+                    .map(|index| vertices[index as usize].position.map(f32::to_bits).into());
+                let first = (0..3).min_by_key(|corner| corners[*corner]).unwrap();
+                [0, 1, 2].map(|corner| corners[(first + corner) % 3])
+            })
+            .collect::<Vec<_>>();
+        triangles.sort();
+        triangles
+    }
+
+    #[test]
+    fn welding_merges_identical_vertices() {
+        let mut vertices = scattered_grid(2);
+        let mut indices = Vec::new();
+        super::weld_vertices(&mut vertices, &mut indices);
+
+        assert_eq!(vertices.len(), 9);
+        assert_eq!(indices.len(), 24);
+        assert_eq!(
+            triangle_set(&vertices, &indices),
+            triangle_set(&scattered_grid(2), &(0..24).collect::<Vec<_>>())
+        );
+    }
+
+    #[test]
+    fn indices_are_generated_without_welding() {
+        let (mut vertices, mut indices) = (scattered_grid(2), Vec::new());
+        let mut topology = crate::world::PrimitiveTopology::Triangles;
+        let report = super::optimize_primitive(
+            &mut vertices,
+            &mut indices,
+            &mut topology,
+            &super::OptimizeOptions {
+                weld_vertices: false,
+                optimize_vertex_cache: false,
+                optimize_overdraw: false,
+                optimize_vertex_fetch: false,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(vertices.len(), 24);
+        assert_eq!(indices, (0..24).collect::<Vec<_>>());
+        assert_eq!(report.after.indices, 24);
+        assert_eq!(report.before.transformed_vertices, 24);
+    }
+
+    #[test]
+    fn optimizing_keeps_every_triangle() {
+        let original = scattered_grid(8);
+        let original_indices = (0..original.len() as u32).collect::<Vec<_>>();
+        let (mut vertices, mut indices) = (original.clone(), Vec::new());
+        let mut topology = crate::world::PrimitiveTopology::Triangles;
+        let report = super::optimize_primitive(
+            &mut vertices,
+            &mut indices,
+            &mut topology,
+            &super::OptimizeOptions::default(),
+        );
+
+        assert_eq!(topology, crate::world::PrimitiveTopology::Triangles);
+        assert_eq!(
+            triangle_set(&vertices, &indices),
+            triangle_set(&original, &original_indices)
+        );
+
+        assert_eq!(
+            report.before,
+            super::primitive_statistics(&original, &[], topology)
+        );
+        assert_eq!(
+            report.after,
+            super::primitive_statistics(&vertices, &indices, topology)
+        );
+        assert_eq!(report.before.vertices, 384);
+        assert_eq!(report.before.indices, 0);
+        assert_eq!(report.after.vertices, 81);
+        assert_eq!(report.after.indices, 384);
+        assert_eq!(report.after.triangles, 128);
+        assert_eq!(report.after.referenced_vertices, 81);
+        assert!(report.after.acmr() < report.before.acmr());
+
+        // Vertex fetch order follows the first use of each vertex
+        let mut seen = vec![false; vertices.len()];
+        let first_uses = indices
+            .iter()
+            .filter(|index| !std::mem::replace(&mut seen[**index as usize], true))
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(first_uses, (0..81).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,663 @@
+use nalgebra_glm as glm;
+
+/// Imports an ascii or binary ply file as a single mesh.
+/// Files without faces become point clouds, and vertex colors are read into `Vertex::color_0`.
+pub fn import_ply(
+    path: impl AsRef<std::path::Path>,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    import_ply_with_progress(path, &crate::gltf::ImportOptions::default(), &mut |_| true)
+}
+
+pub fn import_ply_with_progress(
+    path: impl AsRef<std::path::Path>,
+    options: &crate::gltf::ImportOptions,
+    progress: &mut dyn FnMut(crate::gltf::ImportProgress) -> bool,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|source| crate::gltf::ImportError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    import_ply_from_slice_with_progress(&bytes, options, progress)
+}
+
+/// Faces without vertex normals get normals generated with the options' normal mode
+pub fn import_ply_from_slice_with_progress(
+    bytes: &[u8],
+    options: &crate::gltf::ImportOptions,
+    progress: &mut dyn FnMut(crate::gltf::ImportProgress) -> bool,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    crate::gltf::report_progress(progress, crate::gltf::ImportStage::Parsing, 0, 1)?;
+    let ply = parse_ply(bytes)?;
+
+    crate::gltf::report_progress(progress, crate::gltf::ImportStage::BuildingMeshes, 0, 1)?;
+    let scale = options.unit_scale.unwrap_or(1.0);
+    let mut vertices = ply.vertices;
+    vertices
+        .iter_mut()
+        .for_each(|vertex| vertex.position *= scale);
+
+    let mut world = crate::world::World::default();
+    let primitive = if ply.faces.is_empty() {
+        crate::import::add_primitive(
+            &mut world,
+            vertices,
+            Vec::new(),
+            crate::world::PrimitiveTopology::Points,
+            None,
+            options,
+        )
+    } else {
+        // Polygons are triangulated as fans, which assumes they are convex
+        let mut indices = ply
+            .faces
+            .iter()
+            .flat_map(|face| {
+                (1..face.len().saturating_sub(1))
+                    .flat_map(move |corner| [face[0], face[corner], face[corner + 1]])
+            })
+            .collect::<Vec<_>>();
+        let mut topology = crate::world::PrimitiveTopology::Triangles;
+        if !ply.has_normals {
+            crate::geometry::generate_normals(
+                &mut vertices,
+                &mut indices,
+                &mut topology,
+                options.normal_mode,
+            );
+        }
+        crate::import::add_primitive(&mut world, vertices, indices, topology, None, options)
+    };
+    world.meshes.push(crate::world::Mesh {
+        primitives: vec![primitive],
+        ..Default::default()
+    });
+
+    crate::gltf::report_progress(progress, crate::gltf::ImportStage::BuildingScenes, 0, 1)?;
+    crate::import::add_mesh_scene(&mut world, vec!["Mesh".to_string()]);
+    world.compute_bounds();
+
+    if let Some(lod_options) = options.lods.as_ref() {
+        crate::lod::generate_lods(&mut world, lod_options);
+    }
+
+    Ok(world)
+}
+
+struct Ply {
+    vertices: Vec<crate::world::Vertex>,
+    has_normals: bool,
+    faces: Vec<Vec<u32>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// The value integer colors are divided by to map them to the unit range
+    fn color_range(&self) -> f64 {
+        match self {
+            Self::U8 | Self::I8 => 255.0,
+            Self::U16 | Self::I16 => 65535.0,
+            Self::U32 | Self::I32 => u32::MAX as f64,
+            Self::F32 | Self::F64 => 1.0,
+        }
+    }
+}
+
+enum Property {
+    Scalar {
+        name: String,
+        scalar_type: ScalarType,
+    },
+    List {
+        name: String,
+        count_type: ScalarType,
+        item_type: ScalarType,
+    },
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+fn malformed(reason: impl Into<String>) -> crate::gltf::ImportError {
+    crate::gltf::ImportError::Malformed(reason.into())
+}
+
+fn parse_ply(bytes: &[u8]) -> Result<Ply, crate::gltf::ImportError> {
+    // The header is read line by line, so end_header inside a comment does not end it
+    let mut header = Vec::new();
+    let mut body_start = None;
+    let mut offset = 0;
+    for line in bytes.split_inclusive(|byte| *byte == b'\n') {
+        offset += line.len();
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\n', '\r']);
+        if line == "end_header" {
+            body_start = Some(offset);
+            break;
+        }
+        header.push(line.to_string());
+    }
+    let body_start = body_start.ok_or_else(|| malformed("the ply header has no end_header"))?;
+
+    let mut lines = header.iter().map(|line| line.trim());
+    if lines.next() != Some("ply") {
+        return Err(malformed("the file does not start with ply"));
+    }
+    let mut encoding = None;
+    let mut elements = Vec::<Element>::new();
+    for line in lines {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens[..] {
+            ["format", format, _] => {
+                encoding = Some(match format {
+                    "ascii" => Encoding::Ascii,
+                    "binary_little_endian" => Encoding::BinaryLittleEndian,
+                    "binary_big_endian" => Encoding::BinaryBigEndian,
+                    _ => return Err(malformed(format!("unknown ply format {format}"))),
+                });
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| malformed(format!("{count} is not an element count")))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_type, item_type, name] => {
+                let scalar_type = |name: &str| {
+                    ScalarType::parse(name)
+                        .ok_or_else(|| malformed(format!("unknown property type {name}")))
+                };
+                elements
+                    .last_mut()
+                    .ok_or_else(|| malformed("a property is outside of an element"))?
+                    .properties
+                    .push(Property::List {
+                        name: name.to_string(),
+                        count_type: scalar_type(count_type)?,
+                        item_type: scalar_type(item_type)?,
+                    });
+            }
+            ["property", scalar_type, name] => elements
+                .last_mut()
+                .ok_or_else(|| malformed("a property is outside of an element"))?
+                .properties
+                .push(Property::Scalar {
+                    name: name.to_string(),
+                    scalar_type: ScalarType::parse(scalar_type)
+                        .ok_or_else(|| malformed(format!("unknown property type {scalar_type}")))?,
+                }),
+            _ => {}
+        }
+    }
+    let encoding = encoding.ok_or_else(|| malformed("the ply header has no format"))?;
+    // Items without properties take up no data, so their count could never be checked against the body
+    if let Some(element) = elements
+        .iter()
+        .find(|element| element.count > 0 && element.properties.is_empty())
+    {
+        return Err(malformed(format!(
+            "element {} has {} items but no properties",
+            element.name, element.count
+        )));
+    }
+
+    let body = &bytes[body_start..];
+    let ascii_body = String::from_utf8_lossy(if encoding == Encoding::Ascii {
+        body
+    } else {
+        &[]
+    });
+    let mut values = match encoding {
+        Encoding::Ascii => Values::Ascii(ascii_body.split_whitespace()),
+        Encoding::BinaryLittleEndian | Encoding::BinaryBigEndian => Values::Binary {
+            bytes: body,
+            offset: 0,
+            big_endian: encoding == Encoding::BinaryBigEndian,
+        },
+    };
+
+    let mut ply = Ply {
+        vertices: Vec::new(),
+        has_normals: false,
+        faces: Vec::new(),
+    };
+    for element in elements.iter() {
+        match element.name.as_str() {
+            "vertex" => read_vertices(element, &mut values, &mut ply)?,
+            "face" => read_faces(element, &mut values, &mut ply)?,
+            // Other elements such as edges and materials are read past
+            _ => (0..element.count).try_for_each(|_| {
+                element
+                    .properties
+                    .iter()
+                    .try_for_each(|property| read_property(property, &mut values).map(|_| ()))
+            })?,
+        }
+    }
+
+    let number_of_vertices = ply.vertices.len() as u32;
+    if ply
+        .faces
+        .iter()
+        .flatten()
+        .any(|index| *index >= number_of_vertices)
+    {
+        return Err(malformed("a face refers to a vertex that does not exist"));
+    }
+    Ok(ply)
+}
+
+fn read_vertices(
+    element: &Element,
+    values: &mut Values,
+    ply: &mut Ply,
+) -> Result<(), crate::gltf::ImportError> {
+    let has_property = |names: &[&str]| {
+        element.properties.iter().any(|property| {
+            matches!(property, Property::Scalar { name, .. } if names.contains(&name.as_str()))
+        })
+    };
+    ply.has_normals = ["nx", "ny", "nz"].iter().all(|name| has_property(&[name]));
+
+    // Counts come from the header, so nothing is reserved before the body proves they hold
+    for _ in 0..element.count {
+        let mut vertex = crate::world::Vertex::default();
+        let mut normal = glm::Vec3::zeros();
+        for property in element.properties.iter() {
+            let Property::Scalar { name, scalar_type } = property else {
+                read_property(property, values)?;
+                continue;
+            };
+            let value = values.read(*scalar_type)?;
+            let color = (value / scalar_type.color_range()) as f32;
+            let value = value as f32;
+            match name.as_str() {
+                "x" => vertex.position.x = value,
+                "y" => vertex.position.y = value,
+                "z" => vertex.position.z = value,
+                "nx" => normal.x = value,
+                "ny" => normal.y = value,
+                "nz" => normal.z = value,
+                "red" | "diffuse_red" | "r" => vertex.color_0.x = color,
+                "green" | "diffuse_green" | "g" => vertex.color_0.y = color,
+                "blue" | "diffuse_blue" | "b" => vertex.color_0.z = color,
+                "s" | "u" | "texture_u" | "texture_s" => vertex.uv_0.x = value,
+                // Ply textures have their origin at the bottom left
+                "t" | "v" | "texture_v" | "texture_t" => vertex.uv_0.y = 1.0 - value,
+                _ => {}
+            }
+        }
+        if ply.has_normals {
+            vertex.normal = normal;
+        }
+        ply.vertices.push(vertex);
+    }
+    Ok(())
+}
+
+fn read_faces(
+    element: &Element,
+    values: &mut Values,
+    ply: &mut Ply,
+) -> Result<(), crate::gltf::ImportError> {
+    for _ in 0..element.count {
+        for property in element.properties.iter() {
+            let items = read_property(property, values)?;
+            if let Property::List { name, .. } = property {
+                if name == "vertex_indices" || name == "vertex_index" {
+                    let face = items
+                        .into_iter()
+                        .map(|index| {
+                            if index >= 0.0 && index <= u32::MAX as f64 && index.fract() == 0.0 {
+                                Ok(index as u32)
+                            } else {
+                                Err(malformed(format!("{index} is not a vertex index")))
+                            }
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    ply.faces.push(face);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads a scalar as a single value or every item of a list
+fn read_property(
+    property: &Property,
+    values: &mut Values,
+) -> Result<Vec<f64>, crate::gltf::ImportError> {
+    match property {
+        Property::Scalar { scalar_type, .. } => Ok(vec![values.read(*scalar_type)?]),
+        Property::List {
+            count_type,
+            item_type,
+            ..
+        } => {
+            let count = values.read(*count_type)? as usize;
+            (0..count).map(|_| values.read(*item_type)).collect()
+        }
+    }
+}
+
+enum Values<'a> {
+    Ascii(std::str::SplitWhitespace<'a>),
+    Binary {
+        bytes: &'a [u8],
+        offset: usize,
+        big_endian: bool,
+    },
+}
+
+impl Values<'_> {
+    fn read(&mut self, scalar_type: ScalarType) -> Result<f64, crate::gltf::ImportError> {
+        match self {
+            Self::Ascii(tokens) => {
+                let token = tokens
+                    .next()
+                    .ok_or_else(|| malformed("the ply data ends early"))?;
+                token
+                    .parse::<f64>()
+                    .map_err(|_| malformed(format!("{token} is not a number")))
+            }
+            Self::Binary {
+                bytes,
+                offset,
+                big_endian,
+            } => {
+                let size = scalar_type.size();
+                let mut value = bytes
+                    .get(*offset..*offset + size)
+                    .ok_or_else(|| malformed("the ply data ends early"))?
+                    .to_vec();
+                *offset += size;
+                if *big_endian {
+                    value.reverse();
+                }
+                Ok(match scalar_type {
+                    ScalarType::I8 => value[0] as i8 as f64,
+                    ScalarType::U8 => value[0] as f64,
+                    ScalarType::I16 => i16::from_le_bytes([value[0], value[1]]) as f64,
+                    ScalarType::U16 => u16::from_le_bytes([value[0], value[1]]) as f64,
+                    ScalarType::I32 => {
+                        i32::from_le_bytes([value[0], value[1], value[2], value[3]]) as f64
+                    }
+                    ScalarType::U32 => {
+                        u32::from_le_bytes([value[0], value[1], value[2], value[3]]) as f64
+                    }
+                    ScalarType::F32 => {
+                        f32::from_le_bytes([value[0], value[1], value[2], value[3]]) as f64
+                    }
+                    ScalarType::F64 => f64::from_le_bytes(value[..8].try_into().unwrap()),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn import(bytes: &[u8]) -> crate::world::World {
+        super::import_ply_from_slice_with_progress(
+            bytes,
+            &crate::gltf::ImportOptions {
+                unit_scale: Some(0.5),
+                ..Default::default()
+            },
+            &mut |_| true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn ascii_faces_keep_vertex_colors() {
+        let world = import(
+            b"ply
+format ascii 1.0
+comment a colored quad
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+2 0 0 0 255 0
+2 2 0 0 0 255
+0 2 0 255 255 255
+4 0 1 2 3
+",
+        );
+
+        let primitive = &world.meshes[0].primitives[0];
+        assert_eq!(
+            primitive.topology,
+            crate::world::PrimitiveTopology::Triangles
+        );
+        // Missing normals are generated flat, unwelding the two triangles
+        assert_eq!(primitive.number_of_vertices, 6);
+        let vertices = &world.vertices[..6];
+        assert!(vertices
+            .iter()
+            .all(|vertex| vertex.normal == nalgebra_glm::Vec3::z()));
+        assert_eq!(vertices[1].position, nalgebra_glm::vec3(1.0, 0.0, 0.0));
+        assert_eq!(vertices[1].color_0.xyz(), nalgebra_glm::vec3(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn point_clouds_become_point_primitives() {
+        let mut bytes = b"ply
+format binary_little_endian 1.0
+element vertex 2
+property double x
+property double y
+property double z
+property float red
+property float green
+property float blue
+end_header
+"
+        .to_vec();
+        [[2.0f64, 4.0, 6.0], [-2.0, 0.0, 8.0]]
+            .iter()
+            .for_each(|position| {
+                position
+                    .iter()
+                    .for_each(|value| bytes.extend(value.to_le_bytes()));
+                [0.25f32, 0.5, 0.75]
+                    .iter()
+                    .for_each(|value| bytes.extend(value.to_le_bytes()));
+            });
+        let world = import(&bytes);
+
+        let primitive = &world.meshes[0].primitives[0];
+        assert_eq!(primitive.topology, crate::world::PrimitiveTopology::Points);
+        assert_eq!(primitive.number_of_vertices, 2);
+        assert_eq!(primitive.number_of_indices, 0);
+        assert_eq!(
+            world.vertices[0].position,
+            nalgebra_glm::vec3(1.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            world.vertices[1].color_0.xyz(),
+            nalgebra_glm::vec3(0.25, 0.5, 0.75)
+        );
+    }
+
+    #[test]
+    fn big_endian_normals_are_kept() {
+        let mut bytes = b"ply
+format binary_big_endian 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+element face 1
+property list uchar uint vertex_indices
+end_header
+"
+        .to_vec();
+        [[0.0f32, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.0]]
+            .iter()
+            .for_each(|position| {
+                position
+                    .iter()
+                    .chain([0.0, 1.0, 0.0].iter())
+                    .for_each(|value| bytes.extend(value.to_be_bytes()));
+            });
+        bytes.push(3);
+        [0u32, 1, 2]
+            .iter()
+            .for_each(|index| bytes.extend(index.to_be_bytes()));
+        let world = import(&bytes);
+
+        let primitive = &world.meshes[0].primitives[0];
+        assert_eq!(primitive.number_of_vertices, 3);
+        assert_eq!(primitive.number_of_indices, 3);
+        assert_eq!(
+            world.vertices[2].position,
+            nalgebra_glm::vec3(0.0, 1.0, 0.0)
+        );
+        assert!(world.vertices[..3]
+            .iter()
+            .all(|vertex| vertex.normal == nalgebra_glm::Vec3::y()));
+    }
+
+    fn import_error(bytes: &[u8]) -> crate::gltf::ImportError {
+        super::import_ply_from_slice_with_progress(
+            bytes,
+            &crate::gltf::ImportOptions::default(),
+            &mut |_| true,
+        )
+        .unwrap_err()
+    }
+
+    const TRIANGLE_HEADER: &str = "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0
+1 0 0
+0 1 0
+";
+
+    #[test]
+    fn faces_with_negative_or_fractional_indices_are_rejected() {
+        ["3 0 -1 2", "3 0 1.5 2", "3 0 nan 2"]
+            .into_iter()
+            .for_each(|face| {
+                let error = import_error(format!("{TRIANGLE_HEADER}{face}\n").as_bytes());
+                assert!(
+                    matches!(&error, crate::gltf::ImportError::Malformed(reason) if reason.contains("is not a vertex index")),
+                    "{face}: {error}"
+                );
+            });
+    }
+
+    #[test]
+    fn header_counts_larger_than_the_body_are_rejected() {
+        [
+            "ply\nformat ascii 1.0\nelement vertex 18446744073709551615\nproperty float x\nend_header\n0\n",
+            "ply\nformat binary_little_endian 1.0\nelement face 4294967295\nproperty list uchar int vertex_indices\nend_header\n",
+        ]
+        .into_iter()
+        .for_each(|source| {
+            let error = import_error(source.as_bytes());
+            assert!(
+                matches!(&error, crate::gltf::ImportError::Malformed(reason) if reason == "the ply data ends early"),
+                "{error}"
+            );
+        });
+    }
+
+    #[test]
+    fn elements_without_properties_are_rejected() {
+        [
+            "ply\nformat ascii 1.0\nelement vertex 18446744073709551615\nend_header\n",
+            "ply\nformat binary_little_endian 1.0\nelement face 18446744073709551615\nend_header\n",
+        ]
+        .into_iter()
+        .for_each(|source| {
+            let error = import_error(source.as_bytes());
+            assert!(
+                matches!(&error, crate::gltf::ImportError::Malformed(reason) if reason.contains("but no properties")),
+                "{error}"
+            );
+        });
+    }
+
+    #[test]
+    fn only_a_whole_end_header_line_ends_the_header() {
+        let source = TRIANGLE_HEADER.replacen(
+            "format ascii 1.0\n",
+            "format ascii 1.0\ncomment exported before end_header was written\n",
+            1,
+        );
+        let world = import(format!("{source}3 0 1 2\n").as_bytes());
+        assert_eq!(world.meshes[0].primitives[0].number_of_vertices, 3);
+
+        let error = import_error(b"ply\nformat ascii 1.0\ncomment end_header\n");
+        assert!(
+            matches!(&error, crate::gltf::ImportError::Malformed(reason) if reason == "the ply header has no end_header"),
+            "{error}"
+        );
+    }
+}
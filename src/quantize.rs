@@ -0,0 +1,362 @@
+use nalgebra_glm as glm;
+
+/// Position, octahedral normal, snorm8 tangent and half float uvs
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct StaticVertex {
+    pub position: [f32; 3],
+    pub normal: [i16; 2],
+    pub tangent: [i8; 4],
+    pub uv_0: [u16; 2],
+    pub uv_1: [u16; 2],
+}
+
+/// A static vertex with a unorm8 color
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ColoredVertex {
+    pub vertex: StaticVertex,
+    pub color_0: [u8; 4],
+}
+
+/// A colored vertex with u16 joint indices and unorm8 joint weights
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkinnedVertex {
+    pub vertex: ColoredVertex,
+    pub joint_0: [u16; 4],
+    pub weight_0: [u8; 4],
+}
+
+/// Returns the smallest vertex format that can hold the vertices' attributes,
+/// falling back to the full format when values fall outside the quantized ranges
+/// or half float uvs would be too coarse to address single texels of a texture `texture_size` texels wide.
+/// Whether joints are present is passed in, as unskinned vertices have no reliable default weights.
+pub fn select_vertex_format(
+    vertices: &[crate::world::Vertex],
+    skinned: bool,
+    texture_size: u32,
+) -> crate::world::VertexFormat {
+    let default = crate::world::Vertex::default();
+    let fits_half_float = |uv: &glm::Vec2| {
+        uv.iter()
+            .all(|value| half_float_resolves(value.abs(), texture_size))
+    };
+    let fits_u16 = |joint: &glm::Vec4| {
+        joint
+            .iter()
+            .all(|value| (0.0..=u16::MAX as f32).contains(value) && value.fract() == 0.0)
+    };
+    if !vertices.iter().all(|vertex| {
+        fits_half_float(&vertex.uv_0) && fits_half_float(&vertex.uv_1) && fits_u16(&vertex.joint_0)
+    }) {
+        return crate::world::VertexFormat::Full;
+    }
+
+    let colored = vertices
+        .iter()
+        .any(|vertex| vertex.color_0 != default.color_0);
+    if skinned {
+        crate::world::VertexFormat::Skinned
+    } else if colored {
+        crate::world::VertexFormat::Colored
+    } else {
+        crate::world::VertexFormat::Static
+    }
+}
+
+/// Returns the size of the largest texture a material samples, or zero without a material or textures
+pub fn material_texture_size(
+    material: Option<&crate::world::Material>,
+    textures: &[crate::world::Texture],
+    images: &[crate::world::Image],
+) -> u32 {
+    let Some(material) = material else {
+        return 0;
+    };
+    [
+        material.base_color_texture_index,
+        material.emissive_texture_index,
+        material.normal_texture_index,
+        material.metallic_roughness_texture_index,
+        material.occlusion_texture_index,
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|texture_index| images.get(textures.get(texture_index)?.image_index))
+    .map(|image| image.width.max(image.height))
+    .max()
+    .unwrap_or(0)
+}
+
+/// Whether half floats step less than a texel apart at this magnitude.
+/// Halves keep 11 bits of mantissa, so the step between them doubles with every power of two.
+fn half_float_resolves(magnitude: f32, texture_size: u32) -> bool {
+    if magnitude > F16_MAX {
+        return false;
+    }
+    // Below the smallest normal exponent the step stays at that of subnormals
+    let exponent = magnitude.log2().floor().max(-14.0);
+    let step = 2.0f32.powf(exponent - 10.0);
+    step * texture_size as f32 <= 1.0
+}
+
+/// Packs vertices into the byte layout of a vertex format
+pub fn encode_vertices(
+    vertex_format: crate::world::VertexFormat,
+    vertices: &[crate::world::Vertex],
+) -> Vec<u8> {
+    match vertex_format {
+        crate::world::VertexFormat::Full => bytemuck::cast_slice(vertices).to_vec(),
+        crate::world::VertexFormat::Static => {
+            bytemuck::cast_slice(&vertices.iter().map(encode_static).collect::<Vec<_>>()).to_vec()
+        }
+        crate::world::VertexFormat::Colored => {
+            bytemuck::cast_slice(&vertices.iter().map(encode_colored).collect::<Vec<_>>()).to_vec()
+        }
+        crate::world::VertexFormat::Skinned => bytemuck::cast_slice(
+            &vertices
+                .iter()
+                .map(|vertex| SkinnedVertex {
+                    vertex: encode_colored(vertex),
+                    joint_0: vertex.joint_0.map(|joint| joint as u16).into(),
+                    weight_0: encode_weights(&vertex.weight_0),
+                })
+                .collect::<Vec<_>>(),
+        )
+        .to_vec(),
+    }
+}
+
+fn encode_static(vertex: &crate::world::Vertex) -> StaticVertex {
+    let normal = octahedral_encode(&vertex.normal);
+    StaticVertex {
+        position: vertex.position.into(),
+        normal: [snorm16(normal.x), snorm16(normal.y)],
+        tangent: vertex.tangent.map(snorm8).into(),
+        uv_0: vertex.uv_0.map(f32_to_f16).into(),
+        uv_1: vertex.uv_1.map(f32_to_f16).into(),
+    }
+}
+
+fn encode_colored(vertex: &crate::world::Vertex) -> ColoredVertex {
+    ColoredVertex {
+        vertex: encode_static(vertex),
+        color_0: vertex.color_0.push(1.0).map(unorm8).into(),
+    }
+}
+
+/// Maps a direction onto the unit octahedron and unfolds it into the [-1, 1] square
+pub fn octahedral_encode(direction: &glm::Vec3) -> glm::Vec2 {
+    let length = direction.x.abs() + direction.y.abs() + direction.z.abs();
+    if length <= f32::EPSILON {
+        return glm::vec2(0.0, 0.0);
+    }
+    let direction = direction / length;
+    let sign = |value: f32| if value >= 0.0 { 1.0 } else { -1.0 };
+    if direction.z >= 0.0 {
+        direction.xy()
+    } else {
+        glm::vec2(
+            (1.0 - direction.y.abs()) * sign(direction.x),
+            (1.0 - direction.x.abs()) * sign(direction.y),
+        )
+    }
+}
+
+pub fn octahedral_decode(encoded: &glm::Vec2) -> glm::Vec3 {
+    let mut direction = glm::vec3(
+        encoded.x,
+        encoded.y,
+        1.0 - encoded.x.abs() - encoded.y.abs(),
+    );
+    let fold = (-direction.z).max(0.0);
+    direction.x += if direction.x >= 0.0 { -fold } else { fold };
+    direction.y += if direction.y >= 0.0 { -fold } else { fold };
+    direction.normalize()
+}
+
+const F16_MAX: f32 = 65504.0;
+
+/// Converts to IEEE 754 half precision, rounding to the nearest representable value
+pub fn f32_to_f16(value: f32) -> u16 {
+    half::f16::from_f32(value).to_bits()
+}
+
+pub fn f16_to_f32(value: u16) -> f32 {
+    half::f16::from_bits(value).to_f32()
+}
+
+fn snorm16(value: f32) -> i16 {
+    (value.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+fn snorm8(value: f32) -> i8 {
+    (value.clamp(-1.0, 1.0) * i8::MAX as f32).round() as i8
+}
+
+/// Quantizes joint weights to unorm8, putting the rounding remainder on the largest weight
+/// so the quantized weights sum to the same total as the original ones
+fn encode_weights(weights: &glm::Vec4) -> [u8; 4] {
+    let mut encoded: [u8; 4] = weights.map(unorm8).into();
+    let total = unorm8(weights.sum()) as i32;
+    let remainder = total - encoded.iter().map(|weight| *weight as i32).sum::<i32>();
+    if let Some(largest) = (0..4).max_by(|a, b| weights[*a].total_cmp(&weights[*b])) {
+        encoded[largest] = (encoded[largest] as i32 + remainder).clamp(0, u8::MAX as i32) as u8;
+    }
+    encoded
+}
+
+fn unorm8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+
+    #[test]
+    fn finite_halves_round_trip() {
+        // Every finite half, including subnormals and both zeros, survives a trip through f32
+        (0..=u16::MAX)
+            .filter(|half| (half >> 10) & 0x1f != 0x1f)
+            .for_each(|half| assert_eq!(super::f32_to_f16(super::f16_to_f32(half)), half));
+    }
+
+    #[test]
+    fn halves_round_to_nearest() {
+        let smallest_subnormal = 2.0f32.powi(-24);
+        let smallest_normal = 2.0f32.powi(-14);
+        [
+            (1.0, 0x3c00),
+            (-2.0, 0xc000),
+            (0.5, 0x3800),
+            (super::F16_MAX, 0x7bff),
+            (smallest_subnormal, 0x0001),
+            (smallest_subnormal * 0.75, 0x0001),
+            (smallest_subnormal * 0.25, 0x0000),
+            (-smallest_subnormal * 0.25, 0x8000),
+            (smallest_subnormal * 1023.0, 0x03ff),
+            (smallest_normal, 0x0400),
+            // Rounding the largest subnormal up carries into the smallest normal exponent
+            (smallest_normal - 2.0f32.powi(-26), 0x0400),
+            // Rounding the largest mantissa below two up carries into the next exponent
+            (2.0 - 2.0f32.powi(-12), 0x4000),
+            (2.0 - 2.0f32.powi(-9), 0x3ffe),
+        ]
+        .into_iter()
+        .for_each(|(value, half)| {
+            assert_eq!(super::f32_to_f16(value), half, "{value}");
+        });
+        assert_eq!(super::f16_to_f32(0x0001), smallest_subnormal);
+        assert_eq!(super::f16_to_f32(0x03ff), smallest_subnormal * 1023.0);
+    }
+
+    #[test]
+    fn halves_overflow_to_infinity_and_keep_nan() {
+        [65519.0, 65520.0, 1.0e6, f32::MAX, f32::INFINITY]
+            .into_iter()
+            .zip([0x7bff, 0x7c00, 0x7c00, 0x7c00, 0x7c00])
+            .for_each(|(value, half)| {
+                assert_eq!(super::f32_to_f16(value), half, "{value}");
+                assert_eq!(super::f32_to_f16(-value), half | 0x8000, "{value}");
+            });
+        assert_eq!(super::f16_to_f32(0x7c00), f32::INFINITY);
+        assert_eq!(super::f16_to_f32(0xfc00), f32::NEG_INFINITY);
+        let nan = super::f32_to_f16(f32::NAN);
+        assert_eq!(nan & 0x7c00, 0x7c00);
+        assert_ne!(nan & 0x03ff, 0);
+        assert!(super::f16_to_f32(nan).is_nan());
+    }
+
+    #[test]
+    fn normals_round_trip_in_every_octant() {
+        let signs = [-1.0, 1.0];
+        signs.iter().for_each(|x_sign| {
+            signs.iter().for_each(|y_sign| {
+                signs.iter().for_each(|z_sign| {
+                    let directions = [
+                        (1.0, 0.0, 0.0),
+                        (0.0, 1.0, 0.0),
+                        (0.0, 0.0, 1.0),
+                        (1.0, 1.0, 1.0),
+                        (0.2, 0.7, 0.1),
+                        (0.9, 0.05, 0.4),
+                        (0.01, 0.3, 2.0),
+                    ];
+                    directions.into_iter().for_each(|(x, y, z)| {
+                        let direction = glm::vec3(x * x_sign, y * y_sign, z * z_sign).normalize();
+                        let encoded = super::octahedral_encode(&direction);
+                        assert!(encoded.x.abs() <= 1.0 && encoded.y.abs() <= 1.0);
+                        let decoded = super::octahedral_decode(&encoded);
+                        assert!(glm::distance(&decoded, &direction) < 1e-5, "{direction:?}");
+
+                        // Quantizing to snorm16 as the static vertex format does
+                        // stays within about 0.01 degrees
+                        let quantized =
+                            encoded.map(|value| super::snorm16(value) as f32 / i16::MAX as f32);
+                        let decoded = super::octahedral_decode(&quantized);
+                        assert!(glm::distance(&decoded, &direction) < 0.01f32.to_radians());
+                    });
+                });
+            });
+        });
+        assert_eq!(
+            super::octahedral_encode(&glm::Vec3::zeros()),
+            glm::Vec2::zeros()
+        );
+    }
+
+    #[test]
+    fn quantized_weights_keep_their_sum() {
+        [
+            glm::vec4(0.25, 0.25, 0.25, 0.25),
+            glm::vec4(0.1, 0.2, 0.3, 0.4),
+            glm::vec4(0.333, 0.333, 0.334, 0.0),
+            glm::vec4(0.002, 0.002, 0.002, 0.994),
+            glm::vec4(1.0, 0.0, 0.0, 0.0),
+        ]
+        .into_iter()
+        .for_each(|weight_0| {
+            let vertex = crate::world::Vertex {
+                weight_0,
+                joint_0: glm::vec4(0.0, 1.0, 2.0, 3.0),
+                ..Default::default()
+            };
+            let bytes = super::encode_vertices(crate::world::VertexFormat::Skinned, &[vertex]);
+            let encoded = bytemuck::from_bytes::<super::SkinnedVertex>(&bytes);
+            let weights = encoded.weight_0;
+            assert_eq!(
+                weights.iter().map(|weight| *weight as u32).sum::<u32>(),
+                255,
+                "{weight_0:?}"
+            );
+            weights
+                .iter()
+                .zip(weight_0.iter())
+                .for_each(|(weight, expected)| {
+                    assert!((*weight as f32 / 255.0 - expected).abs() <= 1.5 / 255.0);
+                });
+        });
+        assert_eq!(super::encode_weights(&glm::Vec4::zeros()), [0; 4]);
+    }
+
+    #[test]
+    fn uvs_too_coarse_for_the_texture_keep_full_precision() {
+        let select = |uv: f32, texture_size: u32| {
+            let vertex = crate::world::Vertex {
+                uv_0: glm::vec2(uv, 0.0),
+                ..Default::default()
+            };
+            super::select_vertex_format(&[vertex], false, texture_size)
+        };
+
+        // Halves step 2^-11 apart just below 1, exactly a texel of a 2048 texture
+        assert_eq!(select(0.9, 2048), crate::world::VertexFormat::Static);
+        assert_eq!(select(0.9, 4096), crate::world::VertexFormat::Full);
+        // Tiling uvs lose precision as they grow, even on small textures
+        assert_eq!(select(7.5, 256), crate::world::VertexFormat::Static);
+        assert_eq!(select(7.5, 512), crate::world::VertexFormat::Full);
+        assert_eq!(select(100_000.0, 0), crate::world::VertexFormat::Full);
+    }
+}
@@ -6,6 +6,7 @@ pub struct Renderer {
     pub grid: crate::grid::GridRender,
     pub sky: crate::sky::SkyRender,
     pub depth_texture_view: wgpu::TextureView,
+    pub scene_target: Option<SceneTarget>,
 }
 
 impl Renderer {
@@ -32,6 +33,7 @@ impl Renderer {
             grid,
             sky,
             depth_texture_view,
+            scene_target: None,
         }
     }
 
@@ -47,6 +49,16 @@ impl Renderer {
         );
     }
 
+    /// Switches between reverse-Z and standard depth, rebuilding every depth-testing pipeline.
+    /// The world view must be synced again afterwards.
+    pub fn set_reverse_z(&mut self, reverse_z: bool) {
+        self.gpu.reverse_z = reverse_z;
+        self.debug = crate::debug::DebugRender::new(&self.gpu);
+        self.grid = crate::grid::GridRender::new(&self.gpu);
+        self.sky = crate::sky::SkyRender::new(&self.gpu);
+        self.view = None;
+    }
+
     pub fn resize(&mut self, width: u32, height: u32) {
         self.gpu.resize(width, height);
         self.depth_texture_view = self.gpu.create_depth_texture(
@@ -70,16 +82,6 @@ impl Renderer {
     }
 
     fn end_frame(&mut self, context: &mut crate::app::Context) {
-        let mut encoder = self
-            .gpu
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
-
-        let (paint_jobs, screen_descriptor) =
-            self.gui.end_frame(&self.gpu, &context.window, &mut encoder);
-
         let surface_texture = self
             .gpu
             .surface
@@ -100,49 +102,172 @@ impl Renderer {
                     array_layer_count: None,
                 });
 
-        encoder.insert_debug_marker("Render scene");
+        self.sync_scene_target(context);
+
+        let (target_view, target_depth_view, target_width, target_height) =
+            match self.scene_target.as_ref() {
+                Some(scene_target) => (
+                    &scene_target.view,
+                    &scene_target.depth_texture_view,
+                    scene_target.width,
+                    scene_target.height,
+                ),
+                None => (
+                    &surface_texture_view,
+                    &self.depth_texture_view,
+                    self.gpu.surface_config.width,
+                    self.gpu.surface_config.height,
+                ),
+            };
+
+        let viewports = resolve_viewports(context, target_width, target_height);
+
+        // Each viewport is submitted separately so the per-renderer camera uniforms
+        // can be rewritten between viewports while sharing the uploaded world data
+        for (viewport_index, viewport) in viewports.iter().enumerate() {
+            let mut encoder =
+                self.gpu
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Viewport Encoder"),
+                    });
+
+            encoder.insert_debug_marker("Render scene");
+
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Viewport Render Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: target_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: if viewport_index == 0 {
+                                wgpu::LoadOp::Clear(CLEAR_COLOR)
+                            } else {
+                                wgpu::LoadOp::Load
+                            },
+                            store: true,
+                        },
+                    })],
+                    // Viewports may overlap, so depth is cleared for every viewport
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: target_depth_view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(self.gpu.depth_clear_value()),
+                            store: true,
+                        }),
+                        stencil_ops: None,
+                    }),
+                });
+
+                render_pass.set_viewport(
+                    viewport.x,
+                    viewport.y,
+                    viewport.width,
+                    viewport.height,
+                    0.0,
+                    1.0,
+                );
+
+                if context.debug_visible {
+                    self.grid
+                        .render(&mut render_pass, &self.gpu, context, viewport);
+                }
+
+                self.debug
+                    .render(&mut render_pass, &self.gpu, context, viewport);
+
+                if let Some(view) = self.view.as_mut() {
+                    view.render(&mut render_pass, &self.gpu, context, viewport);
+                }
+
+                self.sky
+                    .render(&mut render_pass, &self.gpu, context, viewport);
+            }
+
+            self.gpu.queue.submit(std::iter::once(encoder.finish()));
+        }
+
+        if let Some(path) = context.scene_screenshot_path.take() {
+            match self.scene_target.as_ref() {
+                Some(scene_target) => scene_target.save_screenshot(&self.gpu, &path),
+                None => log::warn!("Scene screenshots require an offscreen scene viewport"),
+            }
+        }
+
+        let mut picking = false;
+        if let (Some(view), Some(window_position)) = (self.view.as_mut(), context.pick_request) {
+            // Pick requests are in window pixels, but the scene may be drawn offscreen
+            let position = match context.scene_viewport {
+                Some(scene_viewport) if self.scene_target.is_some() => {
+                    window_position - nalgebra_glm::vec2(scene_viewport.x, scene_viewport.y)
+                }
+                _ => window_position,
+            };
+            let picked_viewport = viewports
+                .iter()
+                .rev()
+                .find(|viewport| viewport.contains(position));
+            match picked_viewport {
+                Some(viewport) if !view.is_pick_pending() => {
+                    let mut encoder =
+                        self.gpu
+                            .device
+                            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                                label: Some("Picking Encoder"),
+                            });
+                    view.render_ids(
+                        &mut encoder,
+                        &self.gpu,
+                        context,
+                        viewport,
+                        (target_width, target_height),
+                        position,
+                    );
+                    self.gpu.queue.submit(std::iter::once(encoder.finish()));
+                    context.pick_request = None;
+                    picking = true;
+                }
+                Some(_) => {}
+                None => context.pick_request = None,
+            }
+        }
+
+        let mut encoder = self
+            .gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        let (paint_jobs, screen_descriptor) =
+            self.gui.end_frame(&self.gpu, &context.window, &mut encoder);
 
-        // This scope around the render_pass prevents the
-        // render_pass from holding a borrow to the encoder,
-        // which would prevent calling `.finish()` in
-        // preparation for queue submission.
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
+                label: Some("GUI Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &surface_texture_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.19,
-                            g: 0.24,
-                            b: 0.42,
-                            a: 1.0,
-                        }),
+                        load: if viewports.is_empty() || self.scene_target.is_some() {
+                            wgpu::LoadOp::Clear(CLEAR_COLOR)
+                        } else {
+                            wgpu::LoadOp::Load
+                        },
                         store: true,
                     },
                 })],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                     view: &self.depth_texture_view,
                     depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
+                        load: wgpu::LoadOp::Clear(self.gpu.depth_clear_value()),
                         store: true,
                     }),
                     stencil_ops: None,
                 }),
             });
 
-            if context.debug_visible {
-                self.grid.render(&mut render_pass, &self.gpu, context);
-                self.debug.render(&mut render_pass, &self.gpu, context);
-            }
-
-            if let Some(view) = self.view.as_mut() {
-                view.render(&mut render_pass, &self.gpu, context);
-            }
-
-            self.sky.render(&mut render_pass, &self.gpu, context);
-
             self.gui
                 .renderer
                 .render(&mut render_pass, &paint_jobs, &screen_descriptor);
@@ -151,9 +276,363 @@ impl Renderer {
         self.gpu.queue.submit(std::iter::once(encoder.finish()));
 
         surface_texture.present();
+
+        if let Some(view) = self.view.as_mut() {
+            if picking {
+                view.map_pick_readback();
+            }
+            if let Some(pick_result) = view.receive_pick_result(&self.gpu) {
+                context.pick_result = Some(pick_result);
+            }
+        }
+    }
+
+    /// Creates, resizes or releases the offscreen scene texture to match the gui's request
+    fn sync_scene_target(&mut self, context: &mut crate::app::Context) {
+        match context.scene_viewport {
+            Some(scene_viewport) => {
+                let (width, height) = (scene_viewport.width.max(1), scene_viewport.height.max(1));
+                let up_to_date = self.scene_target.as_ref().map_or(false, |target| {
+                    target.width == width && target.height == height
+                });
+                if !up_to_date {
+                    let texture_id = self.scene_target.take().map(|target| target.texture_id);
+                    self.scene_target = Some(SceneTarget::new(
+                        &self.gpu,
+                        &mut self.gui.renderer,
+                        width,
+                        height,
+                        texture_id,
+                    ));
+                }
+            }
+            None => {
+                if let Some(scene_target) = self.scene_target.take() {
+                    self.gui.renderer.free_texture(&scene_target.texture_id);
+                }
+            }
+        }
+        context.scene_texture_id = self
+            .scene_target
+            .as_ref()
+            .map(|scene_target| scene_target.texture_id);
     }
 }
 
+/// Where the offscreen scene texture is displayed in the window, in physical pixels
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SceneViewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: u32,
+    pub height: u32,
+    /// The gui layer displaying the texture, which windows and popups can cover
+    pub layer_id: egui::LayerId,
+}
+
+impl SceneViewport {
+    pub fn contains(&self, position: nalgebra_glm::Vec2) -> bool {
+        position.x >= self.x
+            && position.y >= self.y
+            && position.x < self.x + self.width as f32
+            && position.y < self.y + self.height as f32
+    }
+
+    /// Whether a window position is over the viewport with no other gui layer covering it
+    pub fn is_exposed_at(&self, gui_context: &egui::Context, position: nalgebra_glm::Vec2) -> bool {
+        let point = position / gui_context.pixels_per_point();
+        let point = egui::pos2(point.x, point.y);
+        self.contains(position) && gui_context.layer_id_at(point) == Some(self.layer_id)
+    }
+}
+
+/// An offscreen color and depth target the world is rendered into
+/// so it can be displayed as an egui image
+pub struct SceneTarget {
+    pub width: u32,
+    pub height: u32,
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub depth_texture_view: wgpu::TextureView,
+    pub texture_id: egui::TextureId,
+}
+
+impl SceneTarget {
+    pub fn new(
+        gpu: &crate::gpu::Gpu,
+        gui_renderer: &mut egui_wgpu::Renderer,
+        width: u32,
+        height: u32,
+        texture_id: Option<egui::TextureId>,
+    ) -> Self {
+        let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Scene Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            // The world pipelines target the surface format
+            format: gpu.surface_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_texture_view = gpu.create_depth_texture(width, height);
+        let texture_id = match texture_id {
+            Some(texture_id) => {
+                gui_renderer.update_egui_texture_from_wgpu_texture(
+                    &gpu.device,
+                    &view,
+                    wgpu::FilterMode::Linear,
+                    texture_id,
+                );
+                texture_id
+            }
+            None => {
+                gui_renderer.register_native_texture(&gpu.device, &view, wgpu::FilterMode::Linear)
+            }
+        };
+        Self {
+            width,
+            height,
+            texture,
+            view,
+            depth_texture_view,
+            texture_id,
+        }
+    }
+
+    /// Reads back the scene texture and writes it to an image file.
+    /// This blocks until the copy has completed.
+    pub fn save_screenshot(&self, gpu: &crate::gpu::Gpu, path: &std::path::Path) {
+        let bgra = match self.texture.format() {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            format => {
+                log::warn!("Screenshots of {format:?} scene textures are not supported");
+                return;
+            }
+        };
+
+        let unpadded_bytes_per_row = self.width * 4;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let bytes_per_row = unpadded_bytes_per_row.div_ceil(alignment) * alignment;
+        let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Screenshot Buffer"),
+            size: (bytes_per_row * self.height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Screenshot Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        gpu.queue.submit(std::iter::once(encoder.finish()));
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        gpu.device.poll(wgpu::Maintain::Wait);
+        if !matches!(receiver.recv(), Ok(Ok(()))) {
+            log::error!("Failed to read back the scene texture");
+            return;
+        }
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * self.height) as usize);
+        {
+            let data = buffer.slice(..).get_mapped_range();
+            data.chunks(bytes_per_row as usize).for_each(|row| {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            });
+        }
+        buffer.unmap();
+
+        if bgra {
+            pixels
+                .chunks_exact_mut(4)
+                .for_each(|pixel| pixel.swap(0, 2));
+        }
+
+        match image::save_buffer(
+            path,
+            &pixels,
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+        ) {
+            Ok(()) => log::info!("Saved scene screenshot to {}", path.display()),
+            Err(error) => log::error!("Failed to save scene screenshot: {error}"),
+        }
+    }
+}
+
+const CLEAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.19,
+    g: 0.24,
+    b: 0.42,
+    a: 1.0,
+};
+
+/// A camera rendering into a region of the render target
+#[derive(Debug, Copy, Clone)]
+pub struct Viewport {
+    pub camera_graph_node_index: petgraph::graph::NodeIndex,
+    /// Normalized rectangle of the render target, with the origin at the top left
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    pub fn fullscreen(camera_graph_node_index: petgraph::graph::NodeIndex) -> Self {
+        Self {
+            camera_graph_node_index,
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+
+    /// Splits the target into `columns * rows` viewports, ordered left to right, top to bottom
+    pub fn grid(
+        camera_graph_node_indices: &[petgraph::graph::NodeIndex],
+        columns: usize,
+        rows: usize,
+    ) -> Vec<Self> {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        camera_graph_node_indices
+            .iter()
+            .take(columns * rows)
+            .enumerate()
+            .map(|(index, camera_graph_node_index)| Self {
+                camera_graph_node_index: *camera_graph_node_index,
+                x: (index % columns) as f32 / columns as f32,
+                y: (index / columns) as f32 / rows as f32,
+                width: 1.0 / columns as f32,
+                height: 1.0 / rows as f32,
+            })
+            .collect()
+    }
+}
+
+/// A viewport resolved to pixels and camera matrices for the current frame
+#[derive(Debug, Copy, Clone)]
+pub struct ViewportCamera {
+    pub camera_graph_node_index: petgraph::graph::NodeIndex,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub camera_position: nalgebra_glm::Vec3,
+    pub projection: nalgebra_glm::Mat4,
+    pub view: nalgebra_glm::Mat4,
+}
+
+impl ViewportCamera {
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width / self.height.max(1.0)
+    }
+
+    pub fn contains(&self, position: nalgebra_glm::Vec2) -> bool {
+        position.x >= self.x
+            && position.y >= self.y
+            && position.x < self.x + self.width
+            && position.y < self.y + self.height
+    }
+}
+
+/// Resolves the context's viewports against a render target of the given size.
+/// With no viewports configured, the active scene's default camera covers the whole target.
+pub fn resolve_viewports(
+    context: &crate::app::Context,
+    width: u32,
+    height: u32,
+) -> Vec<ViewportCamera> {
+    let Some(scene_index) = context.active_scene_index else {
+        return Vec::new();
+    };
+    let scene = &context.world.scenes[scene_index];
+
+    let default_viewport = [Viewport::fullscreen(scene.default_camera_graph_node_index)];
+    let viewports = if context.viewports.is_empty() {
+        &default_viewport[..]
+    } else {
+        &context.viewports[..]
+    };
+
+    let (width, height) = (width as f32, height as f32);
+    viewports
+        .iter()
+        .filter(|viewport| {
+            scene
+                .graph
+                .node_weight(viewport.camera_graph_node_index)
+                .map_or(false, |node_index| {
+                    context.world.nodes[*node_index].camera_index.is_some()
+                })
+        })
+        .filter_map(|viewport| {
+            let x = (viewport.x.clamp(0.0, 1.0) * width).floor();
+            let y = (viewport.y.clamp(0.0, 1.0) * height).floor();
+            let right = ((viewport.x + viewport.width).clamp(0.0, 1.0) * width).floor();
+            let bottom = ((viewport.y + viewport.height).clamp(0.0, 1.0) * height).floor();
+            if right <= x || bottom <= y {
+                return None;
+            }
+            let (viewport_width, viewport_height) = (right - x, bottom - y);
+            let (camera_position, mut projection, view) =
+                crate::world::create_camera_matrices_for_node(
+                    &context.world,
+                    scene,
+                    viewport.camera_graph_node_index,
+                    viewport_width / viewport_height,
+                );
+            if context.reverse_z {
+                projection = crate::world::reverse_z(&projection);
+            }
+            Some(ViewportCamera {
+                camera_graph_node_index: viewport.camera_graph_node_index,
+                x,
+                y,
+                width: viewport_width,
+                height: viewport_height,
+                camera_position,
+                projection,
+                view,
+            })
+        })
+        .collect()
+}
+
 impl From<crate::world::Sampler> for wgpu::SamplerDescriptor<'static> {
     fn from(sampler: crate::world::Sampler) -> Self {
         let min_filter = match sampler.min_filter {
@@ -204,3 +683,44 @@ impl From<crate::world::Sampler> for wgpu::SamplerDescriptor<'static> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn windows_over_the_scene_viewport_cover_it() {
+        let gui_context = egui::Context::default();
+        let mut layer_id = None;
+        // Areas are laid out in the first frame and known to the next one
+        (0..2).for_each(|_| {
+            let _ = gui_context.run(
+                egui::RawInput {
+                    screen_rect: Some(egui::Rect::from_min_size(
+                        egui::Pos2::ZERO,
+                        egui::vec2(800.0, 600.0),
+                    )),
+                    ..Default::default()
+                },
+                |gui_context| {
+                    egui::CentralPanel::default().show(gui_context, |ui| {
+                        layer_id = Some(ui.layer_id());
+                    });
+                    egui::Window::new("Inspector")
+                        .fixed_pos(egui::pos2(100.0, 100.0))
+                        .fixed_size(egui::vec2(200.0, 200.0))
+                        .show(gui_context, |ui| ui.label("Covers the viewport"));
+                },
+            );
+        });
+
+        let scene_viewport = super::SceneViewport {
+            x: 0.0,
+            y: 0.0,
+            width: 800,
+            height: 600,
+            layer_id: layer_id.unwrap(),
+        };
+        assert!(!scene_viewport.is_exposed_at(&gui_context, nalgebra_glm::vec2(150.0, 150.0)));
+        assert!(scene_viewport.is_exposed_at(&gui_context, nalgebra_glm::vec2(600.0, 500.0)));
+        assert!(!scene_viewport.is_exposed_at(&gui_context, nalgebra_glm::vec2(900.0, 500.0)));
+    }
+}
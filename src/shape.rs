@@ -0,0 +1,499 @@
+use nalgebra_glm as glm;
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShapeVertex {
+    pub position: glm::Vec3,
+    pub normal: glm::Vec3,
+}
+
+/// Procedurally generated geometry for a `Shape`,
+/// with triangle indices for solid rendering and line indices for wireframe rendering
+#[derive(Default, Debug, Clone)]
+pub struct ShapeMesh {
+    pub vertices: Vec<ShapeVertex>,
+    pub triangle_indices: Vec<u32>,
+    pub line_indices: Vec<u32>,
+}
+
+impl ShapeMesh {
+    fn append(&mut self, mesh: ShapeMesh) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(mesh.vertices);
+        self.triangle_indices
+            .extend(mesh.triangle_indices.iter().map(|index| index + offset));
+        self.line_indices
+            .extend(mesh.line_indices.iter().map(|index| index + offset));
+    }
+}
+
+pub fn create_shape_mesh(shape: &crate::world::Shape) -> ShapeMesh {
+    use crate::world::Shape;
+    match *shape {
+        Shape::Cube => create_cube(),
+        Shape::Sphere {
+            radius,
+            sectors,
+            stacks,
+        } => create_sphere(radius, sectors, stacks),
+        Shape::Capsule {
+            radius,
+            half_height,
+            sectors,
+            stacks,
+        } => create_capsule(radius, half_height, sectors, stacks),
+        Shape::Cylinder {
+            radius,
+            half_height,
+            sectors,
+        } => create_cylinder(radius, half_height, sectors),
+        Shape::Cone {
+            radius,
+            half_height,
+            sectors,
+        } => create_cone(radius, half_height, sectors),
+        Shape::Plane {
+            half_extents,
+            subdivisions,
+        } => create_plane(half_extents, subdivisions),
+        Shape::Torus {
+            major_radius,
+            minor_radius,
+            major_segments,
+            minor_segments,
+        } => create_torus(major_radius, minor_radius, major_segments, minor_segments),
+        Shape::Arrow {
+            length,
+            shaft_radius,
+            head_length,
+            head_radius,
+            sectors,
+        } => create_arrow(length, shaft_radius, head_length, head_radius, sectors),
+    }
+}
+
+/// Builds a `rows` x `columns` grid of quads from a function mapping grid coordinates to vertices.
+/// Rows are connected along the grid's first axis and wrapped columns skip the duplicate seam line,
+/// as does a last row that lands back on the first, such as the inner ring of a torus.
+fn create_grid(
+    rows: u32,
+    columns: u32,
+    wrap_columns: bool,
+    vertex: impl Fn(u32, u32) -> ShapeVertex,
+) -> ShapeMesh {
+    let mut mesh = ShapeMesh::default();
+    for row in 0..=rows {
+        for column in 0..=columns {
+            mesh.vertices.push(vertex(row, column));
+        }
+    }
+
+    let index = |row: u32, column: u32| row * (columns + 1) + column;
+    for row in 0..rows {
+        for column in 0..columns {
+            mesh.triangle_indices.extend_from_slice(&[
+                index(row, column),
+                index(row + 1, column),
+                index(row, column + 1),
+                index(row, column + 1),
+                index(row + 1, column),
+                index(row + 1, column + 1),
+            ]);
+        }
+    }
+
+    let is_degenerate = |a: u32, b: u32| {
+        glm::distance2(
+            &mesh.vertices[a as usize].position,
+            &mesh.vertices[b as usize].position,
+        ) < f32::EPSILON
+    };
+    let rows_wrap =
+        (0..=columns).all(|column| is_degenerate(index(0, column), index(rows, column)));
+    let line_rows = if rows_wrap { rows } else { rows + 1 };
+    let mut line_indices = Vec::new();
+    for row in 0..line_rows {
+        for column in 0..columns {
+            let (a, b) = (index(row, column), index(row, column + 1));
+            if !is_degenerate(a, b) {
+                line_indices.extend_from_slice(&[a, b]);
+            }
+        }
+    }
+    let line_columns = if wrap_columns { columns } else { columns + 1 };
+    for column in 0..line_columns {
+        for row in 0..rows {
+            let (a, b) = (index(row, column), index(row + 1, column));
+            if !is_degenerate(a, b) {
+                line_indices.extend_from_slice(&[a, b]);
+            }
+        }
+    }
+    mesh.line_indices = line_indices;
+
+    mesh
+}
+
+/// A point on the profile of a surface of revolution, as (radius, height)
+/// with its normal in the same radial plane
+#[derive(Copy, Clone)]
+struct ProfilePoint {
+    position: glm::Vec2,
+    normal: glm::Vec2,
+}
+
+fn profile_point(radius: f32, height: f32, normal: glm::Vec2) -> ProfilePoint {
+    ProfilePoint {
+        position: glm::vec2(radius, height),
+        normal: normal.normalize(),
+    }
+}
+
+/// Revolves a profile around the Y axis. Faces point along the profile's direction
+/// rotated clockwise in the (radius, height) plane, so profiles run upward for outer walls.
+fn create_lathe(profile: &[ProfilePoint], sectors: u32) -> ShapeMesh {
+    let sectors = sectors.max(3);
+    create_grid(profile.len() as u32 - 1, sectors, true, |row, column| {
+        let point = profile[row as usize];
+        let angle = column as f32 / sectors as f32 * std::f32::consts::TAU;
+        let (sin, cos) = angle.sin_cos();
+        ShapeVertex {
+            position: glm::vec3(
+                point.position.x * cos,
+                point.position.y,
+                point.position.x * sin,
+            ),
+            normal: glm::vec3(point.normal.x * cos, point.normal.y, point.normal.x * sin),
+        }
+    })
+}
+
+fn create_disk(radius: f32, height: f32, facing_up: bool, sectors: u32) -> ShapeMesh {
+    let normal = glm::vec2(0.0, if facing_up { 1.0 } else { -1.0 });
+    let center = profile_point(0.0, height, normal);
+    let rim = profile_point(radius, height, normal);
+    if facing_up {
+        create_lathe(&[rim, center], sectors)
+    } else {
+        create_lathe(&[center, rim], sectors)
+    }
+}
+
+fn create_cube() -> ShapeMesh {
+    let mut mesh = ShapeMesh::default();
+    let axes = [glm::Vec3::x(), glm::Vec3::y(), glm::Vec3::z()];
+    for axis_index in 0..3 {
+        for sign in [1.0, -1.0] {
+            let normal = axes[axis_index] * sign;
+            // The in-plane axes are ordered so their cross product points along the face normal
+            let u = axes[(axis_index + 1) % 3] * sign;
+            let v = axes[(axis_index + 2) % 3];
+            mesh.append(create_grid(1, 1, false, |row, column| ShapeVertex {
+                position: normal + u * (row as f32 * 2.0 - 1.0) + v * (column as f32 * 2.0 - 1.0),
+                normal,
+            }));
+        }
+    }
+    mesh
+}
+
+fn create_sphere(radius: f32, sectors: u32, stacks: u32) -> ShapeMesh {
+    let stacks = stacks.max(2);
+    let profile = (0..=stacks)
+        .map(|stack| {
+            let angle = (stack as f32 / stacks as f32 - 0.5) * std::f32::consts::PI;
+            let normal = glm::vec2(angle.cos(), angle.sin());
+            profile_point(normal.x * radius, normal.y * radius, normal)
+        })
+        .collect::<Vec<_>>();
+    create_lathe(&profile, sectors)
+}
+
+fn create_capsule(radius: f32, half_height: f32, sectors: u32, stacks: u32) -> ShapeMesh {
+    // Stacks are per hemisphere
+    let stacks = stacks.max(1);
+    let hemisphere = |from: f32, to: f32, center: f32| {
+        (0..=stacks).map(move |stack| {
+            let angle = from + (to - from) * stack as f32 / stacks as f32;
+            let normal = glm::vec2(angle.cos(), angle.sin());
+            profile_point(normal.x * radius, center + normal.y * radius, normal)
+        })
+    };
+    let profile = hemisphere(-std::f32::consts::FRAC_PI_2, 0.0, -half_height)
+        .chain(hemisphere(0.0, std::f32::consts::FRAC_PI_2, half_height))
+        .collect::<Vec<_>>();
+    create_lathe(&profile, sectors)
+}
+
+fn create_cylinder(radius: f32, half_height: f32, sectors: u32) -> ShapeMesh {
+    let mut mesh = create_disk(radius, -half_height, false, sectors);
+    let side = glm::vec2(1.0, 0.0);
+    mesh.append(create_lathe(
+        &[
+            profile_point(radius, -half_height, side),
+            profile_point(radius, half_height, side),
+        ],
+        sectors,
+    ));
+    mesh.append(create_disk(radius, half_height, true, sectors));
+    mesh
+}
+
+fn create_cone(radius: f32, half_height: f32, sectors: u32) -> ShapeMesh {
+    let mut mesh = create_disk(radius, -half_height, false, sectors);
+    let side = glm::vec2(2.0 * half_height, radius);
+    mesh.append(create_lathe(
+        &[
+            profile_point(radius, -half_height, side),
+            profile_point(0.0, half_height, side),
+        ],
+        sectors,
+    ));
+    mesh
+}
+
+fn create_plane(half_extents: glm::Vec2, subdivisions: u32) -> ShapeMesh {
+    let cells = subdivisions + 1;
+    create_grid(cells, cells, false, |row, column| ShapeVertex {
+        position: glm::vec3(
+            (column as f32 / cells as f32 * 2.0 - 1.0) * half_extents.x,
+            0.0,
+            (row as f32 / cells as f32 * 2.0 - 1.0) * half_extents.y,
+        ),
+        normal: glm::Vec3::y(),
+    })
+}
+
+fn create_torus(
+    major_radius: f32,
+    minor_radius: f32,
+    major_segments: u32,
+    minor_segments: u32,
+) -> ShapeMesh {
+    let minor_segments = minor_segments.max(3);
+    let profile = (0..=minor_segments)
+        .map(|segment| {
+            let angle = segment as f32 / minor_segments as f32 * std::f32::consts::TAU;
+            let normal = glm::vec2(angle.cos(), angle.sin());
+            profile_point(
+                major_radius + normal.x * minor_radius,
+                normal.y * minor_radius,
+                normal,
+            )
+        })
+        .collect::<Vec<_>>();
+    create_lathe(&profile, major_segments)
+}
+
+/// An arrow pointing along +Y starting at the origin
+fn create_arrow(
+    length: f32,
+    shaft_radius: f32,
+    head_length: f32,
+    head_radius: f32,
+    sectors: u32,
+) -> ShapeMesh {
+    let head_length = head_length.min(length);
+    let shaft_length = length - head_length;
+
+    let mut mesh = create_disk(shaft_radius, 0.0, false, sectors);
+    let side = glm::vec2(1.0, 0.0);
+    mesh.append(create_lathe(
+        &[
+            profile_point(shaft_radius, 0.0, side),
+            profile_point(shaft_radius, shaft_length, side),
+        ],
+        sectors,
+    ));
+    let down = glm::vec2(0.0, -1.0);
+    mesh.append(create_lathe(
+        &[
+            profile_point(shaft_radius, shaft_length, down),
+            profile_point(head_radius, shaft_length, down),
+        ],
+        sectors,
+    ));
+    let head = glm::vec2(head_length, head_radius);
+    mesh.append(create_lathe(
+        &[
+            profile_point(head_radius, shaft_length, head),
+            profile_point(0.0, length, head),
+        ],
+        sectors,
+    ));
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+
+    /// Checks every vertex normal is unit length and agrees with the winding of its triangles,
+    /// and that `outward` gives a direction each triangle faces away from
+    fn assert_outward(mesh: &super::ShapeMesh, outward: impl Fn(&glm::Vec3) -> glm::Vec3) {
+        assert!(mesh
+            .vertices
+            .iter()
+            .all(|vertex| (vertex.normal.norm() - 1.0).abs() < 1e-5));
+        mesh.triangle_indices.chunks_exact(3).for_each(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|corner| mesh.vertices[triangle[corner] as usize]);
+            let face_normal = (b.position - a.position).cross(&(c.position - a.position));
+            // Triangles collapsed onto a pole or apex have no facing
+            if face_normal.norm() < 1e-6 {
+                return;
+            }
+            let center = (a.position + b.position + c.position) / 3.0;
+            assert!(face_normal.dot(&outward(&center)) > 0.0, "{center:?}");
+            assert!([a, b, c]
+                .iter()
+                .all(|vertex| vertex.normal.dot(&face_normal) > 0.0));
+        });
+    }
+
+    fn assert_counts(mesh: &super::ShapeMesh, vertices: usize, triangles: usize) {
+        assert_eq!(mesh.vertices.len(), vertices);
+        assert_eq!(mesh.triangle_indices.len(), triangles * 3);
+        assert!(mesh
+            .triangle_indices
+            .iter()
+            .chain(mesh.line_indices.iter())
+            .all(|index| (*index as usize) < vertices));
+        assert_eq!(mesh.line_indices.len() % 2, 0);
+    }
+
+    #[test]
+    fn cubes_have_four_vertices_and_edges_per_face() {
+        let mesh = super::create_shape_mesh(&crate::world::Shape::Cube);
+        assert_counts(&mesh, 24, 12);
+        assert_eq!(mesh.line_indices.len(), 6 * 4 * 2);
+        assert!(mesh
+            .vertices
+            .iter()
+            .all(|vertex| vertex.position.abs() == glm::vec3(1.0, 1.0, 1.0)));
+        assert_outward(&mesh, |center| *center);
+    }
+
+    #[test]
+    fn spheres_and_capsules_face_away_from_their_axis() {
+        let mesh = super::create_shape_mesh(&crate::world::Shape::Sphere {
+            radius: 2.0,
+            sectors: 8,
+            stacks: 6,
+        });
+        assert_counts(&mesh, 7 * 9, 6 * 8 * 2);
+        assert!(mesh
+            .vertices
+            .iter()
+            .all(|vertex| (vertex.position.norm() - 2.0).abs() < 1e-5));
+        assert_outward(&mesh, |center| *center);
+
+        let mesh = super::create_shape_mesh(&crate::world::Shape::Capsule {
+            radius: 0.5,
+            half_height: 1.0,
+            sectors: 8,
+            stacks: 4,
+        });
+        assert_counts(&mesh, 10 * 9, 9 * 8 * 2);
+        assert_outward(&mesh, |center| {
+            *center - glm::vec3(0.0, center.y.clamp(-1.0, 1.0), 0.0)
+        });
+    }
+
+    #[test]
+    fn cylinders_and_cones_are_capped() {
+        let mesh = super::create_shape_mesh(&crate::world::Shape::Cylinder {
+            radius: 1.0,
+            half_height: 2.0,
+            sectors: 12,
+        });
+        assert_counts(&mesh, 3 * 2 * 13, 3 * 12 * 2);
+        assert_outward(&mesh, |center| *center);
+
+        let mesh = super::create_shape_mesh(&crate::world::Shape::Cone {
+            radius: 1.0,
+            half_height: 1.0,
+            sectors: 12,
+        });
+        assert_counts(&mesh, 2 * 2 * 13, 2 * 12 * 2);
+        assert_outward(&mesh, |center| *center);
+    }
+
+    #[test]
+    fn planes_face_up() {
+        let mesh = super::create_shape_mesh(&crate::world::Shape::Plane {
+            half_extents: glm::vec2(2.0, 1.0),
+            subdivisions: 3,
+        });
+        assert_counts(&mesh, 5 * 5, 4 * 4 * 2);
+        // Every grid line is drawn once, four cells and five lines along each axis
+        assert_eq!(mesh.line_indices.len(), 2 * 5 * 4 * 2);
+        assert!(mesh
+            .vertices
+            .iter()
+            .all(|vertex| vertex.normal == glm::Vec3::y()
+                && vertex.position.x.abs() <= 2.0
+                && vertex.position.z.abs() <= 1.0));
+        assert_outward(&mesh, |_| glm::Vec3::y());
+    }
+
+    #[test]
+    fn tori_face_away_from_their_ring_and_draw_each_line_once() {
+        let (major_segments, minor_segments) = (12, 6);
+        let mesh = super::create_shape_mesh(&crate::world::Shape::Torus {
+            major_radius: 2.0,
+            minor_radius: 0.5,
+            major_segments,
+            minor_segments,
+        });
+        assert_counts(
+            &mesh,
+            ((minor_segments + 1) * (major_segments + 1)) as usize,
+            (minor_segments * major_segments * 2) as usize,
+        );
+        let ring_point =
+            |position: &glm::Vec3| glm::vec3(position.x, 0.0, position.z).normalize() * 2.0;
+        assert_outward(&mesh, |center| *center - ring_point(center));
+
+        // Both the rings around the tube and the rings around the axis wrap without a seam
+        assert_eq!(
+            mesh.line_indices.len(),
+            (2 * minor_segments * major_segments * 2) as usize
+        );
+        let segments = mesh
+            .line_indices
+            .chunks_exact(2)
+            .map(|line| [line[0], line[1]].map(|index| mesh.vertices[index as usize].position))
+            .collect::<Vec<_>>();
+        segments.iter().enumerate().for_each(|(index, [a, b])| {
+            assert!(!segments[..index].iter().any(|[other_a, other_b]| {
+                let same = |x: &glm::Vec3, y: &glm::Vec3| glm::distance(x, y) < 1e-5;
+                (same(a, other_a) && same(b, other_b)) || (same(a, other_b) && same(b, other_a))
+            }));
+        });
+    }
+
+    #[test]
+    fn arrows_point_up_from_the_origin() {
+        let mesh = super::create_shape_mesh(&crate::world::Shape::Arrow {
+            length: 2.0,
+            shaft_radius: 0.1,
+            head_length: 0.5,
+            head_radius: 0.3,
+            sectors: 8,
+        });
+        assert_counts(&mesh, 4 * 2 * 9, 4 * 8 * 2);
+        let heights = mesh.vertices.iter().map(|vertex| vertex.position.y);
+        assert_eq!(heights.clone().fold(f32::MAX, f32::min), 0.0);
+        assert_eq!(heights.fold(f32::MIN, f32::max), 2.0);
+        // The shaft and head are convex around the axis, apart from the step between them
+        assert_outward(&mesh, |center| {
+            if center.y.abs() < 1e-5 {
+                -glm::Vec3::y()
+            } else if (center.y - 1.5).abs() < 1e-5 {
+                glm::vec3(center.x, -1.0, center.z)
+            } else {
+                glm::vec3(center.x, if center.y > 1.5 { 0.1 } else { 0.0 }, center.z)
+            }
+        });
+    }
+}
@@ -8,6 +8,9 @@ pub struct Uniform {
     pub proj_inv: glm::Mat4,
     pub view: glm::Mat4,
     pub cam_pos: glm::Vec4,
+    /// Depth of the far plane, which the sky is drawn at
+    pub far_depth: f32,
+    pub _padding: [f32; 3],
 }
 
 pub struct SkyRender {
@@ -153,9 +156,9 @@ impl SkyRender {
                 conservative: false,
             },
             depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
+                format: crate::gpu::Gpu::DEPTH_FORMAT,
                 depth_write_enabled: false,
-                depth_compare: wgpu::CompareFunction::LessEqual,
+                depth_compare: gpu.depth_compare_or_equal(),
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
@@ -203,19 +206,17 @@ impl SkyRender {
         render_pass: &mut wgpu::RenderPass<'rp>,
         gpu: &crate::gpu::Gpu,
         context: &crate::app::Context,
+        viewport: &crate::render::ViewportCamera,
     ) {
-        if let Some(scene_index) = context.active_scene_index {
-            let scene = &context.world.scenes[scene_index];
-
-            let (camera_position, projection, view) =
-                crate::world::create_camera_matrices(&context.world, scene, gpu.aspect_ratio());
-
-            // Update uniform with camera data from context
-            self.uniform.proj = projection;
-            self.uniform.proj_inv = nalgebra_glm::inverse(&projection);
-            self.uniform.view = view;
+        if context.active_scene_index.is_some() {
+            // Update uniform with the viewport's camera data
+            let camera_position = viewport.camera_position;
+            self.uniform.proj = viewport.projection;
+            self.uniform.proj_inv = nalgebra_glm::inverse(&viewport.projection);
+            self.uniform.view = viewport.view;
             self.uniform.cam_pos =
                 nalgebra_glm::vec4(camera_position.x, camera_position.y, camera_position.z, 1.0);
+            self.uniform.far_depth = gpu.depth_clear_value();
 
             // Write updated uniform to the GPU
             gpu.queue.write_buffer(
@@ -237,6 +238,7 @@ struct Uniform {
     proj_inv: mat4x4<f32>,
     view: mat4x4<f32>,
     cam_pos: vec4<f32>,
+    far_depth: f32,
 };
 
 @group(0) @binding(0)
@@ -260,7 +262,7 @@ fn vs_sky(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
     let pos = vec4<f32>(
         f32(tmp1) * 4.0 - 1.0,
         f32(tmp2) * 4.0 - 1.0,
-        1.0,
+        u.far_depth,
         1.0
     );
 
@@ -0,0 +1,321 @@
+use nalgebra_glm as glm;
+
+/// Size of the header that starts a binary stl file, before the triangle count
+const HEADER_SIZE: usize = 80;
+
+/// Size of each triangle in a binary stl file: a normal, three positions and an attribute byte count
+const TRIANGLE_SIZE: usize = 50;
+
+/// Imports a binary or ascii stl file, with a mesh for each solid
+pub fn import_stl(
+    path: impl AsRef<std::path::Path>,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    import_stl_with_progress(path, &crate::gltf::ImportOptions::default(), &mut |_| true)
+}
+
+pub fn import_stl_with_progress(
+    path: impl AsRef<std::path::Path>,
+    options: &crate::gltf::ImportOptions,
+    progress: &mut dyn FnMut(crate::gltf::ImportProgress) -> bool,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|source| crate::gltf::ImportError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    import_stl_from_slice_with_progress(&bytes, options, progress)
+}
+
+/// Facet normals are ignored in favor of normals generated with the options' normal mode,
+/// as many exporters leave them zeroed
+pub fn import_stl_from_slice_with_progress(
+    bytes: &[u8],
+    options: &crate::gltf::ImportOptions,
+    progress: &mut dyn FnMut(crate::gltf::ImportProgress) -> bool,
+) -> Result<crate::world::World, crate::gltf::ImportError> {
+    crate::gltf::report_progress(progress, crate::gltf::ImportStage::Parsing, 0, 1)?;
+    let solids = if is_binary(bytes) {
+        vec![parse_binary(bytes)?]
+    } else {
+        parse_ascii(&String::from_utf8_lossy(bytes))?
+    };
+
+    let scale = options.unit_scale.unwrap_or(1.0);
+    let mut world = crate::world::World::default();
+    let number_of_solids = solids.len();
+    let mesh_names = solids
+        .into_iter()
+        .enumerate()
+        .map(|(solid_index, solid)| {
+            crate::gltf::report_progress(
+                progress,
+                crate::gltf::ImportStage::BuildingMeshes,
+                solid_index,
+                number_of_solids,
+            )?;
+            let mut vertices = solid
+                .positions
+                .iter()
+                .map(|position| crate::world::Vertex {
+                    position: position * scale,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            let mut indices = (0..vertices.len() as u32).collect::<Vec<_>>();
+            let mut topology = crate::world::PrimitiveTopology::Triangles;
+            // Facets repeat their corners, so they are welded before smooth normals are averaged
+            crate::optimize::weld_vertices(&mut vertices, &mut indices);
+            crate::geometry::generate_normals(
+                &mut vertices,
+                &mut indices,
+                &mut topology,
+                options.normal_mode,
+            );
+            let primitive = crate::import::add_primitive(
+                &mut world, vertices, indices, topology, None, options,
+            );
+            world.meshes.push(crate::world::Mesh {
+                primitives: vec![primitive],
+                ..Default::default()
+            });
+            Ok(solid.name)
+        })
+        .collect::<Result<Vec<_>, crate::gltf::ImportError>>()?;
+
+    crate::gltf::report_progress(progress, crate::gltf::ImportStage::BuildingScenes, 0, 1)?;
+    crate::import::add_mesh_scene(&mut world, mesh_names);
+    world.compute_bounds();
+
+    if let Some(lod_options) = options.lods.as_ref() {
+        crate::lod::generate_lods(&mut world, lod_options);
+    }
+
+    Ok(world)
+}
+
+struct Solid {
+    name: String,
+    /// Three corners per facet
+    positions: Vec<glm::Vec3>,
+}
+
+/// Binary files may also start with "solid", so the size implied by the triangle count decides
+fn is_binary(bytes: &[u8]) -> bool {
+    let Some(count) = bytes.get(HEADER_SIZE..HEADER_SIZE + 4) else {
+        return false;
+    };
+    let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize;
+    let starts_with_solid = String::from_utf8_lossy(&bytes[..HEADER_SIZE])
+        .trim_start()
+        .starts_with("solid");
+    bytes.len() == HEADER_SIZE + 4 + count * TRIANGLE_SIZE || !starts_with_solid
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Solid, crate::gltf::ImportError> {
+    let count = bytes
+        .get(HEADER_SIZE..HEADER_SIZE + 4)
+        .map(|count| u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize)
+        .ok_or_else(|| {
+            crate::gltf::ImportError::Malformed("the stl header is truncated".to_string())
+        })?;
+    let triangles = bytes
+        .get(HEADER_SIZE + 4..HEADER_SIZE + 4 + count * TRIANGLE_SIZE)
+        .ok_or_else(|| {
+            crate::gltf::ImportError::Malformed(format!(
+                "the stl file should hold {count} triangles but is {} bytes long",
+                bytes.len()
+            ))
+        })?;
+    let positions = triangles
+        .chunks_exact(TRIANGLE_SIZE)
+        .flat_map(|triangle| {
+            // The facet normal comes first and is skipped
+            triangle[12..48].chunks_exact(12).map(|corner| {
+                let [x, y, z] = [0, 4, 8].map(|offset| {
+                    f32::from_le_bytes([
+                        corner[offset],
+                        corner[offset + 1],
+                        corner[offset + 2],
+                        corner[offset + 3],
+                    ])
+                });
+                glm::vec3(x, y, z)
+            })
+        })
+        .collect();
+    Ok(Solid {
+        name: "Mesh".to_string(),
+        positions,
+    })
+}
+
+fn parse_ascii(source: &str) -> Result<Vec<Solid>, crate::gltf::ImportError> {
+    let mut solids = Vec::new();
+    let mut loop_corners = 0;
+    for (line_index, line) in source.lines().enumerate() {
+        let syntax_error = |reason: &str| crate::gltf::ImportError::Syntax {
+            line: line_index + 1,
+            reason: reason.to_string(),
+        };
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("solid") => solids.push(Solid {
+                name: match tokens.collect::<Vec<_>>().join(" ") {
+                    name if name.is_empty() => "Mesh".to_string(),
+                    name => name,
+                },
+                positions: Vec::new(),
+            }),
+            Some("outer") => loop_corners = 0,
+            Some("vertex") => {
+                let coordinates = tokens
+                    .map(|token| token.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| syntax_error("a vertex coordinate is not a number"))?;
+                let [x, y, z] = coordinates[..] else {
+                    return Err(syntax_error("a vertex needs three coordinates"));
+                };
+                solids
+                    .last_mut()
+                    .ok_or_else(|| syntax_error("a vertex is outside of a solid"))?
+                    .positions
+                    .push(glm::vec3(x, y, z));
+                loop_corners += 1;
+            }
+            Some("endloop") if loop_corners != 3 => {
+                return Err(syntax_error("a facet needs three vertices"));
+            }
+            _ => {}
+        }
+    }
+    if solids.is_empty() {
+        return Err(crate::gltf::ImportError::Malformed(
+            "the stl file has no solids".to_string(),
+        ));
+    }
+    Ok(solids)
+}
+
+#[cfg(test)]
+mod tests {
+    const TWO_SOLIDS: &str = "solid Left
+  facet normal 0 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 1000 0 0
+      vertex 0 1000 0
+    endloop
+  endfacet
+  facet normal 0 0 0
+    outer loop
+      vertex 1000 0 0
+      vertex 1000 1000 0
+      vertex 0 1000 0
+    endloop
+  endfacet
+endsolid Left
+solid
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 0 0 1000
+      vertex 0 1000 0
+    endloop
+  endfacet
+endsolid
+";
+
+    fn binary_stl(triangles: &[[[f32; 3]; 3]]) -> Vec<u8> {
+        // The header deliberately starts with "solid", as some exporters write it
+        let mut bytes = b"solid binary".to_vec();
+        bytes.resize(super::HEADER_SIZE, 0);
+        bytes.extend((triangles.len() as u32).to_le_bytes());
+        triangles.iter().for_each(|triangle| {
+            bytes.extend([0.0f32; 3].iter().flat_map(|value| value.to_le_bytes()));
+            triangle
+                .iter()
+                .flatten()
+                .for_each(|value| bytes.extend(value.to_le_bytes()));
+            bytes.extend([0, 0]);
+        });
+        bytes
+    }
+
+    #[test]
+    fn ascii_solids_become_scaled_meshes() {
+        let options = crate::gltf::ImportOptions {
+            unit_scale: Some(0.001),
+            normal_mode: crate::geometry::NormalMode::Smooth,
+            ..Default::default()
+        };
+        let world = super::import_stl_from_slice_with_progress(
+            TWO_SOLIDS.as_bytes(),
+            &options,
+            &mut |_| true,
+        )
+        .unwrap();
+
+        assert_eq!(world.meshes.len(), 2);
+        let names = world
+            .nodes
+            .iter()
+            .filter(|node| node.mesh_index.is_some())
+            .map(|node| world.metadata[node.metadata_index].name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Left", "Mesh"]);
+
+        // The quad's shared corners are welded
+        let quad = &world.meshes[0].primitives[0];
+        assert_eq!(quad.number_of_vertices, 4);
+        assert_eq!(quad.number_of_indices, 6);
+        let quad_vertices =
+            &world.vertices[quad.vertex_offset..quad.vertex_offset + quad.number_of_vertices];
+        assert!(quad_vertices.iter().all(|vertex| {
+            vertex.position.max() <= 1.0 && vertex.normal == nalgebra_glm::Vec3::z()
+        }));
+    }
+
+    #[test]
+    fn binary_stl_gets_flat_normals() {
+        let bytes = binary_stl(&[
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        ]);
+        let world = super::import_stl_from_slice_with_progress(
+            &bytes,
+            &crate::gltf::ImportOptions::default(),
+            &mut |_| true,
+        )
+        .unwrap();
+
+        assert_eq!(world.meshes.len(), 1);
+        let primitive = &world.meshes[0].primitives[0];
+        assert_eq!(primitive.number_of_vertices, 6);
+        let normals = world.vertices[..6]
+            .iter()
+            .map(|vertex| vertex.normal)
+            .collect::<Vec<_>>();
+        assert!(normals[..3]
+            .iter()
+            .all(|normal| *normal == nalgebra_glm::Vec3::z()));
+        assert!(normals[3..]
+            .iter()
+            .all(|normal| *normal == nalgebra_glm::Vec3::x()));
+    }
+
+    #[test]
+    fn truncated_binary_stl_is_malformed() {
+        let mut bytes = binary_stl(&[[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]]);
+        bytes[super::HEADER_SIZE] = 2;
+        bytes[0] = b'x';
+        let result = super::import_stl_from_slice_with_progress(
+            &bytes,
+            &crate::gltf::ImportOptions::default(),
+            &mut |_| true,
+        );
+        assert!(matches!(
+            result,
+            Err(crate::gltf::ImportError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,714 @@
+/// An image converted to a format the gpu can filter, along with its mip chain
+pub struct TextureUpload {
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    /// The pixels of each mip level, starting with the full size image
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl TextureUpload {
+    /// Eight bit images stay eight bits per channel, using hardware srgb decoding for color.
+    /// Deeper images become half floats, with color decoded to linear up front.
+    /// Formats without an alpha channel are expanded to rgba, as the gpu has no three channel formats.
+    /// Compressed images are uploaded as is when the gpu features allow it and decoded on the cpu otherwise.
+    pub fn new(
+        image: &crate::world::Image,
+        color_space: crate::world::ColorSpace,
+        features: wgpu::Features,
+    ) -> Self {
+        if let Some(compression) = image.compression {
+            return Self::new_compressed(image, compression, color_space, features);
+        }
+
+        let srgb = color_space == crate::world::ColorSpace::Srgb;
+        let eight_bit = image.format.component_size() == 1;
+        let format = match (eight_bit, srgb) {
+            (true, true) => wgpu::TextureFormat::Rgba8UnormSrgb,
+            (true, false) => wgpu::TextureFormat::Rgba8Unorm,
+            (false, _) => wgpu::TextureFormat::Rgba16Float,
+        };
+
+        let encode = |pixels: &[nalgebra_glm::Vec4], linear: bool| -> Vec<u8> {
+            if !eight_bit {
+                let halves = pixels
+                    .iter()
+                    .flat_map(|pixel| <[f32; 4]>::from(*pixel).map(crate::quantize::f32_to_f16))
+                    .collect::<Vec<_>>();
+                return bytemuck::cast_slice(&halves).to_vec();
+            }
+            pixels
+                .iter()
+                .flat_map(|pixel| {
+                    let rgb = if srgb && linear {
+                        pixel.xyz().map(linear_to_srgb)
+                    } else {
+                        pixel.xyz()
+                    };
+                    [rgb.x, rgb.y, rgb.z, pixel.w]
+                })
+                .map(|value| (value.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8)
+                .collect()
+        };
+        let table = (0..=u8::MAX)
+            .map(|value| srgb_to_linear(value as f32 / u8::MAX as f32))
+            .collect::<Vec<_>>();
+        let linearize = |pixels: &mut [nalgebra_glm::Vec4]| {
+            if !srgb {
+                return;
+            }
+            let to_linear = |value: f32| {
+                if eight_bit {
+                    table[(value * u8::MAX as f32).round() as usize]
+                } else {
+                    srgb_to_linear(value)
+                }
+            };
+            pixels.iter_mut().for_each(|pixel| {
+                pixel.x = to_linear(pixel.x);
+                pixel.y = to_linear(pixel.y);
+                pixel.z = to_linear(pixel.z);
+            });
+        };
+
+        // Mip levels that came with the image are used instead of generating them
+        if !image.mips.is_empty() {
+            let levels = std::iter::once(&image.pixels)
+                .chain(image.mips.iter())
+                .map(|pixels| {
+                    let mut pixels = image.format.to_rgba_f32(pixels);
+                    if !eight_bit {
+                        linearize(&mut pixels);
+                    }
+                    encode(&pixels, !eight_bit)
+                })
+                .collect();
+            return Self {
+                format,
+                width: image.width.max(1),
+                height: image.height.max(1),
+                levels,
+            };
+        }
+
+        // Eight bit color stays srgb encoded at full size, the gpu decodes it when sampling
+        let mut pixels = image.to_rgba_f32();
+        let mut levels = Vec::new();
+        if eight_bit {
+            levels.push(encode(&pixels, false));
+        }
+        // Mips are filtered in linear space so they keep the image's brightness
+        linearize(&mut pixels);
+        if !eight_bit {
+            levels.push(encode(&pixels, true));
+        }
+
+        let (mut width, mut height) = (image.width.max(1), image.height.max(1));
+        while width > 1 || height > 1 {
+            (pixels, width, height) = downsample(&pixels, width, height);
+            levels.push(encode(&pixels, true));
+        }
+
+        Self {
+            format,
+            width: image.width.max(1),
+            height: image.height.max(1),
+            levels,
+        }
+    }
+
+    fn new_compressed(
+        image: &crate::world::Image,
+        compression: crate::world::BlockCompression,
+        color_space: crate::world::ColorSpace,
+        features: wgpu::Features,
+    ) -> Self {
+        let (block_width, block_height) = compression.block_dimensions();
+        // The gpu only accepts compressed images made of whole blocks
+        let format = compressed_format(compression, color_space).filter(|format| {
+            features.contains(format.required_features())
+                && image.width % block_width == 0
+                && image.height % block_height == 0
+        });
+        if let Some(format) = format {
+            return Self {
+                format,
+                width: image.width,
+                height: image.height,
+                levels: std::iter::once(&image.pixels)
+                    .chain(image.mips.iter())
+                    .cloned()
+                    .collect(),
+            };
+        }
+        match crate::compression::decompress(image) {
+            Ok(image) => Self::new(&image, color_space, features),
+            Err(error) => {
+                log::error!(
+                    "This gpu cannot sample {compression:?} images and decoding on the cpu failed, so a placeholder is used: {error}"
+                );
+                Self::new(&crate::world::Image::placeholder(), color_space, features)
+            }
+        }
+    }
+
+    /// Creates the texture and uploads every mip level
+    pub fn create_texture(&self, gpu: &crate::gpu::Gpu) -> wgpu::Texture {
+        let size = wgpu::Extent3d {
+            width: self.width,
+            height: self.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: self.levels.len() as u32,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let (block_width, block_height) = self.format.block_dimensions();
+        let block_size = self.format.block_size(None).unwrap_or_default();
+        self.levels
+            .iter()
+            .enumerate()
+            .for_each(|(mip_level, pixels)| {
+                // Compressed levels are copied in whole blocks, even when smaller than a block
+                let size = size
+                    .mip_level_size(mip_level as u32, wgpu::TextureDimension::D2)
+                    .physical_size(self.format);
+                gpu.queue.write_texture(
+                    wgpu::ImageCopyTexture {
+                        aspect: wgpu::TextureAspect::All,
+                        texture: &texture,
+                        mip_level: mip_level as u32,
+                        origin: wgpu::Origin3d::ZERO,
+                    },
+                    pixels,
+                    wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(size.width / block_width * block_size),
+                        rows_per_image: Some(size.height / block_height),
+                    },
+                    size,
+                );
+            });
+        texture
+    }
+}
+
+/// The gpu format of a compression, picking the srgb variant for color where one exists
+fn compressed_format(
+    compression: crate::world::BlockCompression,
+    color_space: crate::world::ColorSpace,
+) -> Option<wgpu::TextureFormat> {
+    let srgb = color_space == crate::world::ColorSpace::Srgb;
+    let pick = |linear, srgb_format| if srgb { srgb_format } else { linear };
+    Some(match compression {
+        crate::world::BlockCompression::Bc1 => pick(
+            wgpu::TextureFormat::Bc1RgbaUnorm,
+            wgpu::TextureFormat::Bc1RgbaUnormSrgb,
+        ),
+        crate::world::BlockCompression::Bc2 => pick(
+            wgpu::TextureFormat::Bc2RgbaUnorm,
+            wgpu::TextureFormat::Bc2RgbaUnormSrgb,
+        ),
+        crate::world::BlockCompression::Bc3 => pick(
+            wgpu::TextureFormat::Bc3RgbaUnorm,
+            wgpu::TextureFormat::Bc3RgbaUnormSrgb,
+        ),
+        crate::world::BlockCompression::Bc4 => wgpu::TextureFormat::Bc4RUnorm,
+        crate::world::BlockCompression::Bc5 => wgpu::TextureFormat::Bc5RgUnorm,
+        crate::world::BlockCompression::Bc6h => wgpu::TextureFormat::Bc6hRgbUfloat,
+        crate::world::BlockCompression::Bc7 => pick(
+            wgpu::TextureFormat::Bc7RgbaUnorm,
+            wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+        ),
+        crate::world::BlockCompression::Etc2Rgb8 => pick(
+            wgpu::TextureFormat::Etc2Rgb8Unorm,
+            wgpu::TextureFormat::Etc2Rgb8UnormSrgb,
+        ),
+        crate::world::BlockCompression::Etc2Rgb8A1 => pick(
+            wgpu::TextureFormat::Etc2Rgb8A1Unorm,
+            wgpu::TextureFormat::Etc2Rgb8A1UnormSrgb,
+        ),
+        crate::world::BlockCompression::Etc2Rgba8 => pick(
+            wgpu::TextureFormat::Etc2Rgba8Unorm,
+            wgpu::TextureFormat::Etc2Rgba8UnormSrgb,
+        ),
+        crate::world::BlockCompression::EacR11 => wgpu::TextureFormat::EacR11Unorm,
+        crate::world::BlockCompression::EacRg11 => wgpu::TextureFormat::EacRg11Unorm,
+        crate::world::BlockCompression::Astc {
+            block_width,
+            block_height,
+        } => wgpu::TextureFormat::Astc {
+            block: match (block_width, block_height) {
+                (4, 4) => wgpu::AstcBlock::B4x4,
+                (5, 4) => wgpu::AstcBlock::B5x4,
+                (5, 5) => wgpu::AstcBlock::B5x5,
+                (6, 5) => wgpu::AstcBlock::B6x5,
+                (6, 6) => wgpu::AstcBlock::B6x6,
+                (8, 5) => wgpu::AstcBlock::B8x5,
+                (8, 6) => wgpu::AstcBlock::B8x6,
+                (8, 8) => wgpu::AstcBlock::B8x8,
+                (10, 5) => wgpu::AstcBlock::B10x5,
+                (10, 6) => wgpu::AstcBlock::B10x6,
+                (10, 8) => wgpu::AstcBlock::B10x8,
+                (10, 10) => wgpu::AstcBlock::B10x10,
+                (12, 10) => wgpu::AstcBlock::B12x10,
+                (12, 12) => wgpu::AstcBlock::B12x12,
+                _ => return None,
+            },
+            channel: if srgb {
+                wgpu::AstcChannel::UnormSrgb
+            } else {
+                wgpu::AstcChannel::Unorm
+            },
+        },
+    })
+}
+
+#[derive(Debug)]
+pub enum Ktx2Error {
+    Io(std::io::Error),
+    Parse(ktx2::ParseError),
+    /// Basis Universal payloads must be transcoded, which is not supported
+    BasisUniversal,
+    UnsupportedSupercompression(ktx2::SupercompressionScheme),
+    UnsupportedFormat(ktx2::Format),
+    /// Array, cube map and volume textures cannot be used as material images
+    NotTwoDimensional,
+    /// A mip level holds fewer bytes than its size requires
+    MissingData {
+        level: usize,
+    },
+}
+
+impl std::fmt::Display for Ktx2Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Failed to read ktx2: {error}"),
+            Self::Parse(error) => write!(f, "Failed to parse ktx2: {error}"),
+            Self::BasisUniversal => write!(f, "Basis Universal textures are not supported"),
+            Self::UnsupportedSupercompression(scheme) => {
+                write!(f, "Unsupported ktx2 supercompression {scheme:?}")
+            }
+            Self::UnsupportedFormat(format) => write!(f, "Unsupported ktx2 format {format:?}"),
+            Self::NotTwoDimensional => write!(f, "Only 2d ktx2 textures are supported"),
+            Self::MissingData { level } => write!(f, "Mip level {level} of the ktx2 is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for Ktx2Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Whether bytes start with the ktx2 file identifier
+pub fn is_ktx2(bytes: &[u8]) -> bool {
+    bytes.starts_with(&[
+        0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+    ])
+}
+
+/// Reads a 2d ktx2 texture along with its mip levels.
+/// The srgb and unorm variants of a format load the same,
+/// as the color space is decided by the material slots that use the image.
+pub fn load_ktx2(bytes: &[u8]) -> Result<crate::world::Image, Ktx2Error> {
+    let reader = ktx2::Reader::new(bytes).map_err(Ktx2Error::Parse)?;
+    let header = reader.header();
+    match header.supercompression_scheme {
+        None => {}
+        Some(ktx2::SupercompressionScheme::BasisLZ) => return Err(Ktx2Error::BasisUniversal),
+        Some(scheme) => return Err(Ktx2Error::UnsupportedSupercompression(scheme)),
+    }
+    // Uastc payloads leave the format undefined
+    let format = header.format.ok_or(Ktx2Error::BasisUniversal)?;
+    if header.pixel_height == 0
+        || header.pixel_depth > 1
+        || header.layer_count > 1
+        || header.face_count > 1
+    {
+        return Err(Ktx2Error::NotTwoDimensional);
+    }
+    let (image_format, compression) =
+        ktx2_format(format).ok_or(Ktx2Error::UnsupportedFormat(format))?;
+
+    let (width, height) = (header.pixel_width, header.pixel_height);
+    let mut levels = reader
+        .levels()
+        .enumerate()
+        .map(|(level, bytes)| {
+            let (level_width, level_height) = ((width >> level).max(1), (height >> level).max(1));
+            let length = match compression {
+                Some(compression) => {
+                    let (block_width, block_height) = compression.block_dimensions();
+                    (level_width.div_ceil(block_width) * level_height.div_ceil(block_height))
+                        as usize
+                        * compression.block_size()
+                }
+                None => {
+                    (level_width * level_height) as usize
+                        * image_format.channels()
+                        * image_format.component_size()
+                }
+            };
+            let mut pixels = bytes
+                .get(..length)
+                .ok_or(Ktx2Error::MissingData { level })?
+                .to_vec();
+            // Ktx2 components are little endian
+            if cfg!(target_endian = "big") && compression.is_none() {
+                pixels
+                    .chunks_exact_mut(image_format.component_size())
+                    .for_each(|component| component.reverse());
+            }
+            Ok(pixels)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let pixels = levels.remove(0);
+    Ok(crate::world::Image {
+        pixels,
+        format: image_format,
+        width,
+        height,
+        mips: levels,
+        compression,
+    })
+}
+
+pub fn load_ktx2_file(path: impl AsRef<std::path::Path>) -> Result<crate::world::Image, Ktx2Error> {
+    load_ktx2(&std::fs::read(path).map_err(Ktx2Error::Io)?)
+}
+
+/// The image format and compression a ktx2 format loads as
+fn ktx2_format(
+    format: ktx2::Format,
+) -> Option<(
+    crate::world::ImageFormat,
+    Option<crate::world::BlockCompression>,
+)> {
+    use crate::world::{BlockCompression, ImageFormat};
+    let uncompressed = |format| Some((format, None));
+    let compressed = |format, compression| Some((format, Some(compression)));
+    match format {
+        ktx2::Format::R8_UNORM | ktx2::Format::R8_SRGB => uncompressed(ImageFormat::R8),
+        ktx2::Format::R8G8_UNORM | ktx2::Format::R8G8_SRGB => uncompressed(ImageFormat::R8G8),
+        ktx2::Format::R8G8B8_UNORM | ktx2::Format::R8G8B8_SRGB => uncompressed(ImageFormat::R8G8B8),
+        ktx2::Format::B8G8R8_UNORM | ktx2::Format::B8G8R8_SRGB => uncompressed(ImageFormat::B8G8R8),
+        ktx2::Format::R8G8B8A8_UNORM | ktx2::Format::R8G8B8A8_SRGB => {
+            uncompressed(ImageFormat::R8G8B8A8)
+        }
+        ktx2::Format::B8G8R8A8_UNORM | ktx2::Format::B8G8R8A8_SRGB => {
+            uncompressed(ImageFormat::B8G8R8A8)
+        }
+        ktx2::Format::R16_UNORM => uncompressed(ImageFormat::R16),
+        ktx2::Format::R16G16_UNORM => uncompressed(ImageFormat::R16G16),
+        ktx2::Format::R16G16B16_UNORM => uncompressed(ImageFormat::R16G16B16),
+        ktx2::Format::R16G16B16A16_UNORM => uncompressed(ImageFormat::R16G16B16A16),
+        ktx2::Format::R16_SFLOAT => uncompressed(ImageFormat::R16F),
+        ktx2::Format::R16G16_SFLOAT => uncompressed(ImageFormat::R16G16F),
+        ktx2::Format::R16G16B16_SFLOAT => uncompressed(ImageFormat::R16G16B16F),
+        ktx2::Format::R16G16B16A16_SFLOAT => uncompressed(ImageFormat::R16G16B16A16F),
+        ktx2::Format::R32_SFLOAT => uncompressed(ImageFormat::R32F),
+        ktx2::Format::R32G32_SFLOAT => uncompressed(ImageFormat::R32G32F),
+        ktx2::Format::R32G32B32_SFLOAT => uncompressed(ImageFormat::R32G32B32F),
+        ktx2::Format::R32G32B32A32_SFLOAT => uncompressed(ImageFormat::R32G32B32A32F),
+        ktx2::Format::BC1_RGB_UNORM_BLOCK
+        | ktx2::Format::BC1_RGB_SRGB_BLOCK
+        | ktx2::Format::BC1_RGBA_UNORM_BLOCK
+        | ktx2::Format::BC1_RGBA_SRGB_BLOCK => {
+            compressed(ImageFormat::R8G8B8A8, BlockCompression::Bc1)
+        }
+        ktx2::Format::BC2_UNORM_BLOCK | ktx2::Format::BC2_SRGB_BLOCK => {
+            compressed(ImageFormat::R8G8B8A8, BlockCompression::Bc2)
+        }
+        ktx2::Format::BC3_UNORM_BLOCK | ktx2::Format::BC3_SRGB_BLOCK => {
+            compressed(ImageFormat::R8G8B8A8, BlockCompression::Bc3)
+        }
+        ktx2::Format::BC4_UNORM_BLOCK => compressed(ImageFormat::R8G8B8A8, BlockCompression::Bc4),
+        ktx2::Format::BC5_UNORM_BLOCK => compressed(ImageFormat::R8G8B8A8, BlockCompression::Bc5),
+        ktx2::Format::BC6H_UFLOAT_BLOCK => {
+            compressed(ImageFormat::R16G16B16A16F, BlockCompression::Bc6h)
+        }
+        ktx2::Format::BC7_UNORM_BLOCK | ktx2::Format::BC7_SRGB_BLOCK => {
+            compressed(ImageFormat::R8G8B8A8, BlockCompression::Bc7)
+        }
+        ktx2::Format::ETC2_R8G8B8_UNORM_BLOCK | ktx2::Format::ETC2_R8G8B8_SRGB_BLOCK => {
+            compressed(ImageFormat::R8G8B8A8, BlockCompression::Etc2Rgb8)
+        }
+        ktx2::Format::ETC2_R8G8B8A1_UNORM_BLOCK | ktx2::Format::ETC2_R8G8B8A1_SRGB_BLOCK => {
+            compressed(ImageFormat::R8G8B8A8, BlockCompression::Etc2Rgb8A1)
+        }
+        ktx2::Format::ETC2_R8G8B8A8_UNORM_BLOCK | ktx2::Format::ETC2_R8G8B8A8_SRGB_BLOCK => {
+            compressed(ImageFormat::R8G8B8A8, BlockCompression::Etc2Rgba8)
+        }
+        ktx2::Format::EAC_R11_UNORM_BLOCK => {
+            compressed(ImageFormat::R8G8B8A8, BlockCompression::EacR11)
+        }
+        ktx2::Format::EAC_R11G11_UNORM_BLOCK => {
+            compressed(ImageFormat::R8G8B8A8, BlockCompression::EacRg11)
+        }
+        _ => {
+            // The astc formats come in unorm and srgb pairs, ordered by block size
+            const ASTC_BLOCKS: [(u32, u32); 14] = [
+                (4, 4),
+                (5, 4),
+                (5, 5),
+                (6, 5),
+                (6, 6),
+                (8, 5),
+                (8, 6),
+                (8, 8),
+                (10, 5),
+                (10, 6),
+                (10, 8),
+                (10, 10),
+                (12, 10),
+                (12, 12),
+            ];
+            let offset = format
+                .0
+                .get()
+                .checked_sub(ktx2::Format::ASTC_4x4_UNORM_BLOCK.0.get())?;
+            let (block_width, block_height) = *ASTC_BLOCKS.get(offset as usize / 2)?;
+            compressed(
+                ImageFormat::R8G8B8A8,
+                BlockCompression::Astc {
+                    block_width,
+                    block_height,
+                },
+            )
+        }
+    }
+}
+
+/// Halves an image with a box filter, clamping at the edges of odd sized images
+pub fn downsample(
+    pixels: &[nalgebra_glm::Vec4],
+    width: u32,
+    height: u32,
+) -> (Vec<nalgebra_glm::Vec4>, u32, u32) {
+    let (next_width, next_height) = ((width / 2).max(1), (height / 2).max(1));
+    let pixel = |x: u32, y: u32| pixels[(y.min(height - 1) * width + x.min(width - 1)) as usize];
+    let downsampled = (0..next_height)
+        .flat_map(|y| (0..next_width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            (pixel(x * 2, y * 2)
+                + pixel(x * 2 + 1, y * 2)
+                + pixel(x * 2, y * 2 + 1)
+                + pixel(x * 2 + 1, y * 2 + 1))
+                * 0.25
+        })
+        .collect();
+    (downsampled, next_width, next_height)
+}
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn image(format: crate::world::ImageFormat, width: u32, height: u32) -> crate::world::Image {
+        crate::world::Image {
+            pixels: vec![
+                0;
+                (width * height) as usize * format.channels() * format.component_size()
+            ],
+            format,
+            width,
+            height,
+            mips: Vec::new(),
+            compression: None,
+        }
+    }
+
+    #[test]
+    fn mip_chains_halve_non_power_of_two_images_down_to_one_pixel() {
+        [
+            (crate::world::ImageFormat::R8G8B8, 5, 3, 4),
+            (crate::world::ImageFormat::R16G16B16A16F, 7, 1, 8),
+            (crate::world::ImageFormat::R8, 1, 6, 4),
+            (crate::world::ImageFormat::R8G8B8A8, 100, 37, 4),
+        ]
+        .into_iter()
+        .for_each(|(format, width, height, texel_size)| {
+            let upload = super::TextureUpload::new(
+                &image(format, width, height),
+                crate::world::ColorSpace::Srgb,
+                wgpu::Features::empty(),
+            );
+            let size = wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            };
+            assert_eq!(
+                upload.levels.len() as u32,
+                size.max_mips(wgpu::TextureDimension::D2),
+                "{width}x{height}"
+            );
+            upload
+                .levels
+                .iter()
+                .enumerate()
+                .for_each(|(mip_level, level)| {
+                    let size = size.mip_level_size(mip_level as u32, wgpu::TextureDimension::D2);
+                    assert_eq!(
+                        level.len(),
+                        (size.width * size.height * texel_size) as usize,
+                        "{width}x{height} level {mip_level}"
+                    );
+                });
+        });
+    }
+
+    #[test]
+    fn srgb_mips_are_filtered_in_linear_space() {
+        let mut image = image(crate::world::ImageFormat::R8, 2, 1);
+        image.pixels = vec![0, u8::MAX];
+        let upload = |color_space| {
+            super::TextureUpload::new(&image, color_space, wgpu::Features::empty()).levels
+        };
+
+        let levels = upload(crate::world::ColorSpace::Srgb);
+        assert_eq!(levels[0], [0, 0, 0, 255, 255, 255, 255, 255]);
+        let half = (super::linear_to_srgb(0.5) * u8::MAX as f32).round() as u8;
+        assert_eq!(levels[1], [half, half, half, 255]);
+
+        let levels = upload(crate::world::ColorSpace::Linear);
+        assert_eq!(levels[1], [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn texture_slots_pick_srgb_or_linear_formats() {
+        let mut world = crate::world::World::default();
+        world.images = vec![
+            image(crate::world::ImageFormat::R8G8B8A8, 1, 1),
+            image(crate::world::ImageFormat::R8G8B8A8, 1, 1),
+            image(crate::world::ImageFormat::R8G8B8A8, 1, 1),
+            image(crate::world::ImageFormat::R8G8B8A8, 1, 1),
+            image(crate::world::ImageFormat::R16G16B16A16, 1, 1),
+        ];
+        world.textures = (0..world.images.len())
+            .map(|image_index| crate::world::Texture {
+                image_index,
+                sampler_index: None,
+            })
+            .collect();
+        world.materials = vec![
+            crate::world::Material {
+                base_color_texture_index: Some(0),
+                normal_texture_index: Some(1),
+                metallic_roughness_texture_index: Some(2),
+                emissive_texture_index: Some(4),
+                ..Default::default()
+            },
+            // An image shared by a color and a data slot stays srgb
+            crate::world::Material {
+                emissive_texture_index: Some(2),
+                occlusion_texture_index: Some(1),
+                ..Default::default()
+            },
+        ];
+
+        let color_spaces = world.image_color_spaces();
+        assert_eq!(
+            color_spaces,
+            [
+                crate::world::ColorSpace::Srgb,
+                crate::world::ColorSpace::Linear,
+                crate::world::ColorSpace::Srgb,
+                crate::world::ColorSpace::Srgb,
+                crate::world::ColorSpace::Srgb,
+            ]
+        );
+        let formats = world
+            .images
+            .iter()
+            .zip(color_spaces)
+            .map(|(image, color_space)| {
+                super::TextureUpload::new(image, color_space, wgpu::Features::empty()).format
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            formats,
+            [
+                wgpu::TextureFormat::Rgba8UnormSrgb,
+                wgpu::TextureFormat::Rgba8Unorm,
+                wgpu::TextureFormat::Rgba8UnormSrgb,
+                wgpu::TextureFormat::Rgba8UnormSrgb,
+                wgpu::TextureFormat::Rgba16Float,
+            ]
+        );
+    }
+
+    #[test]
+    fn compressed_formats_follow_the_color_space() {
+        let mut image = image(crate::world::ImageFormat::R8G8B8A8, 4, 4);
+        let format = |image: &crate::world::Image, color_space| {
+            super::TextureUpload::new(image, color_space, wgpu::Features::TEXTURE_COMPRESSION_BC)
+                .format
+        };
+        [
+            (
+                crate::world::BlockCompression::Bc1,
+                8,
+                wgpu::TextureFormat::Bc1RgbaUnormSrgb,
+                wgpu::TextureFormat::Bc1RgbaUnorm,
+            ),
+            (
+                crate::world::BlockCompression::Bc3,
+                16,
+                wgpu::TextureFormat::Bc3RgbaUnormSrgb,
+                wgpu::TextureFormat::Bc3RgbaUnorm,
+            ),
+            // Two channel normal maps have no srgb variant
+            (
+                crate::world::BlockCompression::Bc5,
+                16,
+                wgpu::TextureFormat::Bc5RgUnorm,
+                wgpu::TextureFormat::Bc5RgUnorm,
+            ),
+        ]
+        .into_iter()
+        .for_each(|(compression, block_size, srgb, linear)| {
+            image.compression = Some(compression);
+            image.pixels = vec![0; block_size];
+            assert_eq!(format(&image, crate::world::ColorSpace::Srgb), srgb);
+            assert_eq!(format(&image, crate::world::ColorSpace::Linear), linear);
+        });
+
+        // Without gpu support the blocks are decoded to pixels on the cpu
+        image.compression = Some(crate::world::BlockCompression::Bc1);
+        image.pixels = vec![0; 8];
+        let upload = super::TextureUpload::new(
+            &image,
+            crate::world::ColorSpace::Srgb,
+            wgpu::Features::empty(),
+        );
+        assert_eq!(upload.format, wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(upload.levels.len(), 3);
+    }
+}
@@ -1,117 +1,61 @@
+/// One pipeline for each vertex format, indexed by `VertexFormat as usize`
+pub type VertexFormatPipelines = [wgpu::RenderPipeline; crate::world::VertexFormat::ALL.len()];
+
 pub struct WorldRender {
+    /// Vertices of each primitive packed in the primitive's vertex format
     pub vertex_buffer: wgpu::Buffer,
+    /// Byte offsets into the vertex buffer, keyed by the vertex offset of the primitives using them
+    pub vertex_buffer_offsets: std::collections::HashMap<usize, wgpu::BufferAddress>,
     pub index_buffer: wgpu::Buffer,
     pub uniform_buffer: wgpu::Buffer,
     pub uniform_bind_group: wgpu::BindGroup,
     pub dynamic_uniform_buffer: wgpu::Buffer,
     pub dynamic_uniform_bind_group: wgpu::BindGroup,
+    pub morph_target_buffer: wgpu::Buffer,
     pub texture_array_bind_group: wgpu::BindGroup,
     pub samplers: Vec<wgpu::Sampler>,
     pub textures: Vec<wgpu::Texture>,
-    pub triangle_filled_pipeline: wgpu::RenderPipeline,
-    pub triangle_blended_pipeline: wgpu::RenderPipeline,
-    pub line_pipeline: wgpu::RenderPipeline,
-    pub line_strip_pipeline: wgpu::RenderPipeline,
-    pub triangle_strip_pipeline: wgpu::RenderPipeline,
+    pub triangle_filled_pipeline: VertexFormatPipelines,
+    pub triangle_blended_pipeline: VertexFormatPipelines,
+    pub line_pipeline: VertexFormatPipelines,
+    pub line_strip_pipeline: VertexFormatPipelines,
+    pub triangle_strip_pipeline: VertexFormatPipelines,
+    /// Draws each point as an instanced quad, reading full precision vertices
+    pub point_pipeline: wgpu::RenderPipeline,
+    pub id_pipeline: VertexFormatPipelines,
+    pub id_strip_pipeline: VertexFormatPipelines,
+    pub picking_targets: Option<PickingTargets>,
+    pub pending_pick: Option<PendingPick>,
 }
 
 impl WorldRender {
     pub fn new(gpu: &crate::gpu::Gpu, world: &crate::world::World) -> Self {
-        let (vertex_buffer, index_buffer) =
-            create_geometry_buffers(&gpu.device, &world.vertices, &world.indices);
+        let (vertex_buffer, vertex_buffer_offsets, index_buffer) =
+            create_geometry_buffers(&gpu.device, world);
         let (uniform_buffer, uniform_bind_group_layout, uniform_bind_group) = create_uniform(gpu);
-        let (dynamic_uniform_buffer, dynamic_uniform_bind_group_layout, dynamic_uniform_bind_group) =
-            create_dynamic_uniform(gpu, world.transforms.len() as _);
+        let (
+            dynamic_uniform_buffer,
+            morph_target_buffer,
+            dynamic_uniform_bind_group_layout,
+            dynamic_uniform_bind_group,
+        ) = create_dynamic_uniform(gpu, world.transforms.len() as _, &world.morph_targets);
 
-        let mut samplers = world
+        // Textures without a sampler use the default sampler after the world's samplers
+        let samplers = world
             .samplers
             .iter()
-            .map(|sampler| {
-                gpu.device.create_sampler(&wgpu::SamplerDescriptor {
-                    address_mode_u: match sampler.wrap_s {
-                        crate::world::WrappingMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
-                        crate::world::WrappingMode::MirroredRepeat => {
-                            wgpu::AddressMode::MirrorRepeat
-                        }
-                        crate::world::WrappingMode::Repeat => wgpu::AddressMode::Repeat,
-                    },
-                    address_mode_v: match sampler.wrap_t {
-                        crate::world::WrappingMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
-                        crate::world::WrappingMode::MirroredRepeat => {
-                            wgpu::AddressMode::MirrorRepeat
-                        }
-                        crate::world::WrappingMode::Repeat => wgpu::AddressMode::Repeat,
-                    },
-                    address_mode_w: wgpu::AddressMode::ClampToEdge,
-                    mag_filter: match sampler.mag_filter {
-                        crate::world::MagFilter::Nearest => wgpu::FilterMode::Nearest,
-                        crate::world::MagFilter::Linear => wgpu::FilterMode::Linear,
-                    },
-                    min_filter: match sampler.min_filter {
-                        crate::world::MinFilter::Nearest
-                        | crate::world::MinFilter::NearestMipmapLinear
-                        | crate::world::MinFilter::NearestMipmapNearest => {
-                            wgpu::FilterMode::Nearest
-                        }
-                        crate::world::MinFilter::Linear
-                        | crate::world::MinFilter::LinearMipmapLinear
-                        | crate::world::MinFilter::LinearMipmapNearest => wgpu::FilterMode::Linear,
-                    },
-                    mipmap_filter: match sampler.min_filter {
-                        crate::world::MinFilter::Nearest
-                        | crate::world::MinFilter::NearestMipmapLinear
-                        | crate::world::MinFilter::NearestMipmapNearest => {
-                            wgpu::FilterMode::Nearest
-                        }
-                        crate::world::MinFilter::Linear
-                        | crate::world::MinFilter::LinearMipmapLinear
-                        | crate::world::MinFilter::LinearMipmapNearest => wgpu::FilterMode::Linear,
-                    },
-                    compare: Some(wgpu::CompareFunction::LessEqual),
-                    lod_min_clamp: 0.0,
-                    lod_max_clamp: 100.0,
-                    ..Default::default()
-                })
-            })
+            .chain(std::iter::once(&crate::world::Sampler::default()))
+            .map(|sampler| create_sampler(&gpu.device, sampler))
             .collect::<Vec<_>>();
 
+        // Images are uploaded once and paired with samplers per material texture slot
         let mut textures = world
-            .textures
+            .images
             .iter()
-            .map(|texture| {
-                let image = &world.images[texture.image_index];
-                let size = wgpu::Extent3d {
-                    width: image.width,
-                    height: image.height,
-                    depth_or_array_layers: 1,
-                };
-                let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
-                    label: None,
-                    size,
-                    mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: wgpu::TextureDimension::D2,
-                    // TODO: map these formats
-                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                    usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                    view_formats: &[],
-                });
-                gpu.queue.write_texture(
-                    wgpu::ImageCopyTexture {
-                        aspect: wgpu::TextureAspect::All,
-                        texture: &texture,
-                        mip_level: 0,
-                        origin: wgpu::Origin3d::ZERO,
-                    },
-                    &image.pixels,
-                    wgpu::ImageDataLayout {
-                        offset: 0,
-                        bytes_per_row: Some(image.width * 4),
-                        rows_per_image: Some(image.height),
-                    },
-                    size,
-                );
-                texture
+            .zip(world.image_color_spaces())
+            .map(|(image, color_space)| {
+                crate::texture::TextureUpload::new(image, color_space, gpu.device.features())
+                    .create_texture(gpu)
             })
             .collect::<Vec<_>>();
 
@@ -121,45 +65,16 @@ impl WorldRender {
                 height: 1,
                 pixels: vec![0x00, 0xFF, 0xFF, 0x00],
                 format: crate::world::ImageFormat::R8G8B8A8,
+                mips: Vec::new(),
+                compression: None,
             };
-            let size = wgpu::Extent3d {
-                width: image.width,
-                height: image.height,
-                depth_or_array_layers: 1,
-            };
-            let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
-                label: None,
-                size,
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                // TODO: map these formats
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                view_formats: &[],
-            });
-            gpu.queue.write_texture(
-                wgpu::ImageCopyTexture {
-                    aspect: wgpu::TextureAspect::All,
-                    texture: &texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                },
-                &image.pixels,
-                wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some(image.width * 4),
-                    rows_per_image: Some(image.height),
-                },
-                size,
-            );
-            textures.push(texture);
-        }
-
-        if samplers.is_empty() {
-            samplers.push(
-                gpu.device
-                    .create_sampler(&wgpu::SamplerDescriptor::default()),
+            textures.push(
+                crate::texture::TextureUpload::new(
+                    &image,
+                    crate::world::ColorSpace::Srgb,
+                    gpu.device.features(),
+                )
+                .create_texture(gpu),
             );
         }
 
@@ -216,73 +131,123 @@ impl WorldRender {
             (texture_array_bind_group, texture_array_bind_group_layout)
         };
 
-        let line_pipeline = create_pipeline(
-            gpu,
-            &[
-                &uniform_bind_group_layout,
-                &dynamic_uniform_bind_group_layout,
-                &texture_array_bind_group_layout,
-            ],
-            false,
-            wgpu::PrimitiveTopology::LineList,
-            wgpu::PolygonMode::Fill,
-        );
+        let line_pipeline = crate::world::VertexFormat::ALL.map(|vertex_format| {
+            create_pipeline(
+                gpu,
+                &[
+                    &uniform_bind_group_layout,
+                    &dynamic_uniform_bind_group_layout,
+                    &texture_array_bind_group_layout,
+                ],
+                false,
+                wgpu::PrimitiveTopology::LineList,
+                wgpu::PolygonMode::Fill,
+                vertex_format,
+            )
+        });
 
-        let line_strip_pipeline = create_pipeline(
-            gpu,
-            &[
-                &uniform_bind_group_layout,
-                &dynamic_uniform_bind_group_layout,
-                &texture_array_bind_group_layout,
-            ],
-            false,
-            wgpu::PrimitiveTopology::LineStrip,
-            wgpu::PolygonMode::Fill,
-        );
+        let line_strip_pipeline = crate::world::VertexFormat::ALL.map(|vertex_format| {
+            create_pipeline(
+                gpu,
+                &[
+                    &uniform_bind_group_layout,
+                    &dynamic_uniform_bind_group_layout,
+                    &texture_array_bind_group_layout,
+                ],
+                false,
+                wgpu::PrimitiveTopology::LineStrip,
+                wgpu::PolygonMode::Fill,
+                vertex_format,
+            )
+        });
 
-        let triangle_filled_pipeline = create_pipeline(
-            gpu,
-            &[
-                &uniform_bind_group_layout,
-                &dynamic_uniform_bind_group_layout,
-                &texture_array_bind_group_layout,
-            ],
-            false,
-            wgpu::PrimitiveTopology::TriangleList,
-            wgpu::PolygonMode::Fill,
-        );
+        let triangle_filled_pipeline = crate::world::VertexFormat::ALL.map(|vertex_format| {
+            create_pipeline(
+                gpu,
+                &[
+                    &uniform_bind_group_layout,
+                    &dynamic_uniform_bind_group_layout,
+                    &texture_array_bind_group_layout,
+                ],
+                false,
+                wgpu::PrimitiveTopology::TriangleList,
+                wgpu::PolygonMode::Fill,
+                vertex_format,
+            )
+        });
 
-        let triangle_blended_pipeline = create_pipeline(
-            gpu,
-            &[
-                &uniform_bind_group_layout,
-                &dynamic_uniform_bind_group_layout,
-                &texture_array_bind_group_layout,
-            ],
-            true,
-            wgpu::PrimitiveTopology::TriangleList,
-            wgpu::PolygonMode::Fill,
-        );
+        let triangle_blended_pipeline = crate::world::VertexFormat::ALL.map(|vertex_format| {
+            create_pipeline(
+                gpu,
+                &[
+                    &uniform_bind_group_layout,
+                    &dynamic_uniform_bind_group_layout,
+                    &texture_array_bind_group_layout,
+                ],
+                true,
+                wgpu::PrimitiveTopology::TriangleList,
+                wgpu::PolygonMode::Fill,
+                vertex_format,
+            )
+        });
+
+        let triangle_strip_pipeline = crate::world::VertexFormat::ALL.map(|vertex_format| {
+            create_pipeline(
+                gpu,
+                &[
+                    &uniform_bind_group_layout,
+                    &dynamic_uniform_bind_group_layout,
+                    &texture_array_bind_group_layout,
+                ],
+                true,
+                wgpu::PrimitiveTopology::TriangleStrip,
+                wgpu::PolygonMode::Fill,
+                vertex_format,
+            )
+        });
 
-        let triangle_strip_pipeline = create_pipeline(
+        let point_pipeline = create_point_pipeline(
             gpu,
             &[
                 &uniform_bind_group_layout,
                 &dynamic_uniform_bind_group_layout,
                 &texture_array_bind_group_layout,
             ],
-            true,
-            wgpu::PrimitiveTopology::TriangleStrip,
-            wgpu::PolygonMode::Fill,
         );
 
+        let id_pipeline = crate::world::VertexFormat::ALL.map(|vertex_format| {
+            create_id_pipeline(
+                gpu,
+                &[
+                    &uniform_bind_group_layout,
+                    &dynamic_uniform_bind_group_layout,
+                ],
+                wgpu::PrimitiveTopology::TriangleList,
+                vertex_format,
+            )
+        });
+
+        let id_strip_pipeline = crate::world::VertexFormat::ALL.map(|vertex_format| {
+            create_id_pipeline(
+                gpu,
+                &[
+                    &uniform_bind_group_layout,
+                    &dynamic_uniform_bind_group_layout,
+                ],
+                wgpu::PrimitiveTopology::TriangleStrip,
+                vertex_format,
+            )
+        });
+
         Self {
             vertex_buffer,
+            vertex_buffer_offsets,
             index_buffer,
             uniform_buffer,
             uniform_bind_group,
             dynamic_uniform_buffer,
             dynamic_uniform_bind_group,
+            morph_target_buffer,
             texture_array_bind_group,
             triangle_filled_pipeline,
             triangle_blended_pipeline,
@@ -291,55 +256,375 @@ impl WorldRender {
             line_pipeline,
             line_strip_pipeline,
             triangle_strip_pipeline,
+            point_pipeline,
+            id_pipeline,
+            id_strip_pipeline,
+            picking_targets: None,
+            pending_pick: None,
         }
     }
 
-    pub fn render<'rp>(
-        &'rp mut self,
+    /// Selects the level of detail to draw a mesh at from its projected size in the viewport
+    fn select_lod<'a>(
+        &self,
+        mesh: &'a crate::world::Mesh,
+        model: &nalgebra_glm::Mat4,
+        viewport: &crate::render::ViewportCamera,
+    ) -> (usize, &'a [crate::world::Primitive]) {
+        if mesh.lods.is_empty() {
+            return (0, &mesh.primitives);
+        }
+        let sphere = mesh.bounding_sphere.transform(model);
+        let screen_size = crate::lod::projected_screen_size(
+            &viewport.projection,
+            &viewport.view,
+            &sphere.center,
+            sphere.radius,
+        );
+        mesh.lod_primitives(screen_size)
+    }
+
+    /// Binds the primitive's vertices, which start at vertex zero, and draws them
+    fn draw_primitive<'rp>(
+        &'rp self,
         render_pass: &mut wgpu::RenderPass<'rp>,
+        primitive: &crate::world::Primitive,
+    ) {
+        let Some(vertex_buffer_offset) = self
+            .vertex_buffer_offsets
+            .get(&primitive.vertex_offset)
+            .filter(|_| primitive.number_of_vertices > 0)
+        else {
+            return;
+        };
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(vertex_buffer_offset..));
+
+        // TODO: support multiple instances per primitive
+        if primitive.topology == crate::world::PrimitiveTopology::Points {
+            // Each point is an instance drawing the four corners of a quad
+            render_pass.draw(0..4, 0..primitive.number_of_vertices as u32);
+        } else if primitive.number_of_indices > 0 {
+            let index_offset = primitive.index_offset as u32;
+            let number_of_indices = index_offset + primitive.number_of_indices as u32;
+            render_pass.draw_indexed(index_offset..number_of_indices, 0, 0..1);
+        } else {
+            render_pass.draw(0..primitive.number_of_vertices as u32, 0..1);
+        }
+    }
+
+    pub fn is_pick_pending(&self) -> bool {
+        self.pending_pick.is_some()
+    }
+
+    /// Renders node and primitive ids into picking targets the size of the render target
+    /// and copies the texels under `position` into the readback buffer.
+    /// Call `map_pick_readback` after the encoder has been submitted.
+    pub fn render_ids(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
         gpu: &crate::gpu::Gpu,
         context: &crate::app::Context,
+        viewport: &crate::render::ViewportCamera,
+        (width, height): (u32, u32),
+        position: nalgebra_glm::Vec2,
     ) {
-        if let Some(scene_index) = context.active_scene_index {
-            let scene = &context.world.scenes[scene_index];
+        let Some(scene_index) = context.active_scene_index else {
+            return;
+        };
+        let scene = &context.world.scenes[scene_index];
+
+        let (width, height) = (width.max(1), height.max(1));
+        if self.picking_targets.as_ref().map_or(true, |targets| {
+            targets.width != width || targets.height != height
+        }) {
+            self.picking_targets = Some(PickingTargets::new(gpu, width, height));
+        }
+
+        let mesh_ubos = self.write_uniforms(gpu, context, viewport);
+        let frustum =
+            crate::world::Frustum::from_view_projection(&(viewport.projection * viewport.view));
+
+        let targets = self.picking_targets.as_ref().unwrap();
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Picking Render Pass"),
+                color_attachments: &[
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &targets.node_id_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: true,
+                        },
+                    }),
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &targets.primitive_id_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: true,
+                        },
+                    }),
+                ],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &targets.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(gpu.depth_clear_value()),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
 
-            let (camera_position, projection, view) =
-                crate::world::create_camera_matrices(&context.world, scene, gpu.aspect_ratio());
-
-            gpu.queue.write_buffer(
-                &self.uniform_buffer,
-                0,
-                bytemuck::cast_slice(&[Uniform {
-                    view,
-                    projection,
-                    camera_position: nalgebra_glm::vec3_to_vec4(&camera_position),
-                }]),
+            render_pass.set_viewport(
+                viewport.x,
+                viewport.y,
+                viewport.width,
+                viewport.height,
+                0.0,
+                1.0,
             );
+            render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
 
-            let mut mesh_ubos = vec![DynamicUniform::default(); context.world.transforms.len()];
             scene
                 .graph
                 .node_indices()
                 .enumerate()
                 .for_each(|(ubo_index, graph_node_index)| {
-                    mesh_ubos[ubo_index] = DynamicUniform {
-                        model: context
-                            .world
-                            .global_transform(&scene.graph, graph_node_index),
+                    let node_index = scene.graph[graph_node_index];
+                    let Some(mesh_index) = context.world.nodes[node_index].mesh_index else {
+                        return;
                     };
+                    let offset = (ubo_index as u64 * gpu.alignment()) as wgpu::DynamicOffset;
+                    render_pass.set_bind_group(1, &self.dynamic_uniform_bind_group, &[offset]);
+                    let mesh = &context.world.meshes[mesh_index];
+                    let model = &mesh_ubos[ubo_index].model;
+                    if !frustum.intersects_sphere(&mesh.bounding_sphere.transform(model)) {
+                        return;
+                    }
+                    // Levels of detail keep their primitives in the same order as the full detail mesh
+                    let (_, primitives) = self.select_lod(mesh, model, viewport);
+                    for (primitive_index, primitive) in primitives.iter().enumerate() {
+                        let vertex_format = primitive.vertex_format as usize;
+                        match primitive.topology {
+                            crate::world::PrimitiveTopology::Triangles => {
+                                render_pass.set_pipeline(&self.id_pipeline[vertex_format]);
+                            }
+                            crate::world::PrimitiveTopology::TriangleStrip => {
+                                render_pass.set_pipeline(&self.id_strip_pipeline[vertex_format]);
+                            }
+                            // Only surfaces can be picked
+                            _ => continue,
+                        }
+
+                        // Zero is reserved for the cleared background
+                        let ids = PickingIds {
+                            node_id: graph_node_index.index() as u32 + 1,
+                            primitive_id: primitive_index as u32,
+                            morph_targets: MorphTargetRange::new(primitive),
+                        };
+                        render_pass.set_push_constants(
+                            wgpu::ShaderStages::VERTEX_FRAGMENT,
+                            0,
+                            bytemuck::cast_slice(&[ids]),
+                        );
+
+                        self.draw_primitive(&mut render_pass, primitive);
+                    }
                 });
-            gpu.queue
-                .write_buffer(&self.dynamic_uniform_buffer, 0, unsafe {
-                    std::slice::from_raw_parts(
-                        mesh_ubos.as_ptr() as *const u8,
-                        mesh_ubos.len() * gpu.alignment() as usize,
-                    )
-                });
+        }
+
+        let x = (position.x.max(0.0) as u32).min(width - 1);
+        let y = (position.y.max(0.0) as u32).min(height - 1);
+        [
+            (&targets.node_id_texture, wgpu::TextureAspect::All),
+            (&targets.primitive_id_texture, wgpu::TextureAspect::All),
+            (&targets.depth_texture, wgpu::TextureAspect::DepthOnly),
+        ]
+        .into_iter()
+        .enumerate()
+        .for_each(|(row, (texture, aspect))| {
+            encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x, y, z: 0 },
+                    aspect,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &targets.readback_buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: row as u64 * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+                        bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                        rows_per_image: Some(1),
+                    },
+                },
+                wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+        });
+
+        let ndc = nalgebra_glm::vec2(
+            (x as f32 + 0.5 - viewport.x) / viewport.width * 2.0 - 1.0,
+            1.0 - (y as f32 + 0.5 - viewport.y) / viewport.height * 2.0,
+        );
+        self.pending_pick = Some(PendingPick {
+            position: nalgebra_glm::vec2(x as f32, y as f32),
+            ndc,
+            inverse_view_projection: nalgebra_glm::inverse(&(viewport.projection * viewport.view)),
+            receiver: None,
+        });
+    }
+
+    /// Starts the asynchronous readback of the texels copied by `render_ids`
+    pub fn map_pick_readback(&mut self) {
+        let (Some(targets), Some(pending_pick)) =
+            (self.picking_targets.as_ref(), self.pending_pick.as_mut())
+        else {
+            return;
+        };
+        if pending_pick.receiver.is_some() {
+            return;
+        }
+        let (sender, receiver) = std::sync::mpsc::channel();
+        targets
+            .readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        pending_pick.receiver = Some(receiver);
+    }
+
+    /// Polls the device and returns the pick result once the readback has completed
+    pub fn receive_pick_result(&mut self, gpu: &crate::gpu::Gpu) -> Option<PickResult> {
+        let receiver = self.pending_pick.as_ref()?.receiver.as_ref()?;
+        gpu.device.poll(wgpu::Maintain::Poll);
+        let mapped = match receiver.try_recv() {
+            Ok(result) => result.is_ok(),
+            Err(std::sync::mpsc::TryRecvError::Empty) => return None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => false,
+        };
+        let pending_pick = self.pending_pick.take()?;
+        let targets = self.picking_targets.as_ref()?;
+        if !mapped {
+            log::warn!("Failed to read back the picking targets");
+            return None;
+        }
+
+        let (node_id, primitive_id, depth) = {
+            let data = targets.readback_buffer.slice(..).get_mapped_range();
+            let read_u32 = |row: usize| {
+                let offset = row * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+                u32::from_ne_bytes([
+                    data[offset],
+                    data[offset + 1],
+                    data[offset + 2],
+                    data[offset + 3],
+                ])
+            };
+            (read_u32(0), read_u32(1), f32::from_bits(read_u32(2)))
+        };
+        targets.readback_buffer.unmap();
+
+        if node_id == 0 {
+            return Some(PickResult {
+                position: pending_pick.position,
+                graph_node_index: None,
+                primitive_index: None,
+                depth,
+                world_position: None,
+            });
+        }
+
+        let clip_position = pending_pick.inverse_view_projection
+            * nalgebra_glm::vec4(pending_pick.ndc.x, pending_pick.ndc.y, depth, 1.0);
+        Some(PickResult {
+            position: pending_pick.position,
+            graph_node_index: Some(petgraph::graph::NodeIndex::new(node_id as usize - 1)),
+            primitive_index: Some(primitive_id as usize),
+            depth,
+            world_position: Some(clip_position.xyz() / clip_position.w),
+        })
+    }
+
+    /// Writes the camera and per-node uniforms, returning the per-node uniforms
+    fn write_uniforms(
+        &self,
+        gpu: &crate::gpu::Gpu,
+        context: &crate::app::Context,
+        viewport: &crate::render::ViewportCamera,
+    ) -> Vec<DynamicUniform> {
+        let Some(scene_index) = context.active_scene_index else {
+            return Vec::new();
+        };
+        let scene = &context.world.scenes[scene_index];
+
+        gpu.queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[Uniform {
+                view: viewport.view,
+                projection: viewport.projection,
+                camera_position: nalgebra_glm::vec3_to_vec4(&viewport.camera_position),
+                viewport_size: nalgebra_glm::vec2(viewport.width, viewport.height),
+                point_size: context.point_size,
+                padding: 0.0,
+            }]),
+        );
+
+        let mut mesh_ubos = vec![DynamicUniform::default(); context.world.transforms.len()];
+        scene
+            .graph
+            .node_indices()
+            .enumerate()
+            .for_each(|(ubo_index, graph_node_index)| {
+                let mut morph_weights = [nalgebra_glm::Vec4::zeros(); MAX_MORPH_TARGETS / 4];
+                context
+                    .world
+                    .morph_weights(scene.graph[graph_node_index])
+                    .iter()
+                    .take(MAX_MORPH_TARGETS)
+                    .enumerate()
+                    .for_each(|(index, weight)| morph_weights[index / 4][index % 4] = *weight);
+                mesh_ubos[ubo_index] = DynamicUniform {
+                    model: context
+                        .world
+                        .global_transform(&scene.graph, graph_node_index),
+                    morph_weights,
+                };
+            });
+        gpu.queue
+            .write_buffer(&self.dynamic_uniform_buffer, 0, unsafe {
+                std::slice::from_raw_parts(
+                    mesh_ubos.as_ptr() as *const u8,
+                    mesh_ubos.len() * gpu.alignment() as usize,
+                )
+            });
+        mesh_ubos
+    }
+
+    pub fn render<'rp>(
+        &'rp mut self,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        gpu: &crate::gpu::Gpu,
+        context: &crate::app::Context,
+        viewport: &crate::render::ViewportCamera,
+    ) {
+        if let Some(scene_index) = context.active_scene_index {
+            let scene = &context.world.scenes[scene_index];
+
+            let mesh_ubos = self.write_uniforms(gpu, context, viewport);
+            let frustum =
+                crate::world::Frustum::from_view_projection(&(viewport.projection * viewport.view));
 
             render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
             render_pass.set_bind_group(2, &self.texture_array_bind_group, &[]);
 
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
             render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
             for alpha_mode in [
                 crate::world::AlphaMode::Opaque,
@@ -364,38 +649,58 @@ impl WorldRender {
                                 &[offset],
                             );
                             let mesh = &context.world.meshes[mesh_index];
+                            let model = &mesh_ubos[ubo_index].model;
+                            if !frustum.intersects_sphere(&mesh.bounding_sphere.transform(model)) {
+                                return;
+                            }
+                            let (lod_index, primitives) = self.select_lod(mesh, model, viewport);
 
-                            for primitive in mesh.primitives.iter() {
+                            for primitive in primitives.iter() {
+                                let vertex_format = primitive.vertex_format as usize;
                                 match primitive.topology {
                                     crate::world::PrimitiveTopology::Lines => {
-                                        render_pass.set_pipeline(&self.line_pipeline);
+                                        render_pass
+                                            .set_pipeline(&self.line_pipeline[vertex_format]);
                                     }
                                     crate::world::PrimitiveTopology::LineStrip => {
-                                        render_pass.set_pipeline(&self.line_strip_pipeline);
+                                        render_pass
+                                            .set_pipeline(&self.line_strip_pipeline[vertex_format]);
                                     }
                                     crate::world::PrimitiveTopology::Triangles => {
                                         match alpha_mode {
                                             crate::world::AlphaMode::Opaque
                                             | crate::world::AlphaMode::Mask => {
-                                                render_pass
-                                                    .set_pipeline(&self.triangle_filled_pipeline);
+                                                render_pass.set_pipeline(
+                                                    &self.triangle_filled_pipeline[vertex_format],
+                                                );
                                             }
                                             crate::world::AlphaMode::Blend => {
-                                                render_pass
-                                                    .set_pipeline(&self.triangle_blended_pipeline);
+                                                render_pass.set_pipeline(
+                                                    &self.triangle_blended_pipeline[vertex_format],
+                                                );
                                             }
                                         }
                                     }
                                     crate::world::PrimitiveTopology::TriangleStrip => {
-                                        render_pass.set_pipeline(&self.triangle_strip_pipeline);
+                                        render_pass.set_pipeline(
+                                            &self.triangle_strip_pipeline[vertex_format],
+                                        );
+                                    }
+                                    crate::world::PrimitiveTopology::Points
+                                        if primitive.vertex_format
+                                            == crate::world::VertexFormat::Full =>
+                                    {
+                                        render_pass.set_pipeline(&self.point_pipeline);
                                     }
 
-                                    // wgpu does not support line loops or triangle fans
-                                    // and Point primitive topology is unsupported on Metal so it is omitted here
+                                    // Line loops and triangle fans are converted on import
                                     _ => continue,
                                 }
 
-                                let mut shader_material = Material::default();
+                                let mut shader_material = Material {
+                                    morph_targets: MorphTargetRange::new(primitive),
+                                    ..Default::default()
+                                };
 
                                 match primitive.material_index {
                                     Some(material_index) => {
@@ -404,10 +709,30 @@ impl WorldRender {
                                             continue;
                                         }
                                         shader_material.base_color = material.base_color_factor;
-                                        shader_material.base_texture_index =
-                                            material.base_color_texture_index as _;
-                                        shader_material.emissive_texture_index =
-                                            material.emissive_texture_index as _;
+                                        (
+                                            shader_material.base_texture_index,
+                                            shader_material.base_sampler_index,
+                                        ) = texture_binding(
+                                            &context.world,
+                                            material.base_color_texture_index,
+                                        );
+                                        (
+                                            shader_material.emissive_texture_index,
+                                            shader_material.emissive_sampler_index,
+                                        ) = texture_binding(
+                                            &context.world,
+                                            material.emissive_texture_index,
+                                        );
+                                        shader_material.base_texture_transform =
+                                            (&material.base_color_texture_transform).into();
+                                        shader_material.emissive_texture_transform =
+                                            (&material.emissive_texture_transform).into();
+                                        shader_material.normal_texture_transform =
+                                            (&material.normal_texture_transform).into();
+                                        shader_material.metallic_roughness_texture_transform =
+                                            (&material.metallic_roughness_texture_transform).into();
+                                        shader_material.occlusion_texture_transform =
+                                            (&material.occlusion_texture_transform).into();
                                         shader_material.emissive_factor = material.emissive_factor;
                                         shader_material.alpha_mode = material.alpha_mode as _;
                                         shader_material.alpha_cutoff =
@@ -423,30 +748,22 @@ impl WorldRender {
                                     }
                                 };
 
+                                if context.lod_debug_colors {
+                                    let colors = crate::lod::LOD_DEBUG_COLORS;
+                                    shader_material.base_color = nalgebra_glm::Vec4::from(
+                                        colors[lod_index.min(colors.len() - 1)],
+                                    );
+                                    shader_material.base_texture_index = -1;
+                                    shader_material.emissive_texture_index = -1;
+                                }
+
                                 render_pass.set_push_constants(
                                     wgpu::ShaderStages::VERTEX_FRAGMENT,
                                     0,
                                     bytemuck::cast_slice(&[shader_material]),
                                 );
 
-                                if primitive.number_of_indices > 0 {
-                                    let index_offset = primitive.index_offset as u32;
-                                    let number_of_indices =
-                                        index_offset + primitive.number_of_indices as u32;
-                                    render_pass.draw_indexed(
-                                        index_offset..number_of_indices,
-                                        primitive.vertex_offset as i32,
-                                        0..1, // TODO: support multiple instances per primitive
-                                    );
-                                } else {
-                                    let vertex_offset = primitive.vertex_offset as u32;
-                                    let number_of_vertices =
-                                        vertex_offset + primitive.number_of_vertices as u32;
-                                    render_pass.draw(
-                                        vertex_offset..number_of_vertices,
-                                        0..1, // TODO: support multiple instances per primitive
-                                    );
-                                }
+                                self.draw_primitive(render_pass, primitive);
                             }
                         }
                     });
@@ -455,10 +772,71 @@ impl WorldRender {
     }
 }
 
+fn create_sampler(device: &wgpu::Device, sampler: &crate::world::Sampler) -> wgpu::Sampler {
+    let address_mode = |wrapping_mode: &crate::world::WrappingMode| match wrapping_mode {
+        crate::world::WrappingMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        crate::world::WrappingMode::MirroredRepeat => wgpu::AddressMode::MirrorRepeat,
+        crate::world::WrappingMode::Repeat => wgpu::AddressMode::Repeat,
+    };
+    let min_filter = match sampler.min_filter {
+        crate::world::MinFilter::Nearest
+        | crate::world::MinFilter::NearestMipmapLinear
+        | crate::world::MinFilter::NearestMipmapNearest => wgpu::FilterMode::Nearest,
+        crate::world::MinFilter::Linear
+        | crate::world::MinFilter::LinearMipmapLinear
+        | crate::world::MinFilter::LinearMipmapNearest => wgpu::FilterMode::Linear,
+    };
+    let mipmap_filter = match sampler.min_filter {
+        crate::world::MinFilter::Nearest
+        | crate::world::MinFilter::NearestMipmapNearest
+        | crate::world::MinFilter::LinearMipmapNearest => wgpu::FilterMode::Nearest,
+        crate::world::MinFilter::Linear
+        | crate::world::MinFilter::NearestMipmapLinear
+        | crate::world::MinFilter::LinearMipmapLinear => wgpu::FilterMode::Linear,
+    };
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: address_mode(&sampler.wrap_s),
+        address_mode_v: address_mode(&sampler.wrap_t),
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: match sampler.mag_filter {
+            crate::world::MagFilter::Nearest => wgpu::FilterMode::Nearest,
+            crate::world::MagFilter::Linear => wgpu::FilterMode::Linear,
+        },
+        min_filter,
+        mipmap_filter,
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 100.0,
+        ..Default::default()
+    })
+}
+
+/// Resolves a texture to the image and sampler array indices the shader samples it with.
+/// Missing textures resolve to an image index of -1.
+fn texture_binding(world: &crate::world::World, texture_index: Option<usize>) -> (i32, i32) {
+    let default_sampler_index = world.samplers.len() as i32;
+    texture_index
+        .and_then(|texture_index| world.textures.get(texture_index))
+        .filter(|texture| texture.image_index < world.images.len())
+        .map_or((-1, default_sampler_index), |texture| {
+            (
+                texture.image_index as i32,
+                texture
+                    .sampler_index
+                    .map_or(default_sampler_index, |sampler_index| sampler_index as i32),
+            )
+        })
+}
+
 fn create_dynamic_uniform(
     gpu: &crate::gpu::Gpu,
     max_meshes: wgpu::BufferAddress,
-) -> (wgpu::Buffer, wgpu::BindGroupLayout, wgpu::BindGroup) {
+    morph_targets: &[crate::world::MorphTargetVertex],
+) -> (
+    wgpu::Buffer,
+    wgpu::Buffer,
+    wgpu::BindGroupLayout,
+    wgpu::BindGroup,
+) {
     let dynamic_uniform_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("dynamic_uniform_buffer"),
         size: max_meshes * gpu.alignment(),
@@ -466,39 +844,75 @@ fn create_dynamic_uniform(
         mapped_at_creation: false,
     });
 
+    // Storage bindings cannot be empty, so worlds without morph targets get a single unused displacement
+    let default_morph_targets = [crate::world::MorphTargetVertex::default()];
+    let morph_targets = if morph_targets.is_empty() {
+        &default_morph_targets[..]
+    } else {
+        morph_targets
+    };
+    let morph_target_buffer = wgpu::util::DeviceExt::create_buffer_init(
+        &gpu.device,
+        &wgpu::util::BufferInitDescriptor {
+            label: Some("morph_target_buffer"),
+            contents: bytemuck::cast_slice(morph_targets),
+            usage: wgpu::BufferUsages::STORAGE,
+        },
+    );
+
     let dynamic_uniform_bind_group_layout =
         gpu.device
             .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: true,
-                        min_binding_size: wgpu::BufferSize::new(
-                            std::mem::size_of::<DynamicUniform>() as _,
-                        ),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: true,
+                            min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<
+                                DynamicUniform,
+                            >()
+                                as _),
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
                 label: Some("dynamic_uniform_buffer_layout"),
             });
 
     let dynamic_uniform_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
         layout: &dynamic_uniform_bind_group_layout,
-        entries: &[wgpu::BindGroupEntry {
-            binding: 0,
-            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                buffer: &dynamic_uniform_buffer,
-                offset: 0,
-                size: wgpu::BufferSize::new(std::mem::size_of::<DynamicUniform>() as _),
-            }),
-        }],
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &dynamic_uniform_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(std::mem::size_of::<DynamicUniform>() as _),
+                }),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: morph_target_buffer.as_entire_binding(),
+            },
+        ],
         label: Some("dynamic_uniform_bind_group"),
     });
 
     (
         dynamic_uniform_buffer,
+        morph_target_buffer,
         dynamic_uniform_bind_group_layout,
         dynamic_uniform_bind_group,
     )
@@ -546,16 +960,39 @@ fn create_uniform(gpu: &crate::gpu::Gpu) -> (wgpu::Buffer, wgpu::BindGroupLayout
     )
 }
 
+/// Packs each primitive's vertices in its vertex format.
+/// Levels of detail share their full detail primitive's vertices, so each vertex range is packed once.
 fn create_geometry_buffers(
     device: &wgpu::Device,
-    vertices: &[crate::world::Vertex],
-    indices: &[u32],
-) -> (wgpu::Buffer, wgpu::Buffer) {
+    world: &crate::world::World,
+) -> (
+    wgpu::Buffer,
+    std::collections::HashMap<usize, wgpu::BufferAddress>,
+    wgpu::Buffer,
+) {
+    let mut vertices = Vec::new();
+    let mut vertex_buffer_offsets = std::collections::HashMap::new();
+    world
+        .meshes
+        .iter()
+        .flat_map(|mesh| mesh.primitives.iter())
+        .for_each(|primitive| {
+            if vertex_buffer_offsets.contains_key(&primitive.vertex_offset) {
+                return;
+            }
+            vertex_buffer_offsets.insert(primitive.vertex_offset, vertices.len() as _);
+            vertices.extend(crate::quantize::encode_vertices(
+                primitive.vertex_format,
+                &world.vertices[primitive.vertex_offset
+                    ..primitive.vertex_offset + primitive.number_of_vertices],
+            ));
+        });
+
     let vertex_buffer = wgpu::util::DeviceExt::create_buffer_init(
         device,
         &wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(vertices),
+            contents: &vertices,
             usage: wgpu::BufferUsages::VERTEX,
         },
     );
@@ -563,11 +1000,11 @@ fn create_geometry_buffers(
         device,
         &wgpu::util::BufferInitDescriptor {
             label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(indices),
+            contents: bytemuck::cast_slice(&world.indices),
             usage: wgpu::BufferUsages::INDEX,
         },
     );
-    (vertex_buffer, index_buffer)
+    (vertex_buffer, vertex_buffer_offsets, index_buffer)
 }
 
 #[repr(C)]
@@ -593,6 +1030,10 @@ pub struct Uniform {
     pub view: nalgebra_glm::Mat4,
     pub projection: nalgebra_glm::Mat4,
     pub camera_position: nalgebra_glm::Vec4,
+    pub viewport_size: nalgebra_glm::Vec2,
+    /// The width of drawn points in pixels
+    pub point_size: f32,
+    pub padding: f32,
 }
 
 pub struct Texture {
@@ -607,6 +1048,7 @@ fn create_pipeline(
     blending_enabled: bool,
     topology: wgpu::PrimitiveTopology,
     polygon_mode: wgpu::PolygonMode,
+    vertex_format: crate::world::VertexFormat,
 ) -> wgpu::RenderPipeline {
     let shader_module = gpu
         .device
@@ -632,10 +1074,8 @@ fn create_pipeline(
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &shader_module,
-                entry_point: "vertex_main",
-                buffers: &[crate::world::Vertex::description(
-                    &crate::world::Vertex::attributes(),
-                )],
+                entry_point: vertex_format.entry_point(),
+                buffers: &[vertex_format.description(&vertex_format.attributes())],
             },
             primitive: wgpu::PrimitiveState {
                 front_face: wgpu::FrontFace::Ccw,
@@ -650,9 +1090,9 @@ fn create_pipeline(
                 ..Default::default()
             },
             depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
+                format: crate::gpu::Gpu::DEPTH_FORMAT,
                 depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
+                depth_compare: gpu.depth_compare(),
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
@@ -678,33 +1118,332 @@ fn create_pipeline(
         })
 }
 
-impl crate::world::Vertex {
-    pub fn attributes() -> Vec<wgpu::VertexAttribute> {
-        wgpu::vertex_attr_array![
-            0 => Float32x3, // position
-            1 => Float32x3, // normal
-            2 => Float32x2, // uv_0
-            3 => Float32x2, // uv_1
-            4 => Float32x4, // joint_0
-            5 => Float32x4, // weight_0
-            6 => Float32x3, // color_0
-        ]
-        .to_vec()
+pub struct PickingTargets {
+    pub width: u32,
+    pub height: u32,
+    pub node_id_texture: wgpu::Texture,
+    pub node_id_view: wgpu::TextureView,
+    pub primitive_id_texture: wgpu::Texture,
+    pub primitive_id_view: wgpu::TextureView,
+    pub depth_texture: wgpu::Texture,
+    pub depth_view: wgpu::TextureView,
+    pub readback_buffer: wgpu::Buffer,
+}
+
+impl PickingTargets {
+    pub const ID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+    pub fn new(gpu: &crate::gpu::Gpu, width: u32, height: u32) -> Self {
+        let create_target = |label, format| {
+            let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            (texture, view)
+        };
+        let (node_id_texture, node_id_view) = create_target("Node Id Texture", Self::ID_FORMAT);
+        let (primitive_id_texture, primitive_id_view) =
+            create_target("Primitive Id Texture", Self::ID_FORMAT);
+        let (depth_texture, depth_view) =
+            create_target("Picking Depth Texture", crate::gpu::Gpu::DEPTH_FORMAT);
+
+        // One aligned row for each of the node id, primitive id and depth texels
+        let readback_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Picking Readback Buffer"),
+            size: 3 * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            width,
+            height,
+            node_id_texture,
+            node_id_view,
+            primitive_id_texture,
+            primitive_id_view,
+            depth_texture,
+            depth_view,
+            readback_buffer,
+        }
     }
+}
+
+pub struct PendingPick {
+    pub position: nalgebra_glm::Vec2,
+    pub ndc: nalgebra_glm::Vec2,
+    pub inverse_view_projection: nalgebra_glm::Mat4,
+    pub receiver: Option<std::sync::mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>>,
+}
 
-    pub fn description(attributes: &[wgpu::VertexAttribute]) -> wgpu::VertexBufferLayout {
+#[derive(Default, Debug, Copy, Clone)]
+pub struct PickResult {
+    /// The pixel that was picked
+    pub position: nalgebra_glm::Vec2,
+    pub graph_node_index: Option<petgraph::graph::NodeIndex>,
+    /// Index into the picked mesh's primitives
+    pub primitive_index: Option<usize>,
+    /// Depth buffer value under the cursor
+    pub depth: f32,
+    /// The surface point under the cursor
+    pub world_position: Option<nalgebra_glm::Vec3>,
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PickingIds {
+    node_id: u32,
+    primitive_id: u32,
+    morph_targets: MorphTargetRange,
+}
+
+fn create_point_pipeline(
+    gpu: &crate::gpu::Gpu,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+) -> wgpu::RenderPipeline {
+    let shader_module = gpu
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(SHADER_SOURCE)),
+        });
+
+    let pipeline_layout = gpu
+        .device
+        .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts,
+            push_constant_ranges: &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                range: 0..(std::mem::size_of::<Material>() as _),
+            }],
+        });
+
+    let vertex_format = crate::world::VertexFormat::Full;
+    let attributes = vertex_format.attributes();
+    gpu.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Point Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vertex_point_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    ..vertex_format.description(&attributes)
+                }],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: crate::gpu::Gpu::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: gpu.depth_compare(),
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fragment_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: gpu.surface_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        })
+}
+
+fn create_id_pipeline(
+    gpu: &crate::gpu::Gpu,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    topology: wgpu::PrimitiveTopology,
+    vertex_format: crate::world::VertexFormat,
+) -> wgpu::RenderPipeline {
+    let shader_module = gpu
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(ID_SHADER_SOURCE)),
+        });
+
+    let pipeline_layout = gpu
+        .device
+        .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts,
+            push_constant_ranges: &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                range: 0..(std::mem::size_of::<PickingIds>() as _),
+            }],
+        });
+
+    let id_target = Some(wgpu::ColorTargetState {
+        format: PickingTargets::ID_FORMAT,
+        blend: None,
+        write_mask: wgpu::ColorWrites::ALL,
+    });
+
+    gpu.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Picking Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vertex_main",
+                buffers: &[vertex_format.description(&vertex_format.attributes())],
+            },
+            primitive: wgpu::PrimitiveState {
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                topology,
+                strip_index_format: (topology == wgpu::PrimitiveTopology::TriangleStrip)
+                    .then_some(wgpu::IndexFormat::Uint32),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: crate::gpu::Gpu::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: gpu.depth_compare(),
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fragment_main",
+                targets: &[id_target.clone(), id_target],
+            }),
+            multiview: None,
+        })
+}
+
+impl crate::world::VertexFormat {
+    /// Attribute locations match the full vertex,
+    /// with each format's attributes listed in the order of its vertex struct
+    pub fn attributes(&self) -> Vec<wgpu::VertexAttribute> {
+        match self {
+            Self::Full => wgpu::vertex_attr_array![
+                0 => Float32x3, // position
+                1 => Float32x3, // normal
+                2 => Float32x2, // uv_0
+                3 => Float32x2, // uv_1
+                4 => Float32x4, // joint_0
+                5 => Float32x4, // weight_0
+                6 => Float32x3, // color_0
+                7 => Float32x4, // tangent
+            ]
+            .to_vec(),
+            Self::Static => wgpu::vertex_attr_array![
+                0 => Float32x3, // position
+                1 => Snorm16x2, // octahedral normal
+                7 => Snorm8x4, // tangent
+                2 => Float16x2, // uv_0
+                3 => Float16x2, // uv_1
+            ]
+            .to_vec(),
+            Self::Colored => wgpu::vertex_attr_array![
+                0 => Float32x3, // position
+                1 => Snorm16x2, // octahedral normal
+                7 => Snorm8x4, // tangent
+                2 => Float16x2, // uv_0
+                3 => Float16x2, // uv_1
+                6 => Unorm8x4, // color_0
+            ]
+            .to_vec(),
+            Self::Skinned => wgpu::vertex_attr_array![
+                0 => Float32x3, // position
+                1 => Snorm16x2, // octahedral normal
+                7 => Snorm8x4, // tangent
+                2 => Float16x2, // uv_0
+                3 => Float16x2, // uv_1
+                6 => Unorm8x4, // color_0
+                4 => Uint16x4, // joint_0
+                5 => Unorm8x4, // weight_0
+            ]
+            .to_vec(),
+        }
+    }
+
+    pub fn stride(&self) -> wgpu::BufferAddress {
+        (match self {
+            Self::Full => std::mem::size_of::<crate::world::Vertex>(),
+            Self::Static => std::mem::size_of::<crate::quantize::StaticVertex>(),
+            Self::Colored => std::mem::size_of::<crate::quantize::ColoredVertex>(),
+            Self::Skinned => std::mem::size_of::<crate::quantize::SkinnedVertex>(),
+        }) as _
+    }
+
+    pub fn description<'a>(
+        &self,
+        attributes: &'a [wgpu::VertexAttribute],
+    ) -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<crate::world::Vertex>() as wgpu::BufferAddress,
+            array_stride: self.stride(),
             step_mode: wgpu::VertexStepMode::Vertex,
             attributes,
         }
     }
+
+    /// Skinned vertices use the colored entry point as skinning is not applied yet
+    fn entry_point(&self) -> &'static str {
+        match self {
+            Self::Full => "vertex_main",
+            Self::Static => "vertex_static_main",
+            Self::Colored | Self::Skinned => "vertex_colored_main",
+        }
+    }
 }
 
+/// The most morph target weights a node can blend, limited by the space left in `DynamicUniform`
+pub const MAX_MORPH_TARGETS: usize = 48;
+
 #[repr(C, align(256))]
 #[derive(Default, Copy, Clone, Debug, bytemuck::Zeroable)]
 pub struct DynamicUniform {
     pub model: nalgebra_glm::Mat4,
+    /// Morph target weights packed four to a vector
+    pub morph_weights: [nalgebra_glm::Vec4; MAX_MORPH_TARGETS / 4],
+}
+
+/// Locates a primitive's morph target displacements for the vertex shader
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct MorphTargetRange {
+    /// The primitive's first displacement
+    base: u32,
+    /// The number of displacements in each target
+    stride: u32,
+    count: u32,
+    padding: u32,
+}
+
+impl MorphTargetRange {
+    fn new(primitive: &crate::world::Primitive) -> Self {
+        Self {
+            base: primitive.morph_target_offset as u32,
+            stride: primitive.number_of_vertices as u32,
+            count: primitive.number_of_morph_targets.min(MAX_MORPH_TARGETS) as u32,
+            padding: 0,
+        }
+    }
 }
 
 #[repr(C)]
@@ -713,10 +1452,44 @@ struct Material {
     pub base_color: nalgebra_glm::Vec4,
     pub emissive_factor: nalgebra_glm::Vec3,
     pub base_texture_index: i32,
+    pub base_sampler_index: i32,
     pub emissive_texture_index: i32,
+    pub emissive_sampler_index: i32,
     pub alpha_mode: i32,
     pub alpha_cutoff: f32,
-    pub sampler_index: i32,
+    pub morph_targets: MorphTargetRange,
+    // Pads to the 16 byte alignment of the shader struct
+    pub padding: [u32; 3],
+    pub base_texture_transform: TextureTransform,
+    pub emissive_texture_transform: TextureTransform,
+    pub normal_texture_transform: TextureTransform,
+    pub metallic_roughness_texture_transform: TextureTransform,
+    pub occlusion_texture_transform: TextureTransform,
+}
+
+// Materials are push constants, which the gpu is requested to provide 256 bytes of
+const _: () = assert!(std::mem::size_of::<Material>() <= 256);
+
+/// The first two rows of a texture transform matrix and the uv set it applies to
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TextureTransform {
+    pub row_0: nalgebra_glm::Vec3,
+    pub uv_set: u32,
+    pub row_1: nalgebra_glm::Vec3,
+    pub padding: u32,
+}
+
+impl From<&crate::world::TextureTransform> for TextureTransform {
+    fn from(transform: &crate::world::TextureTransform) -> Self {
+        let matrix = transform.matrix();
+        Self {
+            row_0: matrix.row(0).transpose(),
+            uv_set: transform.uv_set as u32,
+            row_1: matrix.row(1).transpose(),
+            padding: 0,
+        }
+    }
 }
 
 impl Default for Material {
@@ -725,10 +1498,19 @@ impl Default for Material {
             base_color: nalgebra_glm::vec4(0.5, 0.5, 0.5, 1.0),
             emissive_factor: nalgebra_glm::zero(),
             base_texture_index: -1,
+            base_sampler_index: 0,
             emissive_texture_index: -1,
+            emissive_sampler_index: 0,
             alpha_mode: 0,
             alpha_cutoff: 0.5,
-            sampler_index: 0,
+            morph_targets: MorphTargetRange::default(),
+            padding: [0; 3],
+            base_texture_transform: (&crate::world::TextureTransform::default()).into(),
+            emissive_texture_transform: (&crate::world::TextureTransform::default()).into(),
+            normal_texture_transform: (&crate::world::TextureTransform::default()).into(),
+            metallic_roughness_texture_transform: (&crate::world::TextureTransform::default())
+                .into(),
+            occlusion_texture_transform: (&crate::world::TextureTransform::default()).into(),
         }
     }
 }
@@ -738,6 +1520,9 @@ struct Uniform {
     view: mat4x4<f32>,
     projection: mat4x4<f32>,
     camera_position: vec4<f32>,
+    viewport_size: vec2<f32>,
+    point_size: f32,
+    padding: f32,
 };
 
 @group(0) @binding(0)
@@ -745,11 +1530,36 @@ var<uniform> ubo: Uniform;
 
 struct DynamicUniform {
     model: mat4x4<f32>,
+    morph_weights: array<vec4<f32>, 12>,
 };
 
 @group(1) @binding(0)
 var<uniform> mesh_ubo: DynamicUniform;
 
+struct MorphTargetVertex {
+    position: vec4<f32>,
+    normal: vec4<f32>,
+    tangent: vec4<f32>,
+};
+
+@group(1) @binding(1)
+var<storage, read> morph_targets: array<MorphTargetVertex>;
+
+struct MorphTargetRange {
+    base: u32,
+    stride: u32,
+    count: u32,
+    padding: u32,
+};
+
+fn morph_weight(morph_index: u32) -> f32 {
+    return mesh_ubo.morph_weights[morph_index / 4u][morph_index % 4u];
+}
+
+fn morph_target(range: MorphTargetRange, morph_index: u32, vertex_index: u32) -> MorphTargetVertex {
+    return morph_targets[range.base + morph_index * range.stride + vertex_index];
+}
+
 @group(2) @binding(0)
 var texture_array: binding_array<texture_2d<f32>>;
 @group(2) @binding(1)
@@ -759,13 +1569,30 @@ struct Material {
     base_color: vec4<f32>,
     emissive_factor: vec3<f32>,
     base_texture_index: i32,
+    base_sampler_index: i32,
     emissive_texture_index: i32,
+    emissive_sampler_index: i32,
     alpha_mode: i32,
     alpha_cutoff: f32,
-    sampler_index: i32,
+    morph_targets: MorphTargetRange,
+    padding_0: u32,
+    padding_1: u32,
+    padding_2: u32,
+    base_texture_transform: TextureTransform,
+    emissive_texture_transform: TextureTransform,
+    normal_texture_transform: TextureTransform,
+    metallic_roughness_texture_transform: TextureTransform,
+    occlusion_texture_transform: TextureTransform,
 }
 var<push_constant> material: Material;
 
+struct TextureTransform {
+    row_0: vec3<f32>,
+    uv_set: u32,
+    row_1: vec3<f32>,
+    padding: u32,
+};
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
@@ -774,41 +1601,112 @@ struct VertexInput {
     @location(4) joint_0: vec4<f32>,
     @location(5) weight_0: vec4<f32>,
     @location(6) color_0: vec3<f32>,
+    @location(7) tangent: vec4<f32>,
+};
+
+struct StaticVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec2<f32>,
+    @location(2) uv_0: vec2<f32>,
+    @location(3) uv_1: vec2<f32>,
+};
+
+struct ColoredVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec2<f32>,
+    @location(2) uv_0: vec2<f32>,
+    @location(3) uv_1: vec2<f32>,
+    @location(6) color_0: vec4<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) normal: vec3<f32>,
     @location(1) color: vec3<f32>,
-    @location(2) tex_coord: vec2<f32>,
+    @location(2) tex_coord_0: vec2<f32>,
+    @location(3) tex_coord_1: vec2<f32>,
 };
 
-@vertex
-fn vertex_main(vert: VertexInput) -> VertexOutput {
+fn octahedral_decode(encoded: vec2<f32>) -> vec3<f32> {
+    var normal = vec3(encoded, 1.0 - abs(encoded.x) - abs(encoded.y));
+    let fold = max(-normal.z, 0.0);
+    normal.x += select(fold, -fold, normal.x >= 0.0);
+    normal.y += select(fold, -fold, normal.y >= 0.0);
+    return normalize(normal);
+}
+
+fn transform_vertex(position: vec3<f32>, normal: vec3<f32>, color: vec3<f32>, tex_coord_0: vec2<f32>, tex_coord_1: vec2<f32>, vertex_index: u32) -> VertexOutput {
+    // Tangent displacements are imported but unused until the shader reads tangents
+    var morphed_position = position;
+    var morphed_normal = normal;
+    for (var morph_index = 0u; morph_index < material.morph_targets.count; morph_index++) {
+        let weight = morph_weight(morph_index);
+        if weight != 0.0 {
+            let displacement = morph_target(material.morph_targets, morph_index, vertex_index);
+            morphed_position += weight * displacement.position.xyz;
+            morphed_normal += weight * displacement.normal.xyz;
+        }
+    }
+
     var out: VertexOutput;
     let mvp = ubo.projection * ubo.view * mesh_ubo.model;
-    out.position = mvp * vec4(vert.position, 1.0);
-    out.normal = vec4((mvp * vec4(vert.normal, 0.0)).xyz, 1.0).xyz;
-    out.color = vert.color_0;
-    out.tex_coord = vert.uv_0;
+    out.position = mvp * vec4(morphed_position, 1.0);
+    out.normal = vec4((mvp * vec4(morphed_normal, 0.0)).xyz, 1.0).xyz;
+    out.color = color;
+    out.tex_coord_0 = tex_coord_0;
+    out.tex_coord_1 = tex_coord_1;
     return out;
+}
+
+fn transform_tex_coord(transform: TextureTransform, in: VertexOutput) -> vec2<f32> {
+    let tex_coord = vec3(select(in.tex_coord_0, in.tex_coord_1, transform.uv_set == 1u), 1.0);
+    return vec2(dot(transform.row_0, tex_coord), dot(transform.row_1, tex_coord));
+}
+
+@vertex
+fn vertex_main(vert: VertexInput, @builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    return transform_vertex(vert.position, vert.normal, vert.color_0, vert.uv_0, vert.uv_1, vertex_index);
+};
+
+// Points are instanced, with each instance expanding its vertex into a quad of four corners
+@vertex
+fn vertex_point_main(vert: VertexInput, @builtin(vertex_index) corner_index: u32, @builtin(instance_index) point_index: u32) -> VertexOutput {
+    var out = transform_vertex(vert.position, vert.normal, vert.color_0, vert.uv_0, vert.uv_1, point_index);
+    let corner = vec2(f32(corner_index & 1u), f32(corner_index >> 1u)) * 2.0 - 1.0;
+    // Offsetting in clip space by w keeps the quad a constant size in pixels
+    out.position += vec4(corner * ubo.point_size / ubo.viewport_size * out.position.w, 0.0, 0.0);
+    return out;
+};
+
+@vertex
+fn vertex_static_main(vert: StaticVertexInput, @builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    return transform_vertex(vert.position, octahedral_decode(vert.normal), vec3(1.0), vert.uv_0, vert.uv_1, vertex_index);
+};
+
+@vertex
+fn vertex_colored_main(vert: ColoredVertexInput, @builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    return transform_vertex(vert.position, octahedral_decode(vert.normal), vert.color_0.rgb, vert.uv_0, vert.uv_1, vertex_index);
 };
 
 @fragment
 fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
     var base_color = material.base_color;
 
+    // Textures are sampled before discarding so mip selection stays in uniform control flow
+    var emissive = vec4(0.0);
+    if material.emissive_texture_index > -1 {
+        emissive = textureSample(texture_array[material.emissive_texture_index], sampler_array[material.emissive_sampler_index], transform_tex_coord(material.emissive_texture_transform, in)) * vec4(material.emissive_factor, 1.0);
+    }
+
     if material.base_texture_index > -1 {
-        base_color *= textureSampleLevel(texture_array[material.base_texture_index], sampler_array[material.sampler_index], in.tex_coord, 0.0);
+        base_color *= textureSample(texture_array[material.base_texture_index], sampler_array[material.base_sampler_index], transform_tex_coord(material.base_texture_transform, in));
     }
 
     if material.alpha_mode == 1 && base_color.a < material.alpha_cutoff {
         discard;
     }
 
-    if material.emissive_texture_index > -1 {
-        base_color += textureSampleLevel(texture_array[material.emissive_texture_index], sampler_array[material.sampler_index], in.tex_coord, 0.0) * vec4(material.emissive_factor, 1.0);
-    }
+    base_color += emissive;
 
     var color = base_color.rgb * in.color;
 
@@ -816,6 +1714,85 @@ fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
 }
 ";
 
+const ID_SHADER_SOURCE: &str = "
+struct Uniform {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    camera_position: vec4<f32>,
+    viewport_size: vec2<f32>,
+    point_size: f32,
+    padding: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> ubo: Uniform;
+
+struct DynamicUniform {
+    model: mat4x4<f32>,
+    morph_weights: array<vec4<f32>, 12>,
+};
+
+@group(1) @binding(0)
+var<uniform> mesh_ubo: DynamicUniform;
+
+struct MorphTargetVertex {
+    position: vec4<f32>,
+    normal: vec4<f32>,
+    tangent: vec4<f32>,
+};
+
+@group(1) @binding(1)
+var<storage, read> morph_targets: array<MorphTargetVertex>;
+
+struct MorphTargetRange {
+    base: u32,
+    stride: u32,
+    count: u32,
+    padding: u32,
+};
+
+fn morph_weight(morph_index: u32) -> f32 {
+    return mesh_ubo.morph_weights[morph_index / 4u][morph_index % 4u];
+}
+
+fn morph_target(range: MorphTargetRange, morph_index: u32, vertex_index: u32) -> MorphTargetVertex {
+    return morph_targets[range.base + morph_index * range.stride + vertex_index];
+}
+
+struct PickingIds {
+    node_id: u32,
+    primitive_id: u32,
+    morph_targets: MorphTargetRange,
+}
+var<push_constant> ids: PickingIds;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+};
+
+struct FragmentOutput {
+    @location(0) node_id: u32,
+    @location(1) primitive_id: u32,
+};
+
+@vertex
+fn vertex_main(vert: VertexInput, @builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    var position = vert.position;
+    for (var morph_index = 0u; morph_index < ids.morph_targets.count; morph_index++) {
+        position += morph_weight(morph_index) * morph_target(ids.morph_targets, morph_index, vertex_index).position.xyz;
+    }
+    return ubo.projection * ubo.view * mesh_ubo.model * vec4(position, 1.0);
+};
+
+@fragment
+fn fragment_main() -> FragmentOutput {
+    var out: FragmentOutput;
+    out.node_id = ids.node_id;
+    out.primitive_id = ids.primitive_id;
+    return out;
+}
+";
+
 impl From<wgpu::PrimitiveTopology> for crate::world::PrimitiveTopology {
     fn from(value: wgpu::PrimitiveTopology) -> Self {
         match value {
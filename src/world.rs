@@ -15,6 +15,8 @@ pub struct World {
     pub textures: Vec<Texture>,
     pub transforms: Vec<Transform>,
     pub vertices: Vec<Vertex>,
+    /// Displacements of every morph target, see `Primitive::morph_target_offset`
+    pub morph_targets: Vec<MorphTargetVertex>,
     pub primitive_meshes: Vec<PrimitiveMesh>,
     pub aabbs: Vec<AxisAlignedBoundingBox>,
     pub physics: crate::physics::PhysicsWorld,
@@ -39,6 +41,7 @@ impl World {
         let metadata_index = self.metadata.len();
         self.metadata.push(crate::world::NodeMetadata {
             name: "Node".to_string(),
+            extras: Extras::default(),
         });
 
         let node_index = self.nodes.len();
@@ -51,6 +54,8 @@ impl World {
             rigid_body_index: None,
             primitive_mesh_index: None,
             aabb_index: None,
+            morph_weights: Vec::new(),
+            skin_index: None,
         };
         self.nodes.push(node);
         node_index
@@ -100,21 +105,266 @@ impl World {
             None => transform,
         }
     }
+
+    /// Recomputes mesh and primitive bounds and points each mesh node at its local space box
+    pub fn compute_bounds(&mut self) {
+        let vertices = &self.vertices;
+        self.meshes
+            .iter_mut()
+            .for_each(|mesh| mesh.compute_bounds(vertices, &self.morph_targets));
+
+        self.aabbs.clear();
+        let meshes = &self.meshes;
+        let aabbs = &mut self.aabbs;
+        self.nodes.iter_mut().for_each(|node| {
+            node.aabb_index = node
+                .mesh_index
+                .map(|mesh_index| meshes[mesh_index].aabb)
+                .filter(|aabb| !aabb.is_empty())
+                .map(|aabb| {
+                    aabbs.push(aabb);
+                    aabbs.len() - 1
+                });
+        });
+    }
+
+    /// Whether each image holds color or linear data, judging by the material slots that sample it.
+    /// Images sampled as both, or not sampled at all, are treated as color.
+    pub fn image_color_spaces(&self) -> Vec<ColorSpace> {
+        let images = |texture_indices: &mut dyn Iterator<Item = Option<usize>>| {
+            texture_indices
+                .flatten()
+                .filter_map(|texture_index| self.textures.get(texture_index))
+                .map(|texture| texture.image_index)
+                .collect::<std::collections::HashSet<_>>()
+        };
+        let color_images = images(&mut self.materials.iter().flat_map(|material| {
+            [
+                material.base_color_texture_index,
+                material.emissive_texture_index,
+            ]
+        }));
+        let data_images = images(&mut self.materials.iter().flat_map(|material| {
+            [
+                material.normal_texture_index,
+                material.metallic_roughness_texture_index,
+                material.occlusion_texture_index,
+            ]
+        }));
+        (0..self.images.len())
+            .map(|image_index| {
+                if data_images.contains(&image_index) && !color_images.contains(&image_index) {
+                    ColorSpace::Linear
+                } else {
+                    ColorSpace::Srgb
+                }
+            })
+            .collect()
+    }
+
+    /// The morph target weights a node's mesh is drawn with
+    pub fn morph_weights(&self, node_index: usize) -> &[f32] {
+        let node = &self.nodes[node_index];
+        match node.mesh_index {
+            Some(_) if !node.morph_weights.is_empty() => &node.morph_weights,
+            Some(mesh_index) => &self.meshes[mesh_index].weights,
+            None => &[],
+        }
+    }
+
+    /// The world space bounds of a node's own mesh, ignoring its children
+    pub fn node_aabb(
+        &self,
+        scenegraph: &SceneGraph,
+        graph_node_index: petgraph::graph::NodeIndex,
+    ) -> AxisAlignedBoundingBox {
+        let transform = self.global_transform(scenegraph, graph_node_index);
+        self.local_mesh_aabb(scenegraph[graph_node_index])
+            .transform(&transform)
+    }
+
+    /// The world space bounds of a node's mesh and the meshes of all its descendants.
+    /// The box is empty if the subtree has no geometry.
+    pub fn subtree_aabb(
+        &self,
+        scenegraph: &SceneGraph,
+        graph_node_index: petgraph::graph::NodeIndex,
+    ) -> AxisAlignedBoundingBox {
+        let mut aabb = AxisAlignedBoundingBox::empty();
+        let mut stack = vec![(
+            graph_node_index,
+            self.global_transform(scenegraph, graph_node_index),
+        )];
+        while let Some((graph_node_index, transform)) = stack.pop() {
+            aabb.expand_to_include(
+                &self
+                    .local_mesh_aabb(scenegraph[graph_node_index])
+                    .transform(&transform),
+            );
+            scenegraph
+                .neighbors_directed(graph_node_index, petgraph::Direction::Outgoing)
+                .for_each(|child| {
+                    let node_index = scenegraph[child];
+                    let local = self.transforms[self.nodes[node_index].transform_index].matrix();
+                    stack.push((child, transform * local));
+                });
+        }
+        aabb
+    }
+
+    /// The world space bounds of every mesh in a scene
+    pub fn scene_aabb(&self, scene_index: usize) -> AxisAlignedBoundingBox {
+        let graph = &self.scenes[scene_index].graph;
+        graph.externals(petgraph::Direction::Incoming).fold(
+            AxisAlignedBoundingBox::empty(),
+            |mut aabb, root| {
+                aabb.expand_to_include(&self.subtree_aabb(graph, root));
+                aabb
+            },
+        )
+    }
+
+    fn local_mesh_aabb(&self, node_index: usize) -> AxisAlignedBoundingBox {
+        self.nodes[node_index]
+            .mesh_index
+            .map(|mesh_index| self.meshes[mesh_index].aabb)
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PrimitiveMesh {
     pub shape: Shape,
     pub color: nalgebra_glm::Vec4,
+    /// Draws the shape's edges instead of its filled surface
+    pub wireframe: bool,
 }
 
+/// Procedural shapes drawn by the debug renderer.
+/// Shapes are centered on the origin with Y up, except the arrow which starts at the origin and points along +Y.
 #[repr(C)]
 #[derive(
     Default, Copy, Clone, Debug, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize,
 )]
 pub enum Shape {
+    /// A cube spanning -1 to 1 on each axis
     #[default]
     Cube,
+    Sphere {
+        radius: f32,
+        sectors: u32,
+        stacks: u32,
+    },
+    /// A cylinder of `2 * half_height` capped with hemispheres, using `stacks` per hemisphere
+    Capsule {
+        radius: f32,
+        half_height: f32,
+        sectors: u32,
+        stacks: u32,
+    },
+    Cylinder {
+        radius: f32,
+        half_height: f32,
+        sectors: u32,
+    },
+    Cone {
+        radius: f32,
+        half_height: f32,
+        sectors: u32,
+    },
+    /// A plane in XZ facing +Y
+    Plane {
+        half_extents: nalgebra_glm::Vec2,
+        subdivisions: u32,
+    },
+    /// A torus around the Y axis
+    Torus {
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: u32,
+        minor_segments: u32,
+    },
+    Arrow {
+        length: f32,
+        shaft_radius: f32,
+        head_length: f32,
+        head_radius: f32,
+        sectors: u32,
+    },
+}
+
+impl Shape {
+    pub fn sphere() -> Self {
+        Self::Sphere {
+            radius: 1.0,
+            sectors: 32,
+            stacks: 16,
+        }
+    }
+
+    pub fn capsule() -> Self {
+        Self::Capsule {
+            radius: 0.5,
+            half_height: 0.5,
+            sectors: 32,
+            stacks: 8,
+        }
+    }
+
+    pub fn cylinder() -> Self {
+        Self::Cylinder {
+            radius: 1.0,
+            half_height: 1.0,
+            sectors: 32,
+        }
+    }
+
+    pub fn cone() -> Self {
+        Self::Cone {
+            radius: 1.0,
+            half_height: 1.0,
+            sectors: 32,
+        }
+    }
+
+    pub fn plane() -> Self {
+        Self::Plane {
+            half_extents: nalgebra_glm::vec2(1.0, 1.0),
+            subdivisions: 0,
+        }
+    }
+
+    pub fn torus() -> Self {
+        Self::Torus {
+            major_radius: 0.75,
+            minor_radius: 0.25,
+            major_segments: 32,
+            minor_segments: 16,
+        }
+    }
+
+    pub fn arrow() -> Self {
+        Self::Arrow {
+            length: 1.0,
+            shaft_radius: 0.05,
+            head_length: 0.25,
+            head_radius: 0.1,
+            sectors: 16,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cube => "Cube",
+            Self::Sphere { .. } => "Sphere",
+            Self::Capsule { .. } => "Capsule",
+            Self::Cylinder { .. } => "Cylinder",
+            Self::Cone { .. } => "Cone",
+            Self::Plane { .. } => "Plane",
+            Self::Torus { .. } => "Torus",
+            Self::Arrow { .. } => "Arrow",
+        }
+    }
 }
 
 #[repr(C)]
@@ -129,6 +379,48 @@ pub struct Vertex {
     pub joint_0: nalgebra_glm::Vec4,
     pub weight_0: nalgebra_glm::Vec4,
     pub color_0: nalgebra_glm::Vec3,
+    /// The xyz tangent direction, with the bitangent sign in w
+    pub tangent: nalgebra_glm::Vec4,
+}
+
+/// The layout a primitive's vertices are uploaded to the GPU with.
+/// Compact formats quantize attributes and leave out the ones the primitive does not use.
+#[derive(
+    Default, Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum VertexFormat {
+    /// Every attribute at full precision, laid out as `Vertex`
+    #[default]
+    Full,
+    /// Octahedral normals, snorm8 tangents and half float uvs, without colors or skinning
+    Static,
+    /// A static vertex with a unorm8 color
+    Colored,
+    /// A colored vertex with u16 joints and unorm8 weights
+    Skinned,
+}
+
+impl VertexFormat {
+    pub const ALL: [Self; 4] = [Self::Full, Self::Static, Self::Colored, Self::Skinned];
+}
+
+/// Offsets added to a vertex's attributes, scaled by the morph target's weight.
+/// The w components are unused and keep the layout aligned for the GPU.
+#[repr(C)]
+#[derive(
+    Default,
+    Debug,
+    Copy,
+    Clone,
+    serde::Serialize,
+    serde::Deserialize,
+    bytemuck::Pod,
+    bytemuck::Zeroable,
+)]
+pub struct MorphTargetVertex {
+    pub position: nalgebra_glm::Vec4,
+    pub normal: nalgebra_glm::Vec4,
+    pub tangent: nalgebra_glm::Vec4,
 }
 
 impl Default for Vertex {
@@ -141,6 +433,7 @@ impl Default for Vertex {
             joint_0: nalgebra_glm::Vec4::default(),
             weight_0: nalgebra_glm::Vec4::default(),
             color_0: nalgebra_glm::vec3(1.0, 1.0, 1.0),
+            tangent: nalgebra_glm::vec4(1.0, 0.0, 0.0, 1.0),
         }
     }
 }
@@ -151,11 +444,123 @@ pub type SceneGraph = petgraph::Graph<usize, ()>;
 pub struct Scene {
     pub default_camera_graph_node_index: petgraph::graph::NodeIndex,
     pub graph: SceneGraph,
+    #[serde(default)]
+    pub extras: Extras,
 }
 
 #[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Mesh {
     pub primitives: Vec<Primitive>,
+    /// Simplified versions of the primitives, from most to least detailed
+    pub lods: Vec<MeshLod>,
+    /// Default morph target weights, used by nodes that do not specify their own
+    pub weights: Vec<f32>,
+    /// Local space bounds of all primitives
+    pub aabb: AxisAlignedBoundingBox,
+    pub bounding_sphere: BoundingSphere,
+    #[serde(default)]
+    pub extras: Extras,
+}
+
+impl Mesh {
+    /// Returns the index of the level of detail to draw at a projected screen size,
+    /// where zero is the full detail mesh, along with its primitives
+    pub fn lod_primitives(&self, screen_size: f32) -> (usize, &[Primitive]) {
+        self.lods
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, lod)| screen_size < lod.screen_size)
+            .map_or((0, &self.primitives[..]), |(lod_index, lod)| {
+                (lod_index + 1, &lod.primitives[..])
+            })
+    }
+
+    /// Recomputes the local space bounds of each primitive and of the mesh as a whole.
+    /// Bounds of morphed primitives are grown to contain any blend of weights between zero and one.
+    pub fn compute_bounds(&mut self, vertices: &[Vertex], morph_targets: &[MorphTargetVertex]) {
+        let primitive_vertices = |primitive: &Primitive| {
+            &vertices
+                [primitive.vertex_offset..primitive.vertex_offset + primitive.number_of_vertices]
+        };
+        self.primitives.iter_mut().for_each(|primitive| {
+            let vertices = primitive_vertices(primitive);
+            primitive.aabb = AxisAlignedBoundingBox::from_vertices(vertices);
+            primitive.bounding_sphere = BoundingSphere::from_vertices(vertices);
+            if primitive.aabb.is_empty() {
+                return;
+            }
+            let primitive_morph_targets = &morph_targets[primitive.morph_target_offset
+                ..primitive.morph_target_offset
+                    + primitive.number_of_morph_targets * primitive.number_of_vertices];
+            primitive_morph_targets
+                .chunks_exact(primitive.number_of_vertices.max(1))
+                .for_each(|displacements| {
+                    let displacement_bounds = AxisAlignedBoundingBox::from_points(
+                        displacements
+                            .iter()
+                            .map(|displacement| displacement.position.xyz())
+                            .collect::<Vec<_>>()
+                            .iter(),
+                    );
+                    primitive.aabb.min +=
+                        nalgebra_glm::min2(&displacement_bounds.min, &nalgebra_glm::Vec3::zeros());
+                    primitive.aabb.max +=
+                        nalgebra_glm::max2(&displacement_bounds.max, &nalgebra_glm::Vec3::zeros());
+                    let longest = displacements
+                        .iter()
+                        .map(|displacement| displacement.position.xyz().norm())
+                        .fold(0.0, f32::max);
+                    primitive.bounding_sphere.radius += longest;
+                });
+        });
+        self.lods.iter_mut().for_each(|lod| {
+            lod.primitives
+                .iter_mut()
+                .zip(self.primitives.iter())
+                .for_each(|(lod_primitive, primitive)| {
+                    lod_primitive.aabb = primitive.aabb;
+                    lod_primitive.bounding_sphere = primitive.bounding_sphere;
+                });
+        });
+
+        self.aabb = AxisAlignedBoundingBox::empty();
+        self.primitives
+            .iter()
+            .for_each(|primitive| self.aabb.expand_to_include(&primitive.aabb));
+        if self.aabb.is_empty() {
+            self.bounding_sphere = BoundingSphere::default();
+            return;
+        }
+        let center = self.aabb.center();
+        let radius = self
+            .primitives
+            .iter()
+            .flat_map(|primitive| primitive_vertices(primitive).iter())
+            .map(|vertex| nalgebra_glm::distance(&center, &vertex.position))
+            .fold(0.0, f32::max);
+        // Morphed primitives may reach beyond their vertices, so their grown spheres are included too
+        let morph_radius = self
+            .primitives
+            .iter()
+            .filter(|primitive| primitive.number_of_morph_targets > 0)
+            .map(|primitive| {
+                nalgebra_glm::distance(&center, &primitive.bounding_sphere.center)
+                    + primitive.bounding_sphere.radius
+            })
+            .fold(0.0, f32::max);
+        self.bounding_sphere = BoundingSphere::new(center, radius.max(morph_radius));
+    }
+}
+
+#[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MeshLod {
+    /// Primitives sharing the vertices of the full detail primitives with simplified indices
+    pub primitives: Vec<Primitive>,
+    /// The level is drawn once the mesh's projected height falls below this fraction of the viewport height
+    pub screen_size: f32,
+    /// The simplification error relative to the mesh's extents
+    pub error: f32,
 }
 
 #[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -273,7 +678,20 @@ pub fn create_camera_matrices(
     scene: &crate::world::Scene,
     aspect_ratio: f32,
 ) -> (nalgebra_glm::Vec3, nalgebra_glm::Mat4, nalgebra_glm::Mat4) {
-    let camera_graph_node_index = scene.default_camera_graph_node_index;
+    create_camera_matrices_for_node(
+        world,
+        scene,
+        scene.default_camera_graph_node_index,
+        aspect_ratio,
+    )
+}
+
+pub fn create_camera_matrices_for_node(
+    world: &crate::world::World,
+    scene: &crate::world::Scene,
+    camera_graph_node_index: petgraph::graph::NodeIndex,
+    aspect_ratio: f32,
+) -> (nalgebra_glm::Vec3, nalgebra_glm::Mat4, nalgebra_glm::Mat4) {
     let camera_node_index = scene.graph[camera_graph_node_index];
     let camera_node = &world.nodes[camera_node_index];
     let camera = &world.cameras[camera_node
@@ -299,6 +717,14 @@ pub fn create_camera_matrices(
     )
 }
 
+/// Remaps a projection with a zero-to-one depth range so the near plane maps to 1 and the far plane to 0.
+/// For an infinite perspective projection this yields the exact reverse-Z infinite projection.
+pub fn reverse_z(projection: &nalgebra_glm::Mat4) -> nalgebra_glm::Mat4 {
+    let mut reversed = *projection;
+    reversed.set_row(2, &(projection.row(3) - projection.row(2)));
+    reversed
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct PerspectiveCamera {
     pub aspect_ratio: Option<f32>,
@@ -451,7 +877,7 @@ impl Default for Orientation {
     }
 }
 
-#[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum PrimitiveTopology {
     Points,
     Lines,
@@ -509,6 +935,13 @@ pub struct Primitive {
     pub number_of_indices: usize,
     pub topology: PrimitiveTopology,
     pub material_index: Option<usize>,
+    pub vertex_format: VertexFormat,
+    /// The first displacement in `World::morph_targets`.
+    /// Targets are stored one after another, each with a displacement per vertex of the primitive.
+    pub morph_target_offset: usize,
+    pub number_of_morph_targets: usize,
+    pub aabb: AxisAlignedBoundingBox,
+    pub bounding_sphere: BoundingSphere,
 }
 
 #[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -521,19 +954,230 @@ pub struct Node {
     pub rigid_body_index: Option<usize>,
     pub primitive_mesh_index: Option<usize>,
     pub aabb_index: Option<usize>,
+    /// Morph target weights overriding the mesh's default weights when not empty
+    pub morph_weights: Vec<f32>,
+    /// The skin whose joints deform the node's mesh
+    #[serde(default)]
+    pub skin_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct NodeMetadata {
     pub name: String,
+    #[serde(default)]
+    pub extras: Extras,
+}
+
+/// Application specific properties from gltf `extras`, such as custom properties set in Blender.
+/// Only object extras are kept, as those are what exporters write.
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Extras(pub std::collections::BTreeMap<String, ExtraValue>);
+
+impl Extras {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ExtraValue> {
+        self.0.get(key)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)?.as_bool()
+    }
+
+    pub fn get_f32(&self, key: &str) -> Option<f32> {
+        self.get(key)?.as_f64().map(|value| value as f32)
+    }
+
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key)?.as_i64()
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_str()
+    }
+
+    /// Reads an array of three numbers, such as a Blender vector property
+    pub fn get_vec3(&self, key: &str) -> Option<nalgebra_glm::Vec3> {
+        match self.get(key)?.as_array()? {
+            [x, y, z] => Some(nalgebra_glm::vec3(
+                x.as_f64()? as f32,
+                y.as_f64()? as f32,
+                z.as_f64()? as f32,
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// A json value from gltf `extras`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ExtraValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<ExtraValue>),
+    Object(std::collections::BTreeMap<String, ExtraValue>),
+}
+
+impl ExtraValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Reads whole numbers that fit in an i64, as json does not distinguish integers
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_f64()
+            .filter(|value| value.fract() == 0.0 && value.abs() < i64::MAX as f64)
+            .map(|value| value as i64)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[ExtraValue]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&std::collections::BTreeMap<String, ExtraValue>> {
+        match self {
+            Self::Object(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ExtraValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Number(value) => write!(f, "{value}"),
+            Self::String(value) => write!(f, "{value:?}"),
+            Self::Array(values) => {
+                write!(f, "[")?;
+                values.iter().enumerate().try_for_each(|(index, value)| {
+                    let separator = if index > 0 { ", " } else { "" };
+                    write!(f, "{separator}{value}")
+                })?;
+                write!(f, "]")
+            }
+            Self::Object(values) => {
+                write!(f, "{{")?;
+                values
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(index, (key, value))| {
+                        let separator = if index > 0 { ", " } else { "" };
+                        write!(f, "{separator}{key:?}: {value}")
+                    })?;
+                write!(f, "}}")
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Image {
+    /// Tightly packed rows of pixels, with multi-byte components in native byte order
     pub pixels: Vec<u8>,
+    /// The format of the pixels, or the format compressed blocks decode to
     pub format: ImageFormat,
     pub width: u32,
     pub height: u32,
+    /// Smaller mip levels that came with the image, each half the size of the one before it
+    #[serde(default)]
+    pub mips: Vec<Vec<u8>>,
+    /// Set when the pixels and mips are compressed blocks rather than pixels
+    #[serde(default)]
+    pub compression: Option<BlockCompression>,
+}
+
+impl Image {
+    /// A single opaque white pixel, standing in for images that cannot be used
+    pub fn placeholder() -> Self {
+        Self {
+            pixels: vec![u8::MAX; 4],
+            format: ImageFormat::R8G8B8A8,
+            width: 1,
+            height: 1,
+            mips: Vec::new(),
+            compression: None,
+        }
+    }
+
+    /// Decodes the pixels to normalized rgba values.
+    /// Grayscale formats are expanded to rgb and missing alpha is opaque.
+    pub fn to_rgba_f32(&self) -> Vec<nalgebra_glm::Vec4> {
+        self.format.to_rgba_f32(&self.pixels)
+    }
+}
+
+/// Block compressed encodings, which gpus sample without decompressing
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum BlockCompression {
+    Bc1,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc5,
+    Bc6h,
+    Bc7,
+    Etc2Rgb8,
+    Etc2Rgb8A1,
+    Etc2Rgba8,
+    EacR11,
+    EacRg11,
+    Astc { block_width: u32, block_height: u32 },
+}
+
+impl BlockCompression {
+    pub fn block_dimensions(&self) -> (u32, u32) {
+        match self {
+            Self::Astc {
+                block_width,
+                block_height,
+            } => (*block_width, *block_height),
+            _ => (4, 4),
+        }
+    }
+
+    /// The size of a single block in bytes
+    pub fn block_size(&self) -> usize {
+        match self {
+            Self::Bc1 | Self::Bc4 | Self::Etc2Rgb8 | Self::Etc2Rgb8A1 | Self::EacR11 => 8,
+            _ => 16,
+        }
+    }
+}
+
+/// How the color channels of an image are encoded
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ColorSpace {
+    /// Base color and emissive images, sampled with hardware srgb decoding
+    #[default]
+    Srgb,
+    /// Normal, metallic-roughness and occlusion images, sampled as is
+    Linear,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -562,7 +1206,108 @@ pub enum ImageFormat {
     R32G32B32A32F,
 }
 
-#[derive(Default, Clone, Debug, serde::Serialize, serde::Deserialize)]
+impl ImageFormat {
+    pub fn channels(&self) -> usize {
+        match self {
+            Self::R8 | Self::R16 | Self::R16F | Self::R32 | Self::R32F => 1,
+            Self::R8G8 | Self::R16G16 | Self::R16G16F | Self::R32G32 | Self::R32G32F => 2,
+            Self::R8G8B8
+            | Self::B8G8R8
+            | Self::R16G16B16
+            | Self::R16G16B16F
+            | Self::R32G32B32
+            | Self::R32G32B32F => 3,
+            Self::R8G8B8A8
+            | Self::B8G8R8A8
+            | Self::R16G16B16A16
+            | Self::R16G16B16A16F
+            | Self::R32G32B32A32
+            | Self::R32G32B32A32F => 4,
+        }
+    }
+
+    /// The size of a single channel in bytes
+    pub fn component_size(&self) -> usize {
+        match self {
+            Self::R8
+            | Self::R8G8
+            | Self::R8G8B8
+            | Self::R8G8B8A8
+            | Self::B8G8R8
+            | Self::B8G8R8A8 => 1,
+            Self::R16
+            | Self::R16G16
+            | Self::R16G16B16
+            | Self::R16G16B16A16
+            | Self::R16F
+            | Self::R16G16F
+            | Self::R16G16B16F
+            | Self::R16G16B16A16F => 2,
+            Self::R32
+            | Self::R32G32
+            | Self::R32G32B32
+            | Self::R32G32B32A32
+            | Self::R32F
+            | Self::R32G32F
+            | Self::R32G32B32F
+            | Self::R32G32B32A32F => 4,
+        }
+    }
+
+    /// Decodes tightly packed pixels of this format to normalized rgba values
+    pub fn to_rgba_f32(&self, pixels: &[u8]) -> Vec<nalgebra_glm::Vec4> {
+        let channels = self.channels();
+        let component_size = self.component_size();
+        let component = |bytes: &[u8]| -> f32 {
+            match self {
+                ImageFormat::R16F
+                | ImageFormat::R16G16F
+                | ImageFormat::R16G16B16F
+                | ImageFormat::R16G16B16A16F => {
+                    crate::quantize::f16_to_f32(u16::from_ne_bytes([bytes[0], bytes[1]]))
+                }
+                ImageFormat::R32F
+                | ImageFormat::R32G32F
+                | ImageFormat::R32G32B32F
+                | ImageFormat::R32G32B32A32F => {
+                    f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+                }
+                _ => match component_size {
+                    1 => bytes[0] as f32 / u8::MAX as f32,
+                    2 => u16::from_ne_bytes([bytes[0], bytes[1]]) as f32 / u16::MAX as f32,
+                    _ => {
+                        u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32
+                            / u32::MAX as f32
+                    }
+                },
+            }
+        };
+        let bgr = matches!(self, ImageFormat::B8G8R8 | ImageFormat::B8G8R8A8);
+        pixels
+            .chunks_exact(channels * component_size)
+            .map(|pixel| {
+                let mut values = [0.0; 4];
+                pixel
+                    .chunks_exact(component_size)
+                    .zip(values.iter_mut())
+                    .for_each(|(bytes, value)| *value = component(bytes));
+                let rgba = match channels {
+                    1 => nalgebra_glm::vec4(values[0], values[0], values[0], 1.0),
+                    2 => nalgebra_glm::vec4(values[0], values[0], values[0], values[1]),
+                    3 => nalgebra_glm::vec4(values[0], values[1], values[2], 1.0),
+                    _ => nalgebra_glm::vec4(values[0], values[1], values[2], values[3]),
+                };
+                if bgr {
+                    nalgebra_glm::vec4(rgba.z, rgba.y, rgba.x, rgba.w)
+                } else {
+                    rgba
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Sampler {
     pub min_filter: MinFilter,
     pub mag_filter: MagFilter,
@@ -570,7 +1315,7 @@ pub struct Sampler {
     pub wrap_t: WrappingMode,
 }
 
-#[derive(Default, Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Default, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum WrappingMode {
     ClampToEdge,
     MirroredRepeat,
@@ -578,14 +1323,14 @@ pub enum WrappingMode {
     Repeat,
 }
 
-#[derive(Default, Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Default, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum MagFilter {
     Nearest = 1,
     #[default]
     Linear,
 }
 
-#[derive(Default, Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Default, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum MinFilter {
     Nearest = 1,
     #[default]
@@ -596,14 +1341,130 @@ pub enum MinFilter {
     LinearMipmapLinear,
 }
 
-#[derive(Default, Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Material {
     pub base_color_factor: nalgebra_glm::Vec4,
-    pub base_color_texture_index: usize,
-    pub emissive_texture_index: usize,
+    /// Indices into the world's textures, which pair an image with a sampler
+    pub base_color_texture_index: Option<usize>,
+    pub emissive_texture_index: Option<usize>,
+    pub normal_texture_index: Option<usize>,
+    pub metallic_roughness_texture_index: Option<usize>,
+    pub occlusion_texture_index: Option<usize>,
     pub emissive_factor: nalgebra_glm::Vec3,
     pub alpha_mode: AlphaMode,
     pub alpha_cutoff: Option<f32>,
+    #[serde(default)]
+    pub extras: Extras,
+    /// The uv set and transform each texture slot samples with
+    #[serde(default)]
+    pub base_color_texture_transform: TextureTransform,
+    #[serde(default)]
+    pub emissive_texture_transform: TextureTransform,
+    #[serde(default)]
+    pub normal_texture_transform: TextureTransform,
+    #[serde(default)]
+    pub metallic_roughness_texture_transform: TextureTransform,
+    #[serde(default)]
+    pub occlusion_texture_transform: TextureTransform,
+    /// Scales the x and y of the sampled tangent space normal
+    #[serde(default = "default_texture_strength")]
+    pub normal_texture_scale: f32,
+    /// How strongly the occlusion texture darkens indirect lighting, from 0 to 1
+    #[serde(default = "default_texture_strength")]
+    pub occlusion_strength: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            base_color_factor: nalgebra_glm::Vec4::zeros(),
+            base_color_texture_index: None,
+            emissive_texture_index: None,
+            normal_texture_index: None,
+            metallic_roughness_texture_index: None,
+            occlusion_texture_index: None,
+            emissive_factor: nalgebra_glm::Vec3::zeros(),
+            alpha_mode: AlphaMode::default(),
+            alpha_cutoff: None,
+            extras: Extras::default(),
+            base_color_texture_transform: TextureTransform::default(),
+            emissive_texture_transform: TextureTransform::default(),
+            normal_texture_transform: TextureTransform::default(),
+            metallic_roughness_texture_transform: TextureTransform::default(),
+            occlusion_texture_transform: TextureTransform::default(),
+            normal_texture_scale: default_texture_strength(),
+            occlusion_strength: default_texture_strength(),
+        }
+    }
+}
+
+fn default_texture_strength() -> f32 {
+    1.0
+}
+
+/// Selects the uv set a texture is sampled with and transforms its coordinates,
+/// following the gltf KHR_texture_transform extension
+#[derive(Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TextureTransform {
+    /// 0 samples with `Vertex::uv_0` and 1 with `Vertex::uv_1`
+    pub uv_set: usize,
+    pub offset: nalgebra_glm::Vec2,
+    /// Counterclockwise rotation of the coordinates in radians
+    pub rotation: f32,
+    pub scale: nalgebra_glm::Vec2,
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
+        Self {
+            uv_set: 0,
+            offset: nalgebra_glm::Vec2::zeros(),
+            rotation: 0.0,
+            scale: nalgebra_glm::vec2(1.0, 1.0),
+        }
+    }
+}
+
+impl TextureTransform {
+    pub fn is_identity(&self) -> bool {
+        let identity = Self::default();
+        self.offset == identity.offset
+            && self.rotation == identity.rotation
+            && self.scale == identity.scale
+    }
+
+    /// Scales, then rotates, then offsets homogeneous texture coordinates
+    pub fn matrix(&self) -> nalgebra_glm::Mat3 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let translation = nalgebra_glm::mat3(
+            1.0,
+            0.0,
+            self.offset.x,
+            0.0,
+            1.0,
+            self.offset.y,
+            0.0,
+            0.0,
+            1.0,
+        );
+        let rotation = nalgebra_glm::mat3(cos, sin, 0.0, -sin, cos, 0.0, 0.0, 0.0, 1.0);
+        let scale = nalgebra_glm::mat3(
+            self.scale.x,
+            0.0,
+            0.0,
+            0.0,
+            self.scale.y,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        );
+        translation * rotation * scale
+    }
+
+    pub fn apply(&self, uv: &nalgebra_glm::Vec2) -> nalgebra_glm::Vec2 {
+        (self.matrix() * nalgebra_glm::vec3(uv.x, uv.y, 1.0)).xy()
+    }
 }
 
 #[derive(Default, Copy, Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -662,11 +1523,29 @@ pub struct AxisAlignedBoundingBox {
     pub max: nalgebra_glm::Vec3,
 }
 
+impl Default for AxisAlignedBoundingBox {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
 impl AxisAlignedBoundingBox {
     pub fn new(min: nalgebra_glm::Vec3, max: nalgebra_glm::Vec3) -> Self {
         Self { min, max }
     }
 
+    /// An inverted box that contains nothing and becomes the other box when expanded
+    pub fn empty() -> Self {
+        Self {
+            min: nalgebra_glm::Vec3::repeat(f32::MAX),
+            max: nalgebra_glm::Vec3::repeat(f32::MIN),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|axis| self.min[axis] > self.max[axis])
+    }
+
     pub fn extents(&self) -> nalgebra_glm::Vec3 {
         self.max - self.min
     }
@@ -675,20 +1554,362 @@ impl AxisAlignedBoundingBox {
         (self.min + self.max) / 2.0
     }
 
+    /// Returns an empty box if there are no vertices
     pub fn from_vertices(vertices: &[Vertex]) -> Self {
-        let mut min = vertices[0].position;
-        let mut max = vertices[0].position;
-
-        for point in vertices.iter().skip(1) {
-            min = nalgebra_glm::min2(&min, &point.position);
-            max = nalgebra_glm::max2(&max, &point.position);
-        }
+        Self::from_points(vertices.iter().map(|vertex| &vertex.position))
+    }
 
-        Self { min, max }
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a nalgebra_glm::Vec3>) -> Self {
+        points.into_iter().fold(Self::empty(), |mut aabb, point| {
+            aabb.expand_to_include_point(point);
+            aabb
+        })
     }
 
     pub fn expand_to_include(&mut self, other: &AxisAlignedBoundingBox) {
         self.min = nalgebra_glm::min2(&self.min, &other.min);
         self.max = nalgebra_glm::max2(&self.max, &other.max);
     }
+
+    pub fn expand_to_include_point(&mut self, point: &nalgebra_glm::Vec3) {
+        self.min = nalgebra_glm::min2(&self.min, point);
+        self.max = nalgebra_glm::max2(&self.max, point);
+    }
+
+    pub fn contains_point(&self, point: &nalgebra_glm::Vec3) -> bool {
+        (0..3).all(|axis| self.min[axis] <= point[axis] && point[axis] <= self.max[axis])
+    }
+
+    pub fn corners(&self) -> [nalgebra_glm::Vec3; 8] {
+        std::array::from_fn(|corner| {
+            nalgebra_glm::vec3(
+                if corner & 1 == 0 {
+                    self.min.x
+                } else {
+                    self.max.x
+                },
+                if corner & 2 == 0 {
+                    self.min.y
+                } else {
+                    self.max.y
+                },
+                if corner & 4 == 0 {
+                    self.min.z
+                } else {
+                    self.max.z
+                },
+            )
+        })
+    }
+
+    /// Returns the smallest axis aligned box containing this box after an affine transform.
+    /// Each output extent sums the absolute contributions of the input extents (Arvo's method),
+    /// which is tighter than the box around the origin and cheaper than transforming all eight corners.
+    pub fn transform(&self, transform: &nalgebra_glm::Mat4) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        let center = transform * self.center().push(1.0);
+        let half_extents = self.extents() / 2.0;
+        let linear = transform.fixed_view::<3, 3>(0, 0).abs();
+        let half_extents = linear * half_extents;
+        Self {
+            min: center.xyz() - half_extents,
+            max: center.xyz() + half_extents,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct BoundingSphere {
+    pub center: nalgebra_glm::Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: nalgebra_glm::Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Centers the sphere on the points' bounding box and grows it to reach the furthest point
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        let aabb = AxisAlignedBoundingBox::from_vertices(vertices);
+        if aabb.is_empty() {
+            return Self::default();
+        }
+        let center = aabb.center();
+        let radius = vertices
+            .iter()
+            .map(|vertex| nalgebra_glm::distance(&center, &vertex.position))
+            .fold(0.0, f32::max);
+        Self { center, radius }
+    }
+
+    /// The radius is scaled by the largest axis scale so the sphere still contains
+    /// the transformed geometry under non-uniform scaling
+    pub fn transform(&self, transform: &nalgebra_glm::Mat4) -> Self {
+        let center = transform * self.center.push(1.0);
+        let scale = (0..3)
+            .map(|column| transform.fixed_view::<3, 1>(0, column).norm())
+            .fold(0.0, f32::max);
+        Self {
+            center: center.xyz(),
+            radius: self.radius * scale,
+        }
+    }
+
+    pub fn contains_point(&self, point: &nalgebra_glm::Vec3) -> bool {
+        nalgebra_glm::distance2(&self.center, point) <= self.radius * self.radius
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct OrientedBoundingBox {
+    pub center: nalgebra_glm::Vec3,
+    pub half_extents: nalgebra_glm::Vec3,
+    /// Unit axes of the box as columns
+    pub axes: nalgebra_glm::Mat3,
+}
+
+impl OrientedBoundingBox {
+    /// The box is exact for transforms without shear,
+    /// in which case the axes are orthonormal
+    pub fn from_aabb(aabb: &AxisAlignedBoundingBox, transform: &nalgebra_glm::Mat4) -> Self {
+        let center = transform * aabb.center().push(1.0);
+        let half_extents = aabb.extents() / 2.0;
+        let mut axes = nalgebra_glm::Mat3::identity();
+        let mut scaled_half_extents = nalgebra_glm::Vec3::zeros();
+        (0..3).for_each(|axis| {
+            let column = transform.fixed_view::<3, 1>(0, axis).into_owned();
+            let length = column.norm();
+            if length > f32::EPSILON {
+                axes.set_column(axis, &(column / length));
+            }
+            scaled_half_extents[axis] = half_extents[axis] * length;
+        });
+        Self {
+            center: center.xyz(),
+            half_extents: scaled_half_extents,
+            axes,
+        }
+    }
+
+    pub fn corners(&self) -> [nalgebra_glm::Vec3; 8] {
+        std::array::from_fn(|corner| {
+            let sign = |bit: usize| if corner & bit == 0 { -1.0 } else { 1.0 };
+            self.center
+                + self.axes.column(0) * self.half_extents.x * sign(1)
+                + self.axes.column(1) * self.half_extents.y * sign(2)
+                + self.axes.column(2) * self.half_extents.z * sign(4)
+        })
+    }
+
+    pub fn contains_point(&self, point: &nalgebra_glm::Vec3) -> bool {
+        let local = self.axes.transpose() * (point - self.center);
+        // Rounding in the rotation grows with the box's size and distance from the origin,
+        // and points on the surface should still be contained
+        let tolerance = 1e-5 * (1.0 + self.center.abs().max() + self.half_extents.max());
+        (0..3).all(|axis| local[axis].abs() <= self.half_extents[axis] + tolerance)
+    }
+
+    /// The axis aligned box enclosing this box
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::from_points(self.corners().iter())
+    }
+
+    /// Returns a transform mapping the unit cube spanning -1 to 1 onto this box
+    pub fn matrix(&self) -> nalgebra_glm::Mat4 {
+        let mut matrix = nalgebra_glm::translation(&self.center);
+        (0..3).for_each(|axis| {
+            let column = self.axes.column(axis) * self.half_extents[axis];
+            matrix.fixed_view_mut::<3, 1>(0, axis).copy_from(&column);
+        });
+        matrix
+    }
+}
+
+/// The side planes of a view frustum, used to cull bounding volumes.
+/// The near and far planes are left out so the test is unaffected by reverse or infinite depth.
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum {
+    pub planes: [nalgebra_glm::Vec4; 4],
+}
+
+impl Frustum {
+    pub fn from_view_projection(view_projection: &nalgebra_glm::Mat4) -> Self {
+        let row = |index: usize| view_projection.row(index).transpose();
+        let planes = [
+            row(3) + row(0),
+            row(3) - row(0),
+            row(3) + row(1),
+            row(3) - row(1),
+        ]
+        .map(|plane| {
+            let length = plane.xyz().norm();
+            if length > f32::EPSILON {
+                plane / length
+            } else {
+                plane
+            }
+        });
+        Self { planes }
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.xyz().dot(&sphere.center) + plane.w >= -sphere.radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+
+    fn rotated_and_translated() -> glm::Mat4 {
+        glm::translation(&glm::vec3(4.0, -2.0, 7.0))
+            * glm::rotation(0.7, &glm::vec3(1.0, 2.0, 3.0).normalize())
+            * glm::scaling(&glm::vec3(1.0, 2.0, 0.5))
+    }
+
+    fn assert_near(a: &glm::Vec3, b: &glm::Vec3) {
+        assert!(glm::distance(a, b) < 1e-4, "{a:?} != {b:?}");
+    }
+
+    fn transform_point(transform: &glm::Mat4, point: &glm::Vec3) -> glm::Vec3 {
+        (transform * point.push(1.0)).xyz()
+    }
+
+    #[test]
+    fn empty_bounds_stay_empty() {
+        let aabb = super::AxisAlignedBoundingBox::from_vertices(&[]);
+        assert!(aabb.is_empty());
+        assert!(aabb.transform(&rotated_and_translated()).is_empty());
+        let sphere = super::BoundingSphere::from_vertices(&[]);
+        assert_eq!(sphere.radius, 0.0);
+
+        let mut grown = super::AxisAlignedBoundingBox::empty();
+        let unit = super::AxisAlignedBoundingBox::new(glm::Vec3::zeros(), glm::vec3(1.0, 1.0, 1.0));
+        grown.expand_to_include(&unit);
+        assert_eq!(grown.min, unit.min);
+        assert_eq!(grown.max, unit.max);
+    }
+
+    #[test]
+    fn transformed_aabb_matches_its_transformed_corners() {
+        let aabb = super::AxisAlignedBoundingBox::new(
+            glm::vec3(-1.0, 0.5, -3.0),
+            glm::vec3(2.0, 1.5, 1.0),
+        );
+        let transform = rotated_and_translated();
+        let transformed = aabb.transform(&transform);
+        let corners = aabb
+            .corners()
+            .map(|corner| transform_point(&transform, &corner));
+        let brute_force = super::AxisAlignedBoundingBox::from_points(corners.iter());
+        assert_near(&transformed.min, &brute_force.min);
+        assert_near(&transformed.max, &brute_force.max);
+    }
+
+    #[test]
+    fn spheres_and_oriented_boxes_contain_their_geometry() {
+        let vertices = [
+            glm::vec3(-1.0, 0.5, -3.0),
+            glm::vec3(2.0, 1.5, 1.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(1.5, 0.5, -2.0),
+        ]
+        .map(|position| super::Vertex {
+            position,
+            ..Default::default()
+        });
+        let transform = rotated_and_translated();
+        let transformed_points = vertices
+            .iter()
+            .map(|vertex| transform_point(&transform, &vertex.position))
+            .collect::<Vec<_>>();
+
+        let sphere = super::BoundingSphere::from_vertices(&vertices);
+        assert!(vertices
+            .iter()
+            .all(|vertex| sphere.contains_point(&vertex.position)));
+        // The radius reaches the furthest point exactly
+        assert!(
+            (sphere.radius - glm::distance(&sphere.center, &vertices[0].position)).abs() < 1e-6
+        );
+        let transformed_sphere = sphere.transform(&transform);
+        assert!(transformed_points.iter().all(|point| {
+            glm::distance(&transformed_sphere.center, point) <= transformed_sphere.radius + 1e-4
+        }));
+
+        let aabb = super::AxisAlignedBoundingBox::from_vertices(&vertices);
+        let obb = super::OrientedBoundingBox::from_aabb(&aabb, &transform);
+        assert!(transformed_points
+            .iter()
+            .all(|point| obb.contains_point(point)));
+        assert!(!obb.contains_point(&transform_point(&transform, &glm::vec3(3.0, 1.0, 0.0))));
+        // Without shear the box's corners are exactly the transformed corners
+        let mut expected = aabb
+            .corners()
+            .map(|corner| transform_point(&transform, &corner));
+        let mut corners = obb.corners();
+        let order = |a: &glm::Vec3, b: &glm::Vec3| a.as_slice().partial_cmp(b.as_slice()).unwrap();
+        expected.sort_by(order);
+        corners.sort_by(order);
+        expected
+            .iter()
+            .zip(corners.iter())
+            .for_each(|(expected, corner)| assert_near(expected, corner));
+        let enclosing = obb.aabb();
+        let transformed_aabb = aabb.transform(&transform);
+        assert_near(&enclosing.min, &transformed_aabb.min);
+        assert_near(&enclosing.max, &transformed_aabb.max);
+    }
+
+    #[test]
+    fn subtree_bounds_include_transformed_descendants() {
+        let mut world = super::World::default();
+        world.meshes.push(super::Mesh {
+            aabb: super::AxisAlignedBoundingBox::new(
+                glm::vec3(-1.0, -1.0, -1.0),
+                glm::vec3(1.0, 1.0, 1.0),
+            ),
+            ..Default::default()
+        });
+        world.scenes.push(super::Scene::default());
+
+        // An empty root with a mesh child, which has a scaled and offset mesh grandchild
+        let root = world.add_node();
+        let child = world.add_node();
+        let grandchild = world.add_node();
+        world.nodes[child].mesh_index = Some(0);
+        world.nodes[grandchild].mesh_index = Some(0);
+        world.transforms[world.nodes[root].transform_index].translation = glm::vec3(10.0, 0.0, 0.0);
+        world.transforms[world.nodes[child].transform_index].translation = glm::vec3(0.0, 5.0, 0.0);
+        let grandchild_transform = &mut world.transforms[world.nodes[grandchild].transform_index];
+        grandchild_transform.translation = glm::vec3(0.0, 0.0, 4.0);
+        grandchild_transform.scale = glm::vec3(2.0, 2.0, 2.0);
+
+        let root_graph_node_index = world.scenes[0].graph.add_node(root);
+        world.add_child_node(0, root_graph_node_index, child);
+        let child_graph_node_index = petgraph::graph::NodeIndex::new(1);
+        world.add_child_node(0, child_graph_node_index, grandchild);
+        let graph = &world.scenes[0].graph;
+
+        let aabb = world.subtree_aabb(graph, root_graph_node_index);
+        assert_near(&aabb.min, &glm::vec3(8.0, 3.0, -1.0));
+        assert_near(&aabb.max, &glm::vec3(12.0, 7.0, 6.0));
+
+        let child_aabb = world.node_aabb(graph, child_graph_node_index);
+        assert_near(&child_aabb.min, &glm::vec3(9.0, 4.0, -1.0));
+        assert_near(&child_aabb.max, &glm::vec3(11.0, 6.0, 1.0));
+
+        assert!(world
+            .subtree_aabb(graph, petgraph::graph::NodeIndex::new(2))
+            .contains_point(&glm::vec3(10.0, 5.0, 5.5)));
+        assert!(world.node_aabb(graph, root_graph_node_index).is_empty());
+        let scene_aabb = world.scene_aabb(0);
+        assert_near(&scene_aabb.min, &aabb.min);
+        assert_near(&scene_aabb.max, &aabb.max);
+    }
 }